@@ -0,0 +1,229 @@
+//! Analytics across collections of MAEC packages
+//!
+//! These functions aggregate data from many packages at once, e.g. for
+//! reporting over a whole corpus of ingested threat intelligence.
+
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+
+use crate::{
+    BehaviorVocab, Capability, CapabilityVocab, DeliveryVector, MalwareActionVocab, MalwareLabel,
+    Package,
+};
+
+/// Collects the distinct malware family names across packages
+///
+/// Family names and their aliases are gathered from every package and
+/// deduplicated by their [normalized](crate::Name::normalized) form, which is
+/// also the form returned. Blank names are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{MalwareFamily, Name, Package};
+///
+/// let family = MalwareFamily::builder()
+///     .name(Name::new("WannaCry"))
+///     .add_alias(Name::new("WCry"))
+///     .build()
+///     .unwrap();
+/// let packages = vec![
+///     Package::builder().add_malware_family(family).build().unwrap(),
+///     Package::builder()
+///         .add_malware_family(MalwareFamily::new("wannacry"))
+///         .build()
+///         .unwrap(),
+/// ];
+///
+/// let names = maec::analytics::unique_family_names(&packages);
+/// assert_eq!(names.into_iter().collect::<Vec<_>>(), vec!["wannacry", "wcry"]);
+/// ```
+pub fn unique_family_names(packages: &[Package]) -> BTreeSet<String> {
+    packages
+        .iter()
+        .flat_map(|package| package.malware_families())
+        .flat_map(|family| std::iter::once(&family.name).chain(&family.aliases))
+        .map(|name| name.normalized())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Variants of one vocabulary split by whether a corpus uses them
+///
+/// Both lists are in declaration order of the vocabulary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantCoverage<T> {
+    /// Variants used at least once
+    pub seen: Vec<T>,
+    /// Variants never used
+    pub unseen: Vec<T>,
+}
+
+impl<T: Clone + Eq + Hash> VariantCoverage<T> {
+    fn from_used(all: &[T], used: &HashSet<T>) -> Self {
+        let (seen, unseen) = all.iter().cloned().partition(|v| used.contains(v));
+        Self { seen, unseen }
+    }
+}
+
+/// Usage of the MAEC vocabularies across a corpus, see [`vocabulary_coverage`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VocabCoverage {
+    /// Behavior names
+    pub behaviors: VariantCoverage<BehaviorVocab>,
+    /// Capability names of families and instances, including refinements
+    pub capabilities: VariantCoverage<CapabilityVocab>,
+    /// Malware action names
+    pub actions: VariantCoverage<MalwareActionVocab>,
+    /// Family and instance labels
+    pub labels: VariantCoverage<MalwareLabel>,
+    /// Delivery vectors in the field data of families and instances
+    pub delivery_vectors: VariantCoverage<DeliveryVector>,
+}
+
+/// Reports which vocabulary values a corpus uses and which it never does
+///
+/// Custom names, labels and delivery vectors outside the vocabularies are
+/// ignored. Vocabularies that no field of the object model carries, such as
+/// [`ObfuscationMethod`](crate::ObfuscationMethod) and
+/// [`EntityAssociation`](crate::EntityAssociation), are not reported.
+pub fn vocabulary_coverage(packages: &[Package]) -> VocabCoverage {
+    let mut behaviors = HashSet::new();
+    let mut capabilities = HashSet::new();
+    let mut actions = HashSet::new();
+    let mut labels = HashSet::new();
+    let mut delivery_vectors = HashSet::new();
+
+    for package in packages {
+        behaviors.extend(
+            package
+                .behaviors()
+                .into_iter()
+                .filter_map(|behavior| behavior.name.known().cloned()),
+        );
+        actions.extend(
+            package
+                .malware_actions()
+                .into_iter()
+                .map(|action| action.name.clone()),
+        );
+
+        let families = package.malware_families();
+        let instances = package.malware_instances();
+        let mut pending: Vec<&Capability> = families
+            .iter()
+            .flat_map(|family| &family.common_capabilities)
+            .chain(instances.iter().flat_map(|instance| &instance.capabilities))
+            .collect();
+        while let Some(capability) = pending.pop() {
+            pending.extend(&capability.refined_capabilities);
+            capabilities.extend(capability.typed_name());
+        }
+
+        labels.extend(
+            families
+                .iter()
+                .flat_map(|family| &family.labels)
+                .chain(instances.iter().flat_map(|instance| &instance.labels))
+                .filter_map(|label| label.parse::<MalwareLabel>().ok()),
+        );
+        delivery_vectors.extend(
+            families
+                .iter()
+                .filter_map(|family| family.field_data.as_ref())
+                .chain(
+                    instances
+                        .iter()
+                        .filter_map(|instance| instance.field_data.as_ref()),
+                )
+                .flat_map(|field_data| field_data.delivery_vectors.iter().flatten())
+                .filter_map(|vector| vector.parse::<DeliveryVector>().ok()),
+        );
+    }
+
+    VocabCoverage {
+        behaviors: VariantCoverage::from_used(BehaviorVocab::all(), &behaviors),
+        capabilities: VariantCoverage::from_used(CapabilityVocab::all(), &capabilities),
+        actions: VariantCoverage::from_used(MalwareActionVocab::all(), &actions),
+        labels: VariantCoverage::from_used(MalwareLabel::all(), &labels),
+        delivery_vectors: VariantCoverage::from_used(DeliveryVector::all(), &delivery_vectors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MalwareFamily, Name};
+
+    #[test]
+    fn test_unique_family_names() {
+        let emotet = MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .add_alias(Name::new(" Heodo "))
+            .build()
+            .unwrap();
+        let mut blank = MalwareFamily::new("Geodo");
+        blank.aliases.push(Name::new("   "));
+
+        let first = Package::builder()
+            .add_malware_family(emotet)
+            .build()
+            .unwrap();
+        let second = Package::builder()
+            .add_malware_family(MalwareFamily::new("EMOTET"))
+            .add_malware_family(blank)
+            .build()
+            .unwrap();
+
+        let names = unique_family_names(&[first, second]);
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec!["emotet", "geodo", "heodo"]
+        );
+        assert!(unique_family_names(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_vocabulary_coverage() {
+        let family = MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .add_label("backdoor")
+            .add_label("loader-ish")
+            .field_data(crate::FieldData::with_delivery_vectors(vec![
+                "phishing".to_string(),
+                "carrier-pigeon".to_string(),
+            ]))
+            .add_capability(Capability::tree(
+                "persistence",
+                vec![Capability::new("anti-detection")],
+            ))
+            .build()
+            .unwrap();
+        let package = Package::builder()
+            .add_malware_family(family)
+            .add_behavior(crate::Behavior::new(BehaviorVocab::SendBeacon))
+            .build()
+            .unwrap();
+
+        let coverage = vocabulary_coverage(&[package]);
+        assert_eq!(coverage.behaviors.seen, vec![BehaviorVocab::SendBeacon]);
+        assert_eq!(
+            coverage.behaviors.seen.len() + coverage.behaviors.unseen.len(),
+            BehaviorVocab::all().len()
+        );
+        assert_eq!(
+            coverage.capabilities.seen,
+            vec![CapabilityVocab::AntiDetection, CapabilityVocab::Persistence]
+        );
+        assert_eq!(coverage.labels.seen, vec![MalwareLabel::Backdoor]);
+        assert!(coverage.actions.seen.is_empty());
+        assert_eq!(
+            coverage.delivery_vectors.seen,
+            vec![DeliveryVector::Phishing]
+        );
+
+        let empty = vocabulary_coverage(&[]);
+        assert_eq!(empty.labels.unseen, MalwareLabel::all());
+        assert_eq!(empty.delivery_vectors.unseen, DeliveryVector::all());
+    }
+}
@@ -1,373 +1,1294 @@
-//! Common MAEC types and utilities
-//!
-//! This module provides core types shared across all MAEC objects, including
-//! common properties, traits, and ID generation/validation helpers.
-
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use uuid::Uuid;
-
-fn default_now() -> DateTime<Utc> {
-    Utc::now()
-}
-
-fn default_version() -> Option<String> {
-    Some("5.0".to_string())
-}
-
-/// Trait implemented by all MAEC objects for basic accessors
-pub trait MaecObject {
-    /// Returns the unique identifier of the object
-    fn id(&self) -> &str;
-
-    /// Returns the type of the MAEC object (e.g., "package", "malware-family")
-    fn type_(&self) -> &str;
-
-    /// Returns when the object was created
-    fn created(&self) -> DateTime<Utc>;
-}
-
-/// Common properties shared by MAEC top-level objects
-///
-/// These properties are flattened into each MAEC object type via serde,
-/// providing consistent ID generation, timestamping, and metadata.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub struct CommonProperties {
-    /// The type of MAEC object (e.g., "package", "malware-family")
-    #[serde(rename = "type")]
-    pub r#type: String,
-
-    /// Unique identifier for this object (format: "type--uuid")
-    pub id: String,
-
-    /// MAEC specification version (should be "5.0")
-    #[serde(default = "default_version", skip_serializing_if = "Option::is_none")]
-    pub schema_version: Option<String>,
-
-    /// Timestamp when the object was created
-    #[serde(default = "default_now")]
-    pub created: DateTime<Utc>,
-
-    /// Timestamp when the object was last modified
-    #[serde(default = "default_now")]
-    pub modified: DateTime<Utc>,
-
-    /// Reference to the identity that created this object
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_by_ref: Option<String>,
-
-    /// Custom properties for extensions
-    #[serde(flatten)]
-    pub custom_properties: HashMap<String, serde_json::Value>,
-}
-
-impl Default for CommonProperties {
-    fn default() -> Self {
-        let now = Utc::now();
-        Self {
-            r#type: String::new(),
-            id: generate_maec_id("object"),
-            schema_version: Some("5.0".to_string()),
-            created: now,
-            modified: now,
-            created_by_ref: None,
-            custom_properties: HashMap::new(),
-        }
-    }
-}
-
-impl CommonProperties {
-    /// Creates a new CommonProperties instance
-    ///
-    /// # Arguments
-    ///
-    /// * `object_type` - The MAEC object type (e.g., "package", "malware-family")
-    /// * `created_by_ref` - Optional reference to the creating identity
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use maec::common::CommonProperties;
-    ///
-    /// let common = CommonProperties::new("malware-family", None);
-    /// assert_eq!(common.r#type, "malware-family");
-    /// assert_eq!(common.schema_version, Some("5.0".to_string()));
-    /// ```
-    pub fn new(object_type: impl Into<String>, created_by_ref: Option<String>) -> Self {
-        let object_type = object_type.into();
-        let now = Utc::now();
-        Self {
-            r#type: object_type.clone(),
-            id: generate_maec_id(&object_type),
-            schema_version: Some("5.0".to_string()),
-            created: now,
-            modified: now,
-            created_by_ref,
-            custom_properties: HashMap::new(),
-        }
-    }
-
-    /// Creates a new version of this object by updating the modified timestamp
-    ///
-    /// In MAEC (like STIX), when you update an object, you keep the same ID
-    /// and created timestamp but update the modified timestamp to indicate
-    /// a new version.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use maec::common::CommonProperties;
-    /// use std::thread;
-    /// use std::time::Duration;
-    ///
-    /// let mut common = CommonProperties::new("malware-family", None);
-    /// let original_modified = common.modified;
-    ///
-    /// thread::sleep(Duration::from_millis(10));
-    /// common.new_version();
-    ///
-    /// assert!(common.modified > original_modified);
-    /// assert_eq!(common.created, original_modified); // created unchanged
-    /// ```
-    pub fn new_version(&mut self) {
-        self.modified = Utc::now();
-    }
-}
-
-impl MaecObject for CommonProperties {
-    fn id(&self) -> &str {
-        &self.id
-    }
-
-    fn type_(&self) -> &str {
-        &self.r#type
-    }
-
-    fn created(&self) -> DateTime<Utc> {
-        self.created
-    }
-}
-
-/// Generates a unique MAEC identifier
-///
-/// MAEC IDs follow the format: `{object-type}--{uuid}`
-///
-/// # Examples
-///
-/// ```
-/// use maec::common::generate_maec_id;
-///
-/// let id = generate_maec_id("malware-family");
-/// assert!(id.starts_with("malware-family--"));
-/// ```
-pub fn generate_maec_id(object_type: &str) -> String {
-    format!("{}--{}", object_type, Uuid::new_v4())
-}
-
-/// Validates that a string is a valid MAEC identifier
-///
-/// MAEC IDs must follow the format: `{object-type}--{uuid}`
-///
-/// # Examples
-///
-/// ```
-/// use maec::common::is_valid_maec_id;
-///
-/// assert!(is_valid_maec_id("malware-family--12345678-1234-1234-1234-123456789abc"));
-/// assert!(is_valid_maec_id("package--550e8400-e29b-41d4-a716-446655440000"));
-/// assert!(!is_valid_maec_id("invalid"));
-/// assert!(!is_valid_maec_id("malware-family-bad-uuid"));
-/// ```
-pub fn is_valid_maec_id(id: &str) -> bool {
-    let parts: Vec<&str> = id.split("--").collect();
-    if parts.len() != 2 {
-        return false;
-    }
-
-    // Validate the UUID part
-    Uuid::parse_str(parts[1]).is_ok()
-}
-
-/// Extracts the object type from a MAEC ID
-///
-/// # Examples
-///
-/// ```
-/// use maec::common::extract_type_from_id;
-///
-/// assert_eq!(
-///     extract_type_from_id("malware-family--12345678-1234-1234-1234-123456789abc"),
-///     Some("malware-family")
-/// );
-/// assert_eq!(extract_type_from_id("invalid"), None);
-/// ```
-pub fn extract_type_from_id(id: &str) -> Option<&str> {
-    let parts: Vec<&str> = id.split("--").collect();
-    if parts.len() == 2 && Uuid::parse_str(parts[1]).is_ok() {
-        Some(parts[0])
-    } else {
-        None
-    }
-}
-
-/// Validates that a reference ID matches the expected object type
-///
-/// # Examples
-///
-/// ```
-/// use maec::common::is_valid_ref_for_type;
-///
-/// assert!(is_valid_ref_for_type(
-///     "malware-family--12345678-1234-1234-1234-123456789abc",
-///     "malware-family"
-/// ));
-/// assert!(!is_valid_ref_for_type(
-///     "package--12345678-1234-1234-1234-123456789abc",
-///     "malware-family"
-/// ));
-/// ```
-pub fn is_valid_ref_for_type(id: &str, expected_type: &str) -> bool {
-    extract_type_from_id(id)
-        .map(|t| t == expected_type)
-        .unwrap_or(false)
-}
-
-/// External Reference - Links to external resources
-///
-/// Used to reference external sources like ATT&CK techniques, CVEs,
-/// or research papers related to MAEC objects.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub struct ExternalReference {
-    /// Name of the source (e.g., "mitre-attack", "cve")
-    pub source_name: String,
-
-    /// Description of the reference
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-
-    /// URL to the external resource
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-
-    /// External identifier (e.g., "T1055" for ATT&CK)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub external_id: Option<String>,
-}
-
-impl ExternalReference {
-    /// Creates a new external reference with just a source name
-    pub fn new(source_name: impl Into<String>) -> Self {
-        Self {
-            source_name: source_name.into(),
-            description: None,
-            url: None,
-            external_id: None,
-        }
-    }
-
-    /// Creates an ATT&CK technique reference
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use maec::common::ExternalReference;
-    ///
-    /// let technique = ExternalReference::attack_technique("T1055", "Process Injection");
-    /// assert_eq!(technique.source_name, "mitre-attack");
-    /// assert_eq!(technique.external_id, Some("T1055".to_string()));
-    /// ```
-    pub fn attack_technique(technique_id: impl Into<String>, name: impl Into<String>) -> Self {
-        let technique_id = technique_id.into();
-        Self {
-            source_name: "mitre-attack".to_string(),
-            description: Some(name.into()),
-            url: Some(format!(
-                "https://attack.mitre.org/techniques/{}",
-                technique_id
-            )),
-            external_id: Some(technique_id),
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_generate_maec_id() {
-        let id = generate_maec_id("malware-family");
-        assert!(id.starts_with("malware-family--"));
-        assert!(is_valid_maec_id(&id));
-    }
-
-    #[test]
-    fn test_is_valid_maec_id() {
-        assert!(is_valid_maec_id(
-            "malware-family--550e8400-e29b-41d4-a716-446655440000"
-        ));
-        assert!(is_valid_maec_id(
-            "package--12345678-1234-1234-1234-123456789abc"
-        ));
-        assert!(!is_valid_maec_id("invalid"));
-        assert!(!is_valid_maec_id("malware-family"));
-        assert!(!is_valid_maec_id("malware-family-no-uuid"));
-    }
-
-    #[test]
-    fn test_extract_type_from_id() {
-        assert_eq!(
-            extract_type_from_id("malware-family--550e8400-e29b-41d4-a716-446655440000"),
-            Some("malware-family")
-        );
-        assert_eq!(
-            extract_type_from_id("package--12345678-1234-1234-1234-123456789abc"),
-            Some("package")
-        );
-        assert_eq!(extract_type_from_id("invalid"), None);
-    }
-
-    #[test]
-    fn test_is_valid_ref_for_type() {
-        assert!(is_valid_ref_for_type(
-            "malware-family--550e8400-e29b-41d4-a716-446655440000",
-            "malware-family"
-        ));
-        assert!(!is_valid_ref_for_type(
-            "package--550e8400-e29b-41d4-a716-446655440000",
-            "malware-family"
-        ));
-    }
-
-    #[test]
-    fn test_common_properties_new() {
-        let common = CommonProperties::new("malware-family", None);
-        assert_eq!(common.r#type, "malware-family");
-        assert_eq!(common.schema_version, Some("5.0".to_string()));
-        assert!(common.id.starts_with("malware-family--"));
-    }
-
-    #[test]
-    fn test_new_version() {
-        let mut common = CommonProperties::new("malware-family", None);
-        let original_created = common.created;
-        let original_modified = common.modified;
-
-        std::thread::sleep(std::time::Duration::from_millis(10));
-        common.new_version();
-
-        assert_eq!(common.created, original_created);
-        assert!(common.modified > original_modified);
-    }
-
-    #[test]
-    fn test_external_reference_attack() {
-        let ref_obj = ExternalReference::attack_technique("T1055", "Process Injection");
-        assert_eq!(ref_obj.source_name, "mitre-attack");
-        assert_eq!(ref_obj.external_id, Some("T1055".to_string()));
-        assert!(ref_obj.url.unwrap().contains("T1055"));
-    }
-}
+//! Common MAEC types and utilities
+//!
+//! This module provides core types shared across all MAEC objects, including
+//! common properties, traits, and ID generation/validation helpers.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::vocab::ConfidenceMeasure;
+
+/// Custom property holding an object-level confidence
+pub(crate) const CONFIDENCE_PROPERTY: &str = "x_maec_confidence";
+
+fn default_now() -> DateTime<Utc> {
+    Utc::now()
+}
+
+fn default_version() -> Option<String> {
+    Some("5.0".to_string())
+}
+
+/// MAEC schema versions this crate can produce
+///
+/// Used to gate version-dependent features, e.g. by
+/// [`Package::downgrade_to`](crate::Package::downgrade_to).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaVersion {
+    /// MAEC 5.0
+    V5_0,
+}
+
+impl SchemaVersion {
+    /// All supported versions, oldest first
+    pub const ALL: &'static [SchemaVersion] = &[SchemaVersion::V5_0];
+
+    /// Parses a `schema_version` value such as `"5.0"`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "5.0" => Some(SchemaVersion::V5_0),
+            _ => None,
+        }
+    }
+}
+
+impl AsRef<str> for SchemaVersion {
+    fn as_ref(&self) -> &str {
+        match self {
+            SchemaVersion::V5_0 => "5.0",
+        }
+    }
+}
+
+/// Trait implemented by all MAEC objects for basic accessors
+pub trait MaecObject {
+    /// Returns the unique identifier of the object
+    fn id(&self) -> &str;
+
+    /// Returns the type of the MAEC object (e.g., "package", "malware-family")
+    fn type_(&self) -> &str;
+
+    /// Returns when the object was created
+    fn created(&self) -> DateTime<Utc>;
+}
+
+/// Common properties shared by MAEC top-level objects
+///
+/// These properties are flattened into each MAEC object type via serde,
+/// providing consistent ID generation, timestamping, and metadata.
+///
+/// `Hash` skips `id`, `created` and `modified`. This agrees with the derived
+/// `Eq`, which compares every field, and lets copies that differ only in
+/// identity land in the same bucket. Wrap objects in a [`ContentKey`] to have
+/// such copies compare equal as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct CommonProperties {
+    /// The type of MAEC object (e.g., "package", "malware-family")
+    #[serde(rename = "type")]
+    pub r#type: String,
+
+    /// Unique identifier for this object (format: "type--uuid")
+    pub id: String,
+
+    /// MAEC specification version (should be "5.0")
+    #[serde(
+        default = "default_version",
+        skip_serializing_if = "Option::is_none",
+        alias = "schemaVersion"
+    )]
+    pub schema_version: Option<String>,
+
+    /// Timestamp when the object was created
+    #[serde(default = "default_now")]
+    pub created: DateTime<Utc>,
+
+    /// Timestamp when the object was last modified
+    #[serde(default = "default_now")]
+    pub modified: DateTime<Utc>,
+
+    /// Reference to the identity that created this object
+    #[serde(skip_serializing_if = "Option::is_none", alias = "createdByRef")]
+    pub created_by_ref: Option<String>,
+
+    /// Custom properties for extensions
+    #[serde(flatten)]
+    pub custom_properties: BTreeMap<String, serde_json::Value>,
+}
+
+impl std::hash::Hash for CommonProperties {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.r#type.hash(state);
+        self.schema_version.hash(state);
+        self.created_by_ref.hash(state);
+        self.custom_properties.hash(state);
+    }
+}
+
+impl Default for CommonProperties {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self {
+            r#type: String::new(),
+            id: generate_maec_id("object"),
+            schema_version: Some("5.0".to_string()),
+            created: now,
+            modified: now,
+            created_by_ref: None,
+            custom_properties: BTreeMap::new(),
+        }
+    }
+}
+
+impl CommonProperties {
+    /// Creates a new CommonProperties instance
+    ///
+    /// # Arguments
+    ///
+    /// * `object_type` - The MAEC object type (e.g., "package", "malware-family")
+    /// * `created_by_ref` - Optional reference to the creating identity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::common::CommonProperties;
+    ///
+    /// let common = CommonProperties::new("malware-family", None);
+    /// assert_eq!(common.r#type, "malware-family");
+    /// assert_eq!(common.schema_version, Some("5.0".to_string()));
+    /// ```
+    pub fn new(object_type: impl Into<String>, created_by_ref: Option<String>) -> Self {
+        let object_type = object_type.into();
+        let now = Utc::now();
+        Self {
+            r#type: object_type.clone(),
+            id: generate_maec_id(&object_type),
+            schema_version: Some("5.0".to_string()),
+            created: now,
+            modified: now,
+            created_by_ref,
+            custom_properties: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a new version of this object by updating the modified timestamp
+    ///
+    /// In MAEC (like STIX), when you update an object, you keep the same ID
+    /// and created timestamp but update the modified timestamp to indicate
+    /// a new version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::common::CommonProperties;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let mut common = CommonProperties::new("malware-family", None);
+    /// let original_modified = common.modified;
+    ///
+    /// thread::sleep(Duration::from_millis(10));
+    /// common.new_version();
+    ///
+    /// assert!(common.modified > original_modified);
+    /// assert_eq!(common.created, original_modified); // created unchanged
+    /// ```
+    pub fn new_version(&mut self) {
+        self.modified = Utc::now();
+    }
+
+    /// Checks that `modified` is not earlier than `created`
+    ///
+    /// Returns `MaecError::ValidationError` naming the object otherwise.
+    pub fn validate_timestamps(&self) -> crate::error::Result<()> {
+        if self.modified < self.created {
+            return Err(crate::error::MaecError::ValidationError(format!(
+                "'{}' was modified ({}) before it was created ({})",
+                self.id,
+                self.modified.to_rfc3339(),
+                self.created.to_rfc3339()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Parses `schema_version` into `(major, minor, patch)` components
+    ///
+    /// Missing components count as zero, so `"5.0"` is `(5, 0, 0)`. Returns
+    /// `None` if there is no schema version or it is not of the form
+    /// `major[.minor[.patch]]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::common::CommonProperties;
+    ///
+    /// let mut common = CommonProperties::new("package", None);
+    /// assert_eq!(common.schema_semver(), Some((5, 0, 0)));
+    ///
+    /// common.schema_version = Some("5.x".to_string());
+    /// assert_eq!(common.schema_semver(), None);
+    /// ```
+    pub fn schema_semver(&self) -> Option<(u64, u64, u64)> {
+        let mut parts = self.schema_version.as_deref()?.trim().split('.');
+        let mut next = |required: bool| match parts.next() {
+            Some(part) => part.parse::<u64>().ok(),
+            None if required => None,
+            None => Some(0),
+        };
+        let version = (next(true)?, next(false)?, next(false)?);
+        parts.next().is_none().then_some(version)
+    }
+
+    /// Returns the `created` timestamp as an RFC 3339 string in UTC
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::common::CommonProperties;
+    ///
+    /// let mut common = CommonProperties::new("malware-family", None);
+    /// common.set_created_rfc3339("2017-05-12T07:44:00.5Z").unwrap();
+    /// assert_eq!(common.created_rfc3339(), "2017-05-12T07:44:00.500Z");
+    /// ```
+    pub fn created_rfc3339(&self) -> String {
+        format_rfc3339(self.created)
+    }
+
+    /// Returns the `modified` timestamp as an RFC 3339 string in UTC
+    pub fn modified_rfc3339(&self) -> String {
+        format_rfc3339(self.modified)
+    }
+
+    /// Sets the `created` timestamp from an RFC 3339 string
+    ///
+    /// Returns `MaecError::ValidationError` if the string cannot be parsed.
+    pub fn set_created_rfc3339(&mut self, timestamp: &str) -> crate::error::Result<()> {
+        self.created = parse_rfc3339(timestamp)?;
+        Ok(())
+    }
+
+    /// Sets the `modified` timestamp from an RFC 3339 string
+    ///
+    /// Returns `MaecError::ValidationError` if the string cannot be parsed.
+    pub fn set_modified_rfc3339(&mut self, timestamp: &str) -> crate::error::Result<()> {
+        self.modified = parse_rfc3339(timestamp)?;
+        Ok(())
+    }
+
+    /// Reads the confidence stored under `x_maec_confidence`, if any
+    pub(crate) fn confidence(&self) -> Option<ConfidenceMeasure> {
+        self.custom_properties
+            .get(CONFIDENCE_PROPERTY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Stores a confidence under `x_maec_confidence`
+    pub(crate) fn set_confidence(&mut self, confidence: ConfidenceMeasure) {
+        self.custom_properties.insert(
+            CONFIDENCE_PROPERTY.to_string(),
+            serde_json::to_value(confidence).expect("vocabulary values serialize"),
+        );
+    }
+}
+
+impl MaecObject for CommonProperties {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn type_(&self) -> &str {
+        &self.r#type
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+}
+
+/// Formats a timestamp in the canonical MAEC representation
+///
+/// Sub-second digits are emitted in groups of three as needed and the
+/// offset is always written as `Z`.
+fn format_rfc3339(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_rfc3339(timestamp: &str) -> crate::error::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|parsed| parsed.with_timezone(&Utc))
+        .map_err(|e| {
+            crate::error::MaecError::ValidationError(format!(
+                "invalid RFC 3339 timestamp '{}': {}",
+                timestamp, e
+            ))
+        })
+}
+
+/// Generates a unique MAEC identifier
+///
+/// MAEC IDs follow the format: `{object-type}--{uuid}`
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::generate_maec_id;
+///
+/// let id = generate_maec_id("malware-family");
+/// assert!(id.starts_with("malware-family--"));
+/// ```
+pub fn generate_maec_id(object_type: &str) -> String {
+    format!("{}--{}", object_type, Uuid::new_v4())
+}
+
+/// Namespace for name-based (UUIDv5) MAEC identifiers
+///
+/// This fixed UUID, `6f1d3b0e-8a4c-4f62-9d3e-2b7a5c9e1f04`, is the default
+/// namespace of [`generate_maec_id_v5_default`]. Because it is shared by all
+/// users of the crate, identical inputs produce identical IDs everywhere,
+/// which lets independently produced packages refer to the same objects.
+pub const MAEC_NAMESPACE: Uuid = Uuid::from_u128(0x6f1d3b0e_8a4c_4f62_9d3e_2b7a5c9e1f04);
+
+/// Generates a deterministic MAEC identifier from a name
+///
+/// The UUID part is the UUIDv5 of `name` within `namespace`, so the same
+/// `object_type`, `namespace` and `name` always produce the same ID. Use
+/// [`MAEC_NAMESPACE`] for IDs meant to agree across producers.
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::{generate_maec_id_v5, MAEC_NAMESPACE};
+///
+/// let a = generate_maec_id_v5("malware-instance", &MAEC_NAMESPACE, "sha256:3f79bb7b");
+/// let b = generate_maec_id_v5("malware-instance", &MAEC_NAMESPACE, "sha256:3f79bb7b");
+/// assert_eq!(a, b);
+/// assert!(maec::is_valid_maec_id(&a));
+/// ```
+pub fn generate_maec_id_v5(object_type: &str, namespace: &Uuid, name: &str) -> String {
+    format!(
+        "{}--{}",
+        object_type,
+        Uuid::new_v5(namespace, name.as_bytes())
+    )
+}
+
+/// Generates a deterministic MAEC identifier in the [`MAEC_NAMESPACE`]
+///
+/// Equivalent to `generate_maec_id_v5(object_type, &MAEC_NAMESPACE, name)`.
+pub fn generate_maec_id_v5_default(object_type: &str, name: &str) -> String {
+    generate_maec_id_v5(object_type, &MAEC_NAMESPACE, name)
+}
+
+/// Validates that a string is a valid MAEC identifier
+///
+/// MAEC IDs must follow the format: `{object-type}--{uuid}`
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::is_valid_maec_id;
+///
+/// assert!(is_valid_maec_id("malware-family--12345678-1234-1234-1234-123456789abc"));
+/// assert!(is_valid_maec_id("package--550e8400-e29b-41d4-a716-446655440000"));
+/// assert!(!is_valid_maec_id("invalid"));
+/// assert!(!is_valid_maec_id("malware-family-bad-uuid"));
+/// ```
+pub fn is_valid_maec_id(id: &str) -> bool {
+    let parts: Vec<&str> = id.split("--").collect();
+    if parts.len() != 2 {
+        return false;
+    }
+
+    // Validate the UUID part
+    Uuid::parse_str(parts[1]).is_ok()
+}
+
+/// Extracts the object type from a MAEC ID
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::extract_type_from_id;
+///
+/// assert_eq!(
+///     extract_type_from_id("malware-family--12345678-1234-1234-1234-123456789abc"),
+///     Some("malware-family")
+/// );
+/// assert_eq!(extract_type_from_id("invalid"), None);
+/// ```
+pub fn extract_type_from_id(id: &str) -> Option<&str> {
+    let parts: Vec<&str> = id.split("--").collect();
+    if parts.len() == 2 && Uuid::parse_str(parts[1]).is_ok() {
+        Some(parts[0])
+    } else {
+        None
+    }
+}
+
+/// Validates that a reference ID matches the expected object type
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::is_valid_ref_for_type;
+///
+/// assert!(is_valid_ref_for_type(
+///     "malware-family--12345678-1234-1234-1234-123456789abc",
+///     "malware-family"
+/// ));
+/// assert!(!is_valid_ref_for_type(
+///     "package--12345678-1234-1234-1234-123456789abc",
+///     "malware-family"
+/// ));
+/// ```
+pub fn is_valid_ref_for_type(id: &str, expected_type: &str) -> bool {
+    extract_type_from_id(id)
+        .map(|t| t == expected_type)
+        .unwrap_or(false)
+}
+
+/// Serializes a JSON value into a canonical, byte-stable string
+///
+/// The output does not depend on map insertion order or on the formatting
+/// choices of a particular serde_json version, making it suitable as input to
+/// hashing and signing:
+/// - object keys are sorted (by their UTF-8 bytes) at every level
+/// - no insignificant whitespace is emitted
+/// - integers, and floats with no fractional part, are written as integers;
+///   other floats use Rust's shortest round-trip representation
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::canonical_json;
+/// use serde_json::json;
+///
+/// let value = json!({"b": [1.0, 2.5], "a": {"z": null, "y": true}});
+/// assert_eq!(canonical_json(&value), r#"{"a":{"y":true,"z":null},"b":[1,2.5]}"#);
+/// ```
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out);
+    out
+}
+
+/// Orders two values by their canonical JSON encoding
+///
+/// Used as the final tiebreak of the canonicalization orderings, so that
+/// values comparing equal are also equal field by field.
+pub(crate) fn canonical_cmp<T: Serialize>(a: &T, b: &T) -> std::cmp::Ordering {
+    let encode = |value: &T| {
+        serde_json::to_value(value)
+            .map(|value| canonical_json(&value))
+            .unwrap_or_default()
+    };
+    encode(a).cmp(&encode(b))
+}
+
+fn write_canonical_json(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Null => out.push_str("null"),
+        serde_json::Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        serde_json::Value::Number(n) => out.push_str(&canonical_number(n)),
+        serde_json::Value::String(s) => write_canonical_string(s, out),
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_string(key, out);
+                out.push(':');
+                write_canonical_json(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn canonical_number(n: &serde_json::Number) -> String {
+    if let Some(i) = n.as_i64() {
+        return i.to_string();
+    }
+    if let Some(u) = n.as_u64() {
+        return u.to_string();
+    }
+
+    // `Display` for f64 never uses exponents and drops a zero fraction; only
+    // negative zero needs normalizing
+    let f = n.as_f64().unwrap_or_default();
+    if f == 0.0 {
+        "0".to_string()
+    } else {
+        f.to_string()
+    }
+}
+
+/// Writes a JSON string literal, escaping only what RFC 8259 requires
+fn write_canonical_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Converts a JSON object into an attribute map
+///
+/// Returns `MaecError::ValidationError` if the value is not a JSON object.
+pub(crate) fn attributes_from_value(
+    value: serde_json::Value,
+) -> crate::error::Result<BTreeMap<String, serde_json::Value>> {
+    match value {
+        serde_json::Value::Object(map) => Ok(map.into_iter().collect()),
+        other => Err(crate::error::MaecError::ValidationError(format!(
+            "attributes must be a JSON object, got {}",
+            other
+        ))),
+    }
+}
+
+/// Maximum nesting depth of attribute and custom property values accepted by
+/// deep validation
+pub const DEFAULT_MAX_VALUE_DEPTH: usize = 32;
+
+/// Maximum serialized size, in bytes, of attribute and custom property values
+/// accepted by deep validation
+pub const DEFAULT_MAX_VALUE_SIZE: usize = 1 << 20;
+
+/// Checks that a JSON value is not nested too deeply and not too large
+///
+/// Depth counts nested arrays and objects: scalars have depth 0 and `{}` or
+/// `[]` depth 1. Size is the length in bytes of the compact JSON encoding.
+/// Returns `MaecError::ValidationError` naming the offending JSON pointer
+/// path within `value` (the empty path denotes `value` itself).
+///
+/// # Examples
+///
+/// ```
+/// use maec::common::validate_value_limits;
+/// use serde_json::json;
+///
+/// let value = json!({"a": [{"b": 1}]});
+/// assert!(validate_value_limits(&value, 3, 1024).is_ok());
+///
+/// let err = validate_value_limits(&value, 2, 1024).unwrap_err();
+/// assert!(err.to_string().contains("'/a/0'"));
+/// ```
+pub fn validate_value_limits(
+    value: &serde_json::Value,
+    max_depth: usize,
+    max_size: usize,
+) -> crate::error::Result<()> {
+    check_value_limits(value, "", max_depth, max_size)
+}
+
+/// Like [`validate_value_limits`], reporting paths below `base`
+pub(crate) fn check_value_limits(
+    value: &serde_json::Value,
+    base: &str,
+    max_depth: usize,
+    max_size: usize,
+) -> crate::error::Result<()> {
+    let size = serde_json::to_vec(value)?.len();
+    if size > max_size {
+        return Err(crate::error::MaecError::ValidationError(format!(
+            "value at '{}' is {} bytes, exceeding the maximum of {}",
+            base, size, max_size
+        )));
+    }
+
+    let mut pending = vec![(value, base.to_string(), 0)];
+    while let Some((value, path, depth)) = pending.pop() {
+        let children: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (i.to_string(), item))
+                .collect(),
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(key, item)| (escape_pointer_token(key), item))
+                .collect(),
+            _ => continue,
+        };
+        if depth + 1 > max_depth {
+            return Err(crate::error::MaecError::ValidationError(format!(
+                "value at '{}' exceeds the maximum nesting depth of {}",
+                path, max_depth
+            )));
+        }
+        for (segment, child) in children {
+            pending.push((child, format!("{}/{}", path, segment), depth + 1));
+        }
+    }
+    Ok(())
+}
+
+/// Options controlling how MAEC objects are serialized by `to_json_with`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Custom property keys starting with any of these prefixes are omitted
+    ///
+    /// Applies to the custom properties of the serialized object and every
+    /// nested MAEC object, whatever their keys look like. Standard fields and
+    /// the contents of `observable_objects` are always kept.
+    pub strip_custom_prefixes: Vec<String>,
+}
+
+impl SerializeOptions {
+    /// Creates options that strip custom properties with the given key prefixes
+    pub fn strip_custom_prefixes<I, S>(prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            strip_custom_prefixes: prefixes.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Strips the matching custom properties of one object
+    pub(crate) fn apply(&self, common: &mut CommonProperties) {
+        common.custom_properties.retain(|key, _| {
+            !self
+                .strip_custom_prefixes
+                .iter()
+                .any(|prefix| key.starts_with(prefix.as_str()))
+        });
+    }
+}
+
+/// Objects owning the common properties of themselves and any nested objects
+pub(crate) trait CommonPropertiesOwner {
+    /// Calls `f` with every set of common properties the object owns
+    fn for_each_common_mut(&mut self, f: &mut dyn FnMut(&mut CommonProperties));
+}
+
+macro_rules! impl_common_properties_owner {
+    ($($name:ty),* $(,)?) => {
+        $(
+            impl CommonPropertiesOwner for $name {
+                fn for_each_common_mut(&mut self, f: &mut dyn FnMut(&mut CommonProperties)) {
+                    f(&mut self.common);
+                }
+            }
+        )*
+    };
+}
+
+impl_common_properties_owner!(
+    crate::Behavior,
+    crate::Collection,
+    crate::MalwareAction,
+    crate::MalwareFamily,
+    crate::MalwareInstance,
+    crate::Relationship,
+);
+
+/// Encodes a single JSON pointer reference token (RFC 6901)
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Decodes a single JSON pointer reference token (RFC 6901)
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Writes `value` at the location identified by a JSON pointer
+///
+/// The parent of the target location must already exist. Object members are
+/// inserted or replaced; array elements may be replaced by index or appended
+/// using the `-` token.
+pub(crate) fn set_json_pointer(
+    root: &mut serde_json::Value,
+    pointer: &str,
+    value: serde_json::Value,
+) -> crate::error::Result<()> {
+    if pointer.is_empty() {
+        *root = value;
+        return Ok(());
+    }
+
+    let invalid = || {
+        crate::error::MaecError::ValidationError(format!(
+            "JSON pointer '{}' does not identify a settable location",
+            pointer
+        ))
+    };
+
+    let (parent_pointer, token) = pointer.rsplit_once('/').ok_or_else(invalid)?;
+    let token = unescape_pointer_token(token);
+    let parent = root.pointer_mut(parent_pointer).ok_or_else(invalid)?;
+
+    match parent {
+        serde_json::Value::Object(map) => {
+            map.insert(token, value);
+            Ok(())
+        }
+        serde_json::Value::Array(items) if token == "-" => {
+            items.push(value);
+            Ok(())
+        }
+        serde_json::Value::Array(items) => {
+            let index: usize = token.parse().map_err(|_| invalid())?;
+            match index.cmp(&items.len()) {
+                std::cmp::Ordering::Less => items[index] = value,
+                std::cmp::Ordering::Equal => items.push(value),
+                std::cmp::Ordering::Greater => return Err(invalid()),
+            }
+            Ok(())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Applies a JSON Merge Patch (RFC 7396) to `target`
+///
+/// Object members of the patch are merged recursively, `null` removes a
+/// member, and any other value replaces the target wholesale. Members the
+/// patch does not mention are left untouched.
+pub(crate) fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(map) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                map.remove(key);
+            } else {
+                merge_patch(
+                    map.entry(key.clone()).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+    }
+}
+
+/// Implements inherent JSON helpers shared by the MAEC object types
+///
+/// The target type must implement `Serialize`, `Deserialize` and provide an
+/// inherent `validate(&self) -> Result<()>` method, which also backs its
+/// [`Validate`](crate::Validate) implementation.
+macro_rules! impl_json_methods {
+    ($ty:ty) => {
+        impl $crate::valid::Validate for $ty {
+            fn validate(&self) -> $crate::error::Result<()> {
+                <$ty>::validate(self)
+            }
+        }
+
+        impl $ty {
+            /// Serializes the object to a compact JSON string
+            pub fn to_json(&self) -> $crate::error::Result<String> {
+                Ok(serde_json::to_string(self)?)
+            }
+
+            /// Serializes the object to a pretty-printed JSON string
+            pub fn to_json_pretty(&self) -> $crate::error::Result<String> {
+                Ok(serde_json::to_string_pretty(self)?)
+            }
+
+            /// Serializes the object to a compact JSON string using the given options
+            ///
+            /// The in-memory object is not modified.
+            pub fn to_json_with(
+                &self,
+                options: &$crate::common::SerializeOptions,
+            ) -> $crate::error::Result<String> {
+                if options.strip_custom_prefixes.is_empty() {
+                    return Ok(serde_json::to_string(self)?);
+                }
+                let mut object = self.clone();
+                $crate::common::CommonPropertiesOwner::for_each_common_mut(
+                    &mut object,
+                    &mut |common| options.apply(common),
+                );
+                Ok(serde_json::to_string(&object)?)
+            }
+
+            /// Deserializes and validates an object from a JSON string
+            pub fn from_json(json: &str) -> $crate::error::Result<Self> {
+                let object: Self = serde_json::from_str(json)?;
+                object.validate()?;
+                Ok(object)
+            }
+
+            /// Returns the value at a JSON pointer (RFC 6901) into the serialized object
+            ///
+            /// Returns `None` if the pointer does not resolve.
+            pub fn get_pointer(&self, pointer: &str) -> Option<serde_json::Value> {
+                serde_json::to_value(self).ok()?.pointer(pointer).cloned()
+            }
+
+            /// Sets the value at a JSON pointer (RFC 6901) into the serialized object
+            ///
+            /// The object is serialized, modified, deserialized and validated; on
+            /// any error it is left unchanged.
+            pub fn set_pointer(
+                &mut self,
+                pointer: &str,
+                value: serde_json::Value,
+            ) -> $crate::error::Result<()> {
+                let mut serialized = serde_json::to_value(&*self)?;
+                $crate::common::set_json_pointer(&mut serialized, pointer, value)?;
+                let updated: Self = serde_json::from_value(serialized)?;
+                updated.validate()?;
+                *self = updated;
+                Ok(())
+            }
+
+            /// Applies a JSON Merge Patch (RFC 7396) to the serialized object
+            ///
+            /// Properties the patch does not mention are kept, including custom
+            /// properties this crate does not know about; a `null` member removes
+            /// a property. The object is serialized, patched, deserialized and
+            /// validated; on any error it is left unchanged. Like `update`, a
+            /// successful patch records a new version by setting `modified` to
+            /// now.
+            pub fn apply_patch(&mut self, patch: &serde_json::Value) -> $crate::error::Result<()> {
+                let mut serialized = serde_json::to_value(&*self)?;
+                $crate::common::merge_patch(&mut serialized, patch);
+                let mut updated: Self = serde_json::from_value(serialized)?;
+                updated.common.new_version();
+                updated.validate()?;
+                *self = updated;
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use impl_json_methods;
+
+/// Wraps an object to compare and hash it by content alone
+///
+/// Two keys are equal when the wrapped objects are equal apart from their
+/// top-level `id`, `created` and `modified` properties, so reissued copies of
+/// the same content collapse into one entry of a `HashSet` or `HashMap`. The
+/// content is encoded as canonical JSON once, when the key is created.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use maec::{ContentKey, MalwareFamily};
+///
+/// let original = MalwareFamily::new("Emotet");
+/// let reissued = MalwareFamily::new("Emotet");
+/// let other = MalwareFamily::new("TrickBot");
+///
+/// let unique: HashSet<_> = [&original, &reissued, &other]
+///     .into_iter()
+///     .map(ContentKey::new)
+///     .collect();
+/// assert_eq!(unique.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentKey<'a, T> {
+    value: &'a T,
+    content: String,
+}
+
+impl<'a, T: Serialize> ContentKey<'a, T> {
+    /// Creates the key of an object
+    pub fn new(value: &'a T) -> Self {
+        let mut content = serde_json::to_value(value).unwrap_or_default();
+        if let Some(map) = content.as_object_mut() {
+            for key in ["id", "created", "modified"] {
+                map.remove(key);
+            }
+        }
+        Self {
+            value,
+            content: canonical_json(&content),
+        }
+    }
+}
+
+impl<'a, T> ContentKey<'a, T> {
+    /// Returns the wrapped object
+    pub fn get(&self) -> &'a T {
+        self.value
+    }
+}
+
+impl<T> PartialEq for ContentKey<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+    }
+}
+
+impl<T> Eq for ContentKey<'_, T> {}
+
+impl<T> std::hash::Hash for ContentKey<'_, T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.content.hash(state);
+    }
+}
+
+/// External Reference - Links to external resources
+///
+/// Used to reference external sources like ATT&CK techniques, CVEs,
+/// or research papers related to MAEC objects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ExternalReference {
+    /// Name of the source (e.g., "mitre-attack", "cve")
+    #[serde(alias = "sourceName")]
+    pub source_name: String,
+
+    /// Description of the reference
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// URL to the external resource
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// External identifier (e.g., "T1055" for ATT&CK)
+    #[serde(skip_serializing_if = "Option::is_none", alias = "externalId")]
+    pub external_id: Option<String>,
+}
+
+impl ExternalReference {
+    /// Creates a new external reference with just a source name
+    pub fn new(source_name: impl Into<String>) -> Self {
+        Self {
+            source_name: source_name.into(),
+            description: None,
+            url: None,
+            external_id: None,
+        }
+    }
+
+    /// Creates an ATT&CK technique reference
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::common::ExternalReference;
+    ///
+    /// let technique = ExternalReference::attack_technique("T1055", "Process Injection");
+    /// assert_eq!(technique.source_name, "mitre-attack");
+    /// assert_eq!(technique.external_id, Some("T1055".to_string()));
+    /// ```
+    pub fn attack_technique(technique_id: impl Into<String>, name: impl Into<String>) -> Self {
+        let technique_id = technique_id.into();
+        Self {
+            source_name: "mitre-attack".to_string(),
+            description: Some(name.into()),
+            url: Some(format!(
+                "https://attack.mitre.org/techniques/{}",
+                technique_id
+            )),
+            external_id: Some(technique_id),
+        }
+    }
+
+    /// Checks that the URL, if any, is a well-formed absolute URL
+    ///
+    /// URLs with surrounding whitespace or a defanged scheme (`hxxp`,
+    /// `hxxps`) are rejected; [`normalize_url`](Self::normalize_url) repairs
+    /// both.
+    pub fn validate_url(&self) -> crate::error::Result<()> {
+        let Some(url) = self.url.as_deref() else {
+            return Ok(());
+        };
+        let invalid = |reason: &str| {
+            crate::error::MaecError::ValidationError(format!("invalid url '{}': {}", url, reason))
+        };
+
+        if url.trim() != url {
+            return Err(invalid("surrounding whitespace"));
+        }
+        let parsed = url::Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+        if matches!(parsed.scheme(), "hxxp" | "hxxps") {
+            return Err(invalid("defanged scheme"));
+        }
+        Ok(())
+    }
+
+    /// Canonicalizes the URL, if any
+    ///
+    /// Surrounding whitespace is trimmed, defanged `hxxp`/`hxxps` schemes and
+    /// `[.]` separators are restored, the scheme and host are lowercased and
+    /// default ports are removed. A URL that still does not parse is left
+    /// trimmed and refanged but otherwise unchanged.
+    pub fn normalize_url(&mut self) {
+        let Some(url) = self.url.as_mut() else {
+            return;
+        };
+
+        let mut refanged = url.trim().replace("[.]", ".");
+        for (defanged, scheme) in [("hxxps:", "https:"), ("hxxp:", "http:")] {
+            if refanged
+                .get(..defanged.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(defanged))
+            {
+                refanged.replace_range(..defanged.len(), scheme);
+                break;
+            }
+        }
+
+        *url = match url::Url::parse(&refanged) {
+            Ok(parsed) => parsed.into(),
+            Err(_) => refanged,
+        };
+    }
+
+    /// Returns a key identifying what the reference points at
+    ///
+    /// The key is `source_name:external_id` if there is an external ID,
+    /// otherwise the URL, otherwise just the source name.
+    pub fn key(&self) -> String {
+        match (&self.external_id, &self.url) {
+            (Some(external_id), _) => format!("{}:{}", self.source_name, external_id),
+            (None, Some(url)) => url.clone(),
+            (None, None) => self.source_name.clone(),
+        }
+    }
+}
+
+/// Resolves external references to data held outside the package
+///
+/// Implement this to plug a lookup (a database, a threat intelligence
+/// platform, ...) into [`Package::resolve_external_refs`](crate::Package::resolve_external_refs).
+pub trait ReferenceResolver {
+    /// Returns the data the reference points at, or `None` if it is unknown
+    fn resolve(&self, reference: &ExternalReference) -> Option<serde_json::Value>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_maec_id() {
+        let id = generate_maec_id("malware-family");
+        assert!(id.starts_with("malware-family--"));
+        assert!(is_valid_maec_id(&id));
+    }
+
+    #[test]
+    fn test_generate_maec_id_v5() {
+        let id = generate_maec_id_v5_default("malware-family", "WannaCry");
+        assert_eq!(
+            id,
+            generate_maec_id_v5("malware-family", &MAEC_NAMESPACE, "WannaCry")
+        );
+        assert_eq!(
+            Uuid::parse_str(&id["malware-family--".len()..])
+                .unwrap()
+                .get_version_num(),
+            5
+        );
+        assert_ne!(id, generate_maec_id_v5_default("malware-family", "Emotet"));
+        assert_ne!(
+            id,
+            generate_maec_id_v5("malware-family", &Uuid::NAMESPACE_URL, "WannaCry")
+        );
+        assert_eq!(
+            MAEC_NAMESPACE.to_string(),
+            "6f1d3b0e-8a4c-4f62-9d3e-2b7a5c9e1f04"
+        );
+    }
+
+    #[test]
+    fn test_is_valid_maec_id() {
+        assert!(is_valid_maec_id(
+            "malware-family--550e8400-e29b-41d4-a716-446655440000"
+        ));
+        assert!(is_valid_maec_id(
+            "package--12345678-1234-1234-1234-123456789abc"
+        ));
+        assert!(!is_valid_maec_id("invalid"));
+        assert!(!is_valid_maec_id("malware-family"));
+        assert!(!is_valid_maec_id("malware-family-no-uuid"));
+    }
+
+    #[test]
+    fn test_extract_type_from_id() {
+        assert_eq!(
+            extract_type_from_id("malware-family--550e8400-e29b-41d4-a716-446655440000"),
+            Some("malware-family")
+        );
+        assert_eq!(
+            extract_type_from_id("package--12345678-1234-1234-1234-123456789abc"),
+            Some("package")
+        );
+        assert_eq!(extract_type_from_id("invalid"), None);
+    }
+
+    #[test]
+    fn test_is_valid_ref_for_type() {
+        assert!(is_valid_ref_for_type(
+            "malware-family--550e8400-e29b-41d4-a716-446655440000",
+            "malware-family"
+        ));
+        assert!(!is_valid_ref_for_type(
+            "package--550e8400-e29b-41d4-a716-446655440000",
+            "malware-family"
+        ));
+    }
+
+    #[test]
+    fn test_common_properties_new() {
+        let common = CommonProperties::new("malware-family", None);
+        assert_eq!(common.r#type, "malware-family");
+        assert_eq!(common.schema_version, Some("5.0".to_string()));
+        assert!(common.id.starts_with("malware-family--"));
+    }
+
+    #[test]
+    fn test_new_version() {
+        let mut common = CommonProperties::new("malware-family", None);
+        let original_created = common.created;
+        let original_modified = common.modified;
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        common.new_version();
+
+        assert_eq!(common.created, original_created);
+        assert!(common.modified > original_modified);
+    }
+
+    #[test]
+    fn test_rfc3339_accessors() {
+        let mut common = CommonProperties::new("malware-family", None);
+        common
+            .set_created_rfc3339("2017-05-12T09:44:00+02:00")
+            .unwrap();
+        assert_eq!(common.created_rfc3339(), "2017-05-12T07:44:00Z");
+
+        common
+            .set_modified_rfc3339("2017-05-13T00:00:00.123456Z")
+            .unwrap();
+        assert_eq!(common.modified_rfc3339(), "2017-05-13T00:00:00.123456Z");
+
+        assert!(common.set_created_rfc3339("yesterday").is_err());
+        assert_eq!(common.created_rfc3339(), "2017-05-12T07:44:00Z");
+    }
+
+    #[test]
+    fn test_set_json_pointer() {
+        let mut value = serde_json::json!({"labels": ["worm"], "a/b": {}});
+        set_json_pointer(&mut value, "/labels/-", serde_json::json!("bot")).unwrap();
+        set_json_pointer(&mut value, "/labels/0", serde_json::json!("virus")).unwrap();
+        set_json_pointer(&mut value, "/a~1b/c", serde_json::json!(1)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"labels": ["virus", "bot"], "a/b": {"c": 1}})
+        );
+
+        assert!(set_json_pointer(&mut value, "/missing/key", serde_json::json!(1)).is_err());
+        assert!(set_json_pointer(&mut value, "/labels/5", serde_json::json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_canonical_json_known_vectors() {
+        let vectors = [
+            (serde_json::json!(null), "null"),
+            (serde_json::json!([true, false]), "[true,false]"),
+            (serde_json::json!(-0.0), "0"),
+            (serde_json::json!(100.0), "100"),
+            (serde_json::json!(1.5e-7), "0.00000015"),
+            (serde_json::json!(1e21), "1000000000000000000000"),
+            (serde_json::json!(u64::MAX), "18446744073709551615"),
+            (
+                serde_json::json!("tab\t \"quote\" \u{1} é"),
+                "\"tab\\t \\\"quote\\\" \\u0001 é\"",
+            ),
+            (
+                serde_json::json!({"b": 1, "a": [{"d": 2, "c": 3}], "B": 0}),
+                r#"{"B":0,"a":[{"c":3,"d":2}],"b":1}"#,
+            ),
+        ];
+
+        for (value, expected) in vectors {
+            assert_eq!(canonical_json(&value), expected);
+        }
+    }
+
+    #[test]
+    fn test_external_reference_attack() {
+        let ref_obj = ExternalReference::attack_technique("T1055", "Process Injection");
+        assert_eq!(ref_obj.source_name, "mitre-attack");
+        assert_eq!(ref_obj.external_id, Some("T1055".to_string()));
+        assert!(ref_obj.url.unwrap().contains("T1055"));
+    }
+
+    #[test]
+    fn test_external_reference_url() {
+        let mut reference = ExternalReference::new("vendor-report");
+        assert!(reference.validate_url().is_ok());
+
+        reference.url = Some(" hxxps://Example[.]COM:443/report ".to_string());
+        assert!(reference.validate_url().is_err());
+        reference.normalize_url();
+        assert_eq!(reference.url.as_deref(), Some("https://example.com/report"));
+        assert!(reference.validate_url().is_ok());
+
+        reference.url = Some("HTTP://Example.com:8080/a".to_string());
+        reference.normalize_url();
+        assert_eq!(reference.url.as_deref(), Some("http://example.com:8080/a"));
+
+        reference.url = Some("not a url".to_string());
+        reference.normalize_url();
+        assert_eq!(reference.url.as_deref(), Some("not a url"));
+        assert!(matches!(
+            reference.validate_url(),
+            Err(crate::error::MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_external_reference_key() {
+        let mut reference = ExternalReference::new("vendor-report");
+        assert_eq!(reference.key(), "vendor-report");
+
+        reference.url = Some("https://example.com/report".to_string());
+        assert_eq!(reference.key(), "https://example.com/report");
+
+        reference.external_id = Some("VR-42".to_string());
+        assert_eq!(reference.key(), "vendor-report:VR-42");
+    }
+
+    #[test]
+    fn test_validate_timestamps() {
+        let mut common = CommonProperties::new("behavior", None);
+        assert!(common.validate_timestamps().is_ok());
+
+        common.modified = common.created - chrono::Duration::seconds(1);
+        assert!(common.validate_timestamps().is_err());
+
+        let mut behavior = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        behavior.common = common;
+        assert!(matches!(
+            behavior.validate(),
+            Err(crate::error::MaecError::ValidationError(_))
+        ));
+    }
+}
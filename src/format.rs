@@ -0,0 +1,231 @@
+//! Serialization format selection
+//!
+//! MAEC content is primarily exchanged as JSON, but other encodings may be
+//! compiled in. This module maps media types onto formats and reports formats
+//! that are not available in the current build as
+//! `MaecError::UnsupportedFormat` instead of failing to compile.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{MaecError, Result};
+
+/// A serialization format for MAEC content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SerializationFormat {
+    /// JSON, the native MAEC 5.0 encoding
+    Json,
+    /// XML
+    Xml,
+    /// YAML
+    Yaml,
+}
+
+impl SerializationFormat {
+    /// Returns the short name of the format (e.g. `"json"`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            SerializationFormat::Json => "json",
+            SerializationFormat::Xml => "xml",
+            SerializationFormat::Yaml => "yaml",
+        }
+    }
+
+    /// Returns the media type used when emitting this format
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            SerializationFormat::Json => crate::MEDIA_TYPE_MAEC,
+            SerializationFormat::Xml => "application/xml",
+            SerializationFormat::Yaml => "application/yaml",
+        }
+    }
+
+    /// Returns whether this build can serialize the format
+    pub fn is_available(&self) -> bool {
+        match self {
+            SerializationFormat::Json => true,
+            SerializationFormat::Xml => cfg!(feature = "xml"),
+            SerializationFormat::Yaml => cfg!(feature = "yaml"),
+        }
+    }
+
+    /// Maps a media type (parameters are ignored) onto a format
+    ///
+    /// Returns `None` for media types that do not denote a known format,
+    /// whether or not that format is available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::SerializationFormat;
+    ///
+    /// assert_eq!(
+    ///     SerializationFormat::from_media_type("application/maec+json;version=5.0"),
+    ///     Some(SerializationFormat::Json)
+    /// );
+    /// assert_eq!(SerializationFormat::from_media_type("image/png"), None);
+    /// ```
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or("").trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "application/maec+json" | "application/json" => Some(SerializationFormat::Json),
+            "application/maec+xml" | "application/xml" | "text/xml" => {
+                Some(SerializationFormat::Xml)
+            }
+            "application/yaml" | "application/x-yaml" | "text/yaml" => {
+                Some(SerializationFormat::Yaml)
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks the preferred available format from an HTTP `Accept` header
+    ///
+    /// Media ranges are tried in order of their `q` value; `*/*` and
+    /// `application/*` select JSON. Returns `MaecError::UnsupportedFormat` if
+    /// no acceptable format is available in this build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::SerializationFormat;
+    ///
+    /// let format = SerializationFormat::negotiate("application/xml;q=0.5, application/json");
+    /// assert_eq!(format.unwrap(), SerializationFormat::Json);
+    /// ```
+    pub fn negotiate(accept: &str) -> Result<Self> {
+        let mut ranges: Vec<(&str, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let media_type = parts.next().unwrap_or("");
+                let quality = parts
+                    .find_map(|param| param.strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                (media_type, quality)
+            })
+            .filter(|(media_type, quality)| !media_type.is_empty() && *quality > 0.0)
+            .collect();
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut unavailable = None;
+        for (media_type, _) in ranges {
+            let format = match media_type {
+                "*/*" | "application/*" => Some(SerializationFormat::Json),
+                other => Self::from_media_type(other),
+            };
+            match format {
+                Some(format) if format.is_available() => return Ok(format),
+                Some(format) => unavailable = unavailable.or(Some(format)),
+                None => {}
+            }
+        }
+
+        Err(match unavailable {
+            Some(format) => format.unsupported(),
+            None => MaecError::UnsupportedFormat(format!("no supported format in '{}'", accept)),
+        })
+    }
+
+    /// Serializes a value in this format
+    ///
+    /// XML documents use the value's `type` property as the root element name.
+    /// XML and YAML go through `serde_json::Value`, so they accept and produce
+    /// the same structure as JSON. Returns `MaecError::UnsupportedFormat` if
+    /// the format is not available.
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
+        match self {
+            SerializationFormat::Json => Ok(serde_json::to_string(value)?),
+            #[cfg(feature = "xml")]
+            SerializationFormat::Xml => {
+                let value = serde_json::to_value(value)?;
+                let root = value.get("type").and_then(|t| t.as_str()).unwrap_or("maec");
+                crate::xml::to_string(root, &value)
+            }
+            #[cfg(feature = "yaml")]
+            SerializationFormat::Yaml => {
+                let value = serde_json::to_value(value)?;
+                serde_yaml::to_string(&value).map_err(|e| MaecError::YamlError(e.to_string()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    /// Deserializes a value from this format
+    ///
+    /// Returns `MaecError::UnsupportedFormat` if the format is not available.
+    pub fn deserialize<T: DeserializeOwned>(&self, input: &str) -> Result<T> {
+        match self {
+            SerializationFormat::Json => Ok(serde_json::from_str(input)?),
+            #[cfg(feature = "xml")]
+            SerializationFormat::Xml => {
+                let (_, value) = crate::xml::from_str(input)?;
+                Ok(serde_json::from_value(value)?)
+            }
+            #[cfg(feature = "yaml")]
+            SerializationFormat::Yaml => {
+                let value: serde_json::Value =
+                    serde_yaml::from_str(input).map_err(|e| MaecError::YamlError(e.to_string()))?;
+                Ok(serde_json::from_value(value)?)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    fn unsupported(&self) -> MaecError {
+        MaecError::UnsupportedFormat(format!(
+            "{} support is not enabled in this build",
+            self.name()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(
+            SerializationFormat::negotiate("text/xml").is_ok(),
+            cfg!(feature = "xml")
+        );
+        assert_eq!(
+            SerializationFormat::negotiate("image/png, */*;q=0.1").unwrap(),
+            SerializationFormat::Json
+        );
+
+        match SerializationFormat::negotiate("application/yaml") {
+            Ok(format) if cfg!(feature = "yaml") => assert_eq!(format, SerializationFormat::Yaml),
+            Err(MaecError::UnsupportedFormat(msg)) if !cfg!(feature = "yaml") => {
+                assert!(msg.contains("yaml"), "{}", msg)
+            }
+            other => panic!("unexpected negotiation result {:?}", other),
+        }
+        assert!(matches!(
+            SerializationFormat::negotiate("image/png, application/json;q=0"),
+            Err(MaecError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_serialize_unavailable_format() {
+        let package = crate::Package::new();
+        assert!(package.serialize_as(SerializationFormat::Json).is_ok());
+        assert_eq!(
+            package.serialize_as(SerializationFormat::Yaml).is_ok(),
+            cfg!(feature = "yaml")
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_error() {
+        let result: Result<serde_json::Value> =
+            SerializationFormat::Yaml.deserialize("type: [unclosed");
+        assert!(matches!(result, Err(MaecError::YamlError(_))));
+    }
+}
@@ -0,0 +1,312 @@
+//! Graph view over the objects of a MAEC package
+//!
+//! [`PackageGraph`] indexes the edges returned by
+//! [`Package::relationship_edges`] by source ID, so the references of an object
+//! can be followed without rescanning the package.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::error::{MaecError, Result};
+use crate::{MaecObject, Package};
+
+/// Directed graph of the objects in a package
+///
+/// Nodes are the IDs of the package's MAEC objects plus every ID that appears
+/// at either end of an edge, including dangling references. Edges are
+/// `(type, target)` pairs keyed by their source, in package order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageGraph {
+    nodes: BTreeSet<String>,
+    edges: BTreeMap<String, Vec<(String, String)>>,
+    edge_count: usize,
+}
+
+impl PackageGraph {
+    /// Builds the graph of a package without any size limits
+    pub fn new(package: &Package) -> Self {
+        Self::with_limits(package, usize::MAX, usize::MAX)
+            .expect("unbounded graph construction cannot fail")
+    }
+
+    /// Builds the graph of a package, bailing out once it grows too large
+    ///
+    /// Returns `MaecError::ValidationError` as soon as the graph would hold
+    /// more than `max_nodes` nodes or `max_edges` edges, before allocating
+    /// for the rest of the package. Use this on untrusted input.
+    pub fn with_limits(package: &Package, max_nodes: usize, max_edges: usize) -> Result<Self> {
+        let mut graph = PackageGraph::default();
+
+        for object in &package.maec_objects {
+            graph.add_node(object.id(), max_nodes)?;
+        }
+        for (source, r#type, target) in package.relationship_edges() {
+            if graph.edge_count >= max_edges {
+                return Err(MaecError::ValidationError(format!(
+                    "package graph exceeds {} edges",
+                    max_edges
+                )));
+            }
+            graph.add_node(source, max_nodes)?;
+            graph.add_node(target, max_nodes)?;
+            graph
+                .edges
+                .entry(source.to_string())
+                .or_default()
+                .push((r#type.to_string(), target.to_string()));
+            graph.edge_count += 1;
+        }
+
+        Ok(graph)
+    }
+
+    fn add_node(&mut self, id: &str, max_nodes: usize) -> Result<()> {
+        if self.nodes.contains(id) {
+            return Ok(());
+        }
+        if self.nodes.len() >= max_nodes {
+            return Err(MaecError::ValidationError(format!(
+                "package graph exceeds {} nodes",
+                max_nodes
+            )));
+        }
+        self.nodes.insert(id.to_string());
+        Ok(())
+    }
+
+    /// Returns the number of nodes
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the number of edges
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Iterates over the node IDs in sorted order
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(String::as_str)
+    }
+
+    /// Returns the outgoing `(type, target)` edges of a node
+    pub fn outgoing(&self, id: &str) -> &[(String, String)] {
+        self.edges.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// Finds the strongly connected components reachable from `roots`
+///
+/// Nodes are indices into `adjacency`; only nodes for which `allowed` holds
+/// are visited, and the bookkeeping grows with the visited nodes rather than
+/// the whole graph. This is Tarjan's algorithm with an explicit call stack,
+/// so long chains cannot overflow the thread's stack. Components are emitted
+/// in reverse topological order, and their nodes are sorted.
+pub(crate) fn strongly_connected_components(
+    adjacency: &[Vec<usize>],
+    roots: impl IntoIterator<Item = usize>,
+    allowed: impl Fn(usize) -> bool,
+) -> Vec<Vec<usize>> {
+    // Visit order and lowest reachable visit order of each visited node
+    let mut visits: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut on_stack = HashSet::new();
+    let mut stack = vec![];
+    let mut components = vec![];
+
+    for root in roots {
+        if visits.contains_key(&root) || !allowed(root) {
+            continue;
+        }
+        // Each frame is a node and the position of its next outgoing edge
+        let mut calls = vec![(root, 0)];
+        visits.insert(root, (visits.len(), visits.len()));
+        stack.push(root);
+        on_stack.insert(root);
+
+        while let Some(&mut (node, ref mut edge)) = calls.last_mut() {
+            if let Some(&next) = adjacency[node].get(*edge) {
+                *edge += 1;
+                if !allowed(next) {
+                    continue;
+                }
+                match visits.get(&next) {
+                    None => {
+                        visits.insert(next, (visits.len(), visits.len()));
+                        stack.push(next);
+                        on_stack.insert(next);
+                        calls.push((next, 0));
+                    }
+                    Some(&(order, _)) if on_stack.contains(&next) => {
+                        let low = &mut visits.get_mut(&node).expect("node is visited").1;
+                        *low = (*low).min(order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            calls.pop();
+            let (order, low) = visits[&node];
+            if let Some(&(parent, _)) = calls.last() {
+                let parent_low = &mut visits.get_mut(&parent).expect("parent is visited").1;
+                *parent_low = (*parent_low).min(low);
+            }
+            if low == order {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(&member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// Enumerates the elementary cycles of a directed graph
+///
+/// Nodes are indices into `adjacency`, whose lists must be free of
+/// duplicates. Each cycle is returned once, starting at its smallest node.
+/// This is Johnson's algorithm: components are found first so that acyclic
+/// parts of the graph are skipped outright. Within a component, each round
+/// searches from the smallest node that still lies on a cycle, and the
+/// circuit search blocks nodes that cannot currently close a cycle, so the
+/// work is bounded by the size of the graph times the number of cycles. All
+/// searches use explicit stacks.
+pub(crate) fn elementary_cycles(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let is_cyclic =
+        |members: &[usize]| members.len() > 1 || adjacency[members[0]].contains(&members[0]);
+
+    let mut cycles = vec![];
+    let mut in_component = vec![false; adjacency.len()];
+    let mut blocked = vec![false; adjacency.len()];
+    let mut blocked_by: Vec<Vec<usize>> = vec![vec![]; adjacency.len()];
+
+    for component in strongly_connected_components(adjacency, 0..adjacency.len(), |_| true) {
+        if !is_cyclic(&component) {
+            continue;
+        }
+        let mut lowest = 0;
+        while lowest < component.len() {
+            // Find the cyclic part of the component above `lowest` that holds
+            // the smallest node; every cycle through that node lies within it
+            let floor = component[lowest];
+            let Some(members) = strongly_connected_components(
+                adjacency,
+                component[lowest..].iter().copied(),
+                |node| node >= floor && component.binary_search(&node).is_ok(),
+            )
+            .into_iter()
+            .filter(|members| is_cyclic(members))
+            .min_by_key(|members| members[0]) else {
+                break;
+            };
+            let start = members[0];
+            lowest = component.binary_search(&start).unwrap_or(lowest) + 1;
+
+            for &member in &members {
+                in_component[member] = true;
+                blocked[member] = false;
+                blocked_by[member].clear();
+            }
+
+            let mut path = vec![start];
+            // Frames hold a node, its next edge and whether a cycle was found
+            let mut calls = vec![(start, 0, false)];
+            blocked[start] = true;
+            while let Some(&mut (node, ref mut edge, ref mut found)) = calls.last_mut() {
+                if let Some(&next) = adjacency[node].get(*edge) {
+                    *edge += 1;
+                    if !in_component[next] {
+                        continue;
+                    }
+                    if next == start {
+                        cycles.push(path.clone());
+                        *found = true;
+                    } else if !blocked[next] {
+                        blocked[next] = true;
+                        path.push(next);
+                        calls.push((next, 0, false));
+                    }
+                    continue;
+                }
+
+                let found = *found;
+                if found {
+                    let mut pending = vec![node];
+                    while let Some(unblocked) = pending.pop() {
+                        if blocked[unblocked] {
+                            blocked[unblocked] = false;
+                            pending.append(&mut blocked_by[unblocked]);
+                        }
+                    }
+                } else {
+                    for &next in &adjacency[node] {
+                        if in_component[next] && !blocked_by[next].contains(&node) {
+                            blocked_by[next].push(node);
+                        }
+                    }
+                }
+                calls.pop();
+                path.pop();
+                if let Some((_, _, parent_found)) = calls.last_mut() {
+                    *parent_found |= found;
+                }
+            }
+
+            for &member in &members {
+                in_component[member] = false;
+            }
+        }
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Behavior, BehaviorVocab, MaecObjectType, Relationship};
+
+    fn sample_package() -> Package {
+        let behavior = Behavior::new(BehaviorVocab::SendBeacon);
+        let id = behavior.common.id.clone();
+        Package::new()
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_relationship(Relationship::new(id.clone(), "related-to", id.clone()))
+            .with_relationship(Relationship::new(
+                id.clone(),
+                "related-to",
+                "behavior--550e8400-e29b-41d4-a716-446655440000",
+            ))
+    }
+
+    #[test]
+    fn test_graph() {
+        let package = sample_package();
+        let graph = package.graph();
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.edge_count(), 2);
+
+        let id = package.maec_objects[0].id();
+        assert_eq!(graph.outgoing(id).len(), 2);
+        assert!(graph.outgoing("behavior--missing").is_empty());
+    }
+
+    #[test]
+    fn test_with_limits() {
+        let package = sample_package();
+        assert!(PackageGraph::with_limits(&package, 2, 2).is_ok());
+        assert!(matches!(
+            PackageGraph::with_limits(&package, 1, 2),
+            Err(MaecError::ValidationError(_))
+        ));
+        assert!(matches!(
+            PackageGraph::with_limits(&package, 2, 1),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+//! Bulk import helpers
+//!
+//! This module builds MAEC objects from simple tabular sources such as CSV
+//! exports. It is enabled by the `csv` feature.
+
+use serde::Deserialize;
+use std::io::Read;
+
+use crate::error::{MaecError, Result};
+use crate::{MalwareFamily, Name};
+
+/// A single row of a malware family CSV file
+#[derive(Debug, Deserialize)]
+struct FamilyRow {
+    name: String,
+    #[serde(default)]
+    aliases: String,
+    #[serde(default)]
+    labels: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Converts a CSV error into a validation error naming the offending line
+fn csv_error(error: csv::Error) -> MaecError {
+    let line = error.position().map(|p| p.line()).unwrap_or_default();
+    MaecError::ValidationError(format!("line {}: {}", line, error))
+}
+
+/// Splits a semicolon-separated cell into its trimmed, non-empty values
+fn split_list(cell: &str) -> Vec<String> {
+    cell.split(';')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Builds malware families from CSV data
+///
+/// The input must have a header row with the columns `name`, `aliases`,
+/// `labels` and `description`; `aliases` and `labels` hold semicolon-separated
+/// lists. Every row is built into a validated [`MalwareFamily`]. The first
+/// malformed row aborts the import with a `MaecError::ValidationError` naming
+/// its line number.
+///
+/// # Examples
+///
+/// ```
+/// let csv = "name,aliases,labels,description\n\
+///            Emotet,Geodo;Heodo,trojan-horse;bot,Banking trojan\n";
+///
+/// let families = maec::import::families_from_csv(csv.as_bytes()).unwrap();
+/// assert_eq!(families[0].name.value, "Emotet");
+/// assert_eq!(families[0].aliases.len(), 2);
+/// ```
+pub fn families_from_csv<R: Read>(reader: R) -> Result<Vec<MalwareFamily>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = reader.headers().map_err(csv_error)?.clone();
+
+    let mut families = vec![];
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record).map_err(csv_error)? {
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let row: FamilyRow = record
+            .deserialize(Some(&headers))
+            .map_err(|e| MaecError::ValidationError(format!("line {}: {}", line, e)))?;
+
+        let mut builder = MalwareFamily::builder()
+            .name(Name::new(row.name))
+            .aliases(
+                split_list(&row.aliases)
+                    .into_iter()
+                    .map(Name::new)
+                    .collect(),
+            )
+            .labels(split_list(&row.labels));
+        if !row.description.is_empty() {
+            builder = builder.description(row.description);
+        }
+
+        let family = builder
+            .build()
+            .map_err(|e| MaecError::ValidationError(format!("line {}: {}", line, e)))?;
+        families.push(family);
+    }
+
+    Ok(families)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_families_from_csv() {
+        let csv = "name,aliases,labels,description\n\
+                   WannaCry,WCry; WanaCrypt0r,ransomware;worm,\n\
+                   Emotet,,trojan-horse,\"Banking trojan, later a loader\"\n";
+
+        let families = families_from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(families.len(), 2);
+        assert_eq!(
+            families[0].aliases,
+            vec![Name::new("WCry"), Name::new("WanaCrypt0r")]
+        );
+        assert_eq!(families[0].labels, vec!["ransomware", "worm"]);
+        assert!(families[0].description.is_none());
+        assert!(families[1].aliases.is_empty());
+        assert_eq!(
+            families[1].description.as_deref(),
+            Some("Banking trojan, later a loader")
+        );
+    }
+
+    #[test]
+    fn test_families_from_csv_reports_line() {
+        let csv = "name,aliases,labels,description\n\
+                   Emotet,,,\n\
+                   Broken,\"unterminated\n";
+
+        match families_from_csv(csv.as_bytes()) {
+            Err(MaecError::ValidationError(msg)) => assert!(msg.starts_with("line 3"), "{}", msg),
+            other => panic!("expected a line-numbered error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! File-system helpers for MAEC content
+//!
+//! These functions validate MAEC packages stored as `.json` files, which is
+//! useful for checking whole repositories of threat intelligence at once.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::{Package, ValidationProfile};
+
+/// Validates the MAEC package files at `path`
+///
+/// If `path` is a directory, every `.json` file directly inside it is parsed
+/// as a [`Package`] and validated with the given profile; subdirectories are
+/// not visited (see [`validate_path_recursive`]). If `path` is a file, only
+/// that file is validated. Results are returned in path order, one per file;
+/// a directory that cannot be read yields a single error for `path`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use maec::ValidationProfile;
+/// use std::path::Path;
+///
+/// for (file, result) in maec::io::validate_path(Path::new("intel/"), ValidationProfile::Deep) {
+///     if let Err(e) = result {
+///         eprintln!("{}: {}", file.display(), e);
+///     }
+/// }
+/// ```
+pub fn validate_path(path: &Path, profile: ValidationProfile) -> Vec<(PathBuf, Result<()>)> {
+    validate_files(path, profile, false)
+}
+
+/// Validates the MAEC package files at `path` and in all its subdirectories
+///
+/// Behaves like [`validate_path`], but descends into subdirectories.
+pub fn validate_path_recursive(
+    path: &Path,
+    profile: ValidationProfile,
+) -> Vec<(PathBuf, Result<()>)> {
+    validate_files(path, profile, true)
+}
+
+/// Parses and validates a single package file
+pub fn validate_file(path: &Path, profile: ValidationProfile) -> Result<()> {
+    let json = fs::read_to_string(path)?;
+    Package::from_json(&json)?.validate_with(profile)
+}
+
+fn validate_files(
+    path: &Path,
+    profile: ValidationProfile,
+    recursive: bool,
+) -> Vec<(PathBuf, Result<()>)> {
+    if !path.is_dir() {
+        return vec![(path.to_path_buf(), validate_file(path, profile))];
+    }
+
+    let mut files = vec![];
+    let mut results = vec![];
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                results.push((dir, Err(e.into())));
+                continue;
+            }
+        };
+
+        for entry in entries {
+            let entry_path = match entry {
+                Ok(entry) => entry.path(),
+                Err(e) => {
+                    results.push((dir.clone(), Err(e.into())));
+                    continue;
+                }
+            };
+            if entry_path.is_dir() {
+                if recursive {
+                    pending.push(entry_path);
+                }
+            } else if is_json_file(&entry_path) {
+                files.push(entry_path);
+            }
+        }
+    }
+
+    results.extend(files.into_iter().map(|file| {
+        let result = validate_file(&file, profile);
+        (file, result)
+    }));
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    results
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_path() {
+        let root = std::env::temp_dir().join(crate::generate_maec_id("maec-io-test"));
+        let nested = root.join("nested");
+        fs::create_dir_all(&nested).unwrap();
+
+        let valid = Package::new().to_json().unwrap();
+        fs::write(root.join("a.json"), &valid).unwrap();
+        fs::write(root.join("b.JSON"), "{\"type\": \"package\"}").unwrap();
+        fs::write(root.join("notes.txt"), "not a package").unwrap();
+        fs::write(nested.join("c.json"), &valid).unwrap();
+
+        let results = validate_path(&root, ValidationProfile::Basic);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, root.join("a.json"));
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_err());
+
+        let results = validate_path_recursive(&root, ValidationProfile::Deep);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2].0, nested.join("c.json"));
+        assert!(results[2].1.is_ok());
+
+        let results = validate_path(&root.join("a.json"), ValidationProfile::Basic);
+        assert_eq!(results.len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
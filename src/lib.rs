@@ -37,6 +37,20 @@
 //! MAEC complements STIX (Structured Threat Information Expression) by providing
 //! detailed malware analysis data. MAEC objects can reference STIX Cyber Observable
 //! Objects (files, network traffic, etc.) via the `observable_objects` field in Package.
+//! With the `stix-integration` feature enabled, the `stix` module converts MAEC
+//! data to and from native STIX 2.1 objects.
+//!
+//! # YAML
+//!
+//! With the `yaml` feature enabled, `Package::to_yaml` and `Package::from_yaml`
+//! read and write the same structure as JSON in YAML syntax.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature enabled, deep validation, package merging and
+//! the relationship graph queries run inside `tracing` spans carrying object
+//! counts, and emit events summarizing their results. Timings are available
+//! from the span durations recorded by the installed subscriber.
 
 // MIME Type Constants for MAEC and HTTP integration
 /// MAEC 5.0 JSON media type for HTTP Content-Type headers
@@ -46,30 +60,54 @@ pub const MEDIA_TYPE_MAEC: &str = "application/maec+json;version=5.0";
 pub const MEDIA_TYPE_MAEC_GENERIC: &str = "application/maec+json";
 
 // Module declarations
+pub mod analytics;
 pub mod common;
 pub mod error;
+pub mod format;
+pub mod graph;
+#[cfg(feature = "csv")]
+pub mod import;
+pub mod io;
 pub mod objects;
+#[cfg(feature = "stix-integration")]
+pub mod stix;
+pub mod upgrade;
+pub mod valid;
 pub mod vocab;
 pub mod vocab_large;
+pub mod writer;
+#[cfg(feature = "xml")]
+mod xml;
 
 // Re-exports for convenient access
 pub use common::{
-    extract_type_from_id, generate_maec_id, is_valid_maec_id, is_valid_ref_for_type,
-    CommonProperties, ExternalReference, MaecObject,
+    extract_type_from_id, generate_maec_id, generate_maec_id_v5, generate_maec_id_v5_default,
+    is_valid_maec_id, is_valid_ref_for_type, CommonProperties, ContentKey, ExternalReference,
+    MaecObject, ReferenceResolver, SchemaVersion, SerializeOptions, MAEC_NAMESPACE,
 };
 
 pub use error::{BuilderError, MaecError, Result};
 
+pub use format::SerializationFormat;
+
+pub use graph::PackageGraph;
+
+pub use valid::{Valid, Validate};
+
+pub use writer::PackageWriter;
+
 pub use objects::{
-    Behavior, BehaviorBuilder, Capability, CapabilityBuilder, Collection, FieldData,
-    FieldDataBuilder, MaecObjectType, MalwareAction, MalwareFamily, MalwareFamilyBuilder,
-    MalwareInstance, MalwareInstanceBuilder, Name, Package, PackageBuilder, Relationship,
-    RelationshipBuilder,
+    object_from_value, AnalysisMetadata, Behavior, BehaviorBuilder, BuildReport, Capability,
+    CapabilityBuilder, Collection, FieldData, FieldDataBuilder, MaecObjectType, MalwareAction,
+    MalwareFamily, MalwareFamilyBuilder, MalwareInstance, MalwareInstanceBuilder, Name, ObjectKind,
+    Package, PackageBuilder, Relationship, RelationshipBuilder, StaticFeatures, StringFeature,
+    TimelineEvent, TimelineEventKind, ValidationProfile,
 };
 
 pub use vocab::{
-    AnalysisConclusionType, AnalysisEnvironment, AnalysisType, ConfidenceMeasure, DeliveryVector,
-    EntityAssociation, MalwareLabel, ObfuscationMethod, ProcessorArchitecture,
+    labels_from_detection_name, AnalysisConclusionType, AnalysisEnvironment, AnalysisType,
+    ConfidenceMeasure, DeliveryVector, EntityAssociation, MalwareLabel, ObfuscationMethod,
+    OpenVocab, ProcessorArchitecture, RelationshipType,
 };
 
 pub use vocab_large::{
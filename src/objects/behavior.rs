@@ -2,25 +2,26 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::common::{ExternalReference, MaecObject};
 use crate::error::{MaecError, Result};
+use crate::vocab::OpenVocab;
 
 /// MAEC Behavior
 ///
 /// A Behavior corresponds to the specific purpose behind a particular snippet of code,
 /// as executed by a malware instance. Examples include keylogging, detecting a virtual
 /// machine, and installing a backdoor.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub struct Behavior {
     /// Common MAEC properties
     #[serde(flatten)]
     pub common: crate::common::CommonProperties,
 
-    /// Name of the behavior
-    pub name: crate::vocab_large::Behavior,
+    /// Name of the behavior (a spec value or a custom name)
+    pub name: OpenVocab<crate::vocab_large::Behavior>,
 
     /// Textual description
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,14 +33,18 @@ pub struct Behavior {
 
     /// Behavior attributes as key/value pairs
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub attributes: Option<HashMap<String, serde_json::Value>>,
+    pub attributes: Option<BTreeMap<String, serde_json::Value>>,
 
     /// References to actions implementing this behavior
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "actionRefs")]
     pub action_refs: Vec<String>,
 
     /// References to techniques used (ATT&CK, etc.)
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "techniqueRefs"
+    )]
     pub technique_refs: Vec<ExternalReference>,
 }
 
@@ -50,10 +55,10 @@ impl Behavior {
     }
 
     /// Creates a minimal Behavior with just a name
-    pub fn new(name: crate::vocab_large::Behavior) -> Self {
+    pub fn new(name: impl Into<OpenVocab<crate::vocab_large::Behavior>>) -> Self {
         Self {
             common: crate::common::CommonProperties::new("behavior", None),
-            name,
+            name: name.into(),
             description: None,
             timestamp: None,
             attributes: None,
@@ -62,6 +67,37 @@ impl Behavior {
         }
     }
 
+    /// Returns whether the behavior name is defined by the MAEC specification
+    pub fn is_spec_behavior(&self) -> bool {
+        self.name.is_known()
+    }
+
+    /// Returns the attribute keys not expected for this behavior
+    ///
+    /// Keys are compared against
+    /// [`expected_attributes`](crate::vocab_large::Behavior::expected_attributes)
+    /// of the behavior name. Custom names and behaviors without an expected
+    /// attribute set are not checked, so they always yield an empty list.
+    pub fn validate_attributes(&self) -> Vec<String> {
+        let expected = match self.name.known() {
+            Some(name) => name.expected_attributes(),
+            None => return vec![],
+        };
+        if expected.is_empty() {
+            return vec![];
+        }
+
+        let mut unexpected: Vec<String> = self
+            .attributes
+            .iter()
+            .flat_map(|attributes| attributes.keys())
+            .filter(|key| !expected.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        unexpected.sort_unstable();
+        unexpected
+    }
+
     /// Validates the Behavior structure
     pub fn validate(&self) -> Result<()> {
         if self.common.r#type != "behavior" {
@@ -74,11 +110,23 @@ impl Behavior {
         if !crate::common::is_valid_maec_id(&self.common.id) {
             return Err(MaecError::InvalidId(self.common.id.clone()));
         }
+        self.common.validate_timestamps()?;
 
         Ok(())
     }
+
+    /// Applies a mutation to the behavior and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
 }
 
+crate::common::impl_json_methods!(Behavior);
+
 impl MaecObject for Behavior {
     fn id(&self) -> &str {
         &self.common.id
@@ -94,15 +142,16 @@ impl MaecObject for Behavior {
 }
 
 /// Builder for Behavior objects
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct BehaviorBuilder {
     id: Option<String>,
-    name: Option<crate::vocab_large::Behavior>,
+    name: Option<OpenVocab<crate::vocab_large::Behavior>>,
     description: Option<String>,
     timestamp: Option<DateTime<Utc>>,
-    attributes: Option<HashMap<String, serde_json::Value>>,
+    attributes: Option<BTreeMap<String, serde_json::Value>>,
     action_refs: Vec<String>,
     technique_refs: Vec<ExternalReference>,
+    validate_reference_urls: bool,
 }
 
 impl BehaviorBuilder {
@@ -111,21 +160,47 @@ impl BehaviorBuilder {
         self
     }
 
-    pub fn name(mut self, name: crate::vocab_large::Behavior) -> Self {
-        self.name = Some(name);
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5("behavior", namespace, name))
+    }
+
+    pub fn name(mut self, name: impl Into<OpenVocab<crate::vocab_large::Behavior>>) -> Self {
+        self.name = Some(name.into());
         self
     }
 
+    /// Sets the name from a string
+    ///
+    /// Values from the behavior vocabulary become spec names; anything else is
+    /// kept as a custom name.
+    pub fn name_str(self, name: &str) -> Self {
+        self.name(OpenVocab::parse(name))
+    }
+
     pub fn description(mut self, desc: impl Into<String>) -> Self {
         self.description = Some(desc.into());
         self
     }
 
-    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
-        self.timestamp = Some(timestamp);
+    pub fn timestamp(mut self, timestamp: impl Into<DateTime<Utc>>) -> Self {
+        self.timestamp = Some(timestamp.into());
         self
     }
 
+    /// Copies the key/value pairs of a flat JSON object into `attributes`
+    ///
+    /// Returns an error if the value is not a JSON object.
+    pub fn attributes_from_value(mut self, value: serde_json::Value) -> Result<Self> {
+        let attributes = crate::common::attributes_from_value(value)?;
+        self.attributes
+            .get_or_insert_with(BTreeMap::new)
+            .extend(attributes);
+        Ok(self)
+    }
+
     pub fn add_action_ref(mut self, ref_id: impl Into<String>) -> Self {
         self.action_refs.push(ref_id.into());
         self
@@ -136,8 +211,63 @@ impl BehaviorBuilder {
         self
     }
 
+    /// Adds several technique references
+    pub fn add_technique_refs(
+        mut self,
+        references: impl IntoIterator<Item = ExternalReference>,
+    ) -> Self {
+        self.technique_refs.extend(references);
+        self
+    }
+
+    /// Sets all technique references, replacing any added so far
+    pub fn set_technique_refs(mut self, references: Vec<ExternalReference>) -> Self {
+        self.technique_refs = references;
+        self
+    }
+
+    /// Checks the URLs of technique references when building (default: off)
+    ///
+    /// See [`ExternalReference::validate_url`].
+    pub fn validate_reference_urls(mut self, validate: bool) -> Self {
+        self.validate_reference_urls = validate;
+        self
+    }
+
+    /// Returns the behavior name set so far
+    pub fn name_ref(&self) -> Option<&OpenVocab<crate::vocab_large::Behavior>> {
+        self.name.as_ref()
+    }
+
+    /// Returns the description set so far
+    pub fn description_ref(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the action references added so far
+    pub fn action_refs(&self) -> &[String] {
+        &self.action_refs
+    }
+
+    /// Returns the technique references added so far
+    pub fn technique_refs(&self) -> &[ExternalReference] {
+        &self.technique_refs
+    }
+
+    /// Builds a preview of the Behavior without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<Behavior> {
+        self.clone().build()
+    }
+
     pub fn build(self) -> Result<Behavior> {
         let name = self.name.ok_or(MaecError::MissingField("name"))?;
+        if self.validate_reference_urls {
+            for reference in &self.technique_refs {
+                reference.validate_url()?;
+            }
+        }
 
         let mut common = crate::common::CommonProperties::new("behavior", None);
         if let Some(id) = self.id {
@@ -158,3 +288,133 @@ impl BehaviorBuilder {
         Ok(behavior)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocab_large::Behavior as BehaviorVocab;
+
+    #[test]
+    fn test_attributes_from_value() {
+        let behavior = Behavior::builder()
+            .name(BehaviorVocab::SendBeacon)
+            .attributes_from_value(serde_json::json!({"c2_url": "http://example.com"}))
+            .unwrap()
+            .attributes_from_value(serde_json::json!({"mutex": "Global\\abc"}))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let attributes = behavior.attributes.unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes["c2_url"], "http://example.com");
+
+        let result =
+            Behavior::builder().attributes_from_value(serde_json::json!(["not", "a", "map"]));
+        assert!(matches!(result, Err(MaecError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_builder_inspection() {
+        let builder = Behavior::builder()
+            .id("behavior--550e8400-e29b-41d4-a716-446655440000")
+            .add_action_ref("malware-action--550e8400-e29b-41d4-a716-446655440001");
+        assert!(builder.name_ref().is_none());
+        assert!(builder.peek().is_err());
+
+        let builder = builder.name(BehaviorVocab::SendBeacon);
+        assert_eq!(builder.name_ref(), Some(&BehaviorVocab::SendBeacon.into()));
+        assert_eq!(builder.action_refs().len(), 1);
+
+        let preview = builder.peek().unwrap();
+        let built = builder.build().unwrap();
+        assert_eq!(preview.common.id, built.common.id);
+        assert_eq!(preview.action_refs, built.action_refs);
+    }
+
+    #[test]
+    fn test_custom_behavior_name() {
+        let spec = Behavior::new(BehaviorVocab::SendBeacon);
+        assert!(spec.is_spec_behavior());
+
+        let custom = Behavior::new(OpenVocab::Custom("mine-cryptocurrency".to_string()));
+        assert!(!custom.is_spec_behavior());
+
+        let json = serde_json::to_string(&custom).unwrap();
+        assert!(json.contains("\"name\":\"mine-cryptocurrency\""));
+        let parsed: Behavior = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, custom);
+    }
+
+    #[test]
+    fn test_name_str() {
+        let spec = Behavior::builder().name_str("send-beacon").build().unwrap();
+        assert_eq!(spec.name, BehaviorVocab::SendBeacon);
+
+        let custom = Behavior::builder().name_str("phone-home").build().unwrap();
+        assert_eq!(custom.name, OpenVocab::Custom("phone-home".to_string()));
+    }
+
+    #[test]
+    fn test_validate_attributes() {
+        let behavior = Behavior::builder()
+            .name(BehaviorVocab::SendBeacon)
+            .attributes_from_value(serde_json::json!({
+                "port-number": 443,
+                "network-protocol": "https",
+                "c2_url": "http://example.com",
+                "mutex": "Global\\abc"
+            }))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(behavior.validate_attributes(), vec!["c2_url", "mutex"]);
+
+        let mut custom = behavior.clone();
+        custom.name = OpenVocab::Custom("beacon-home".to_string());
+        assert!(custom.validate_attributes().is_empty());
+
+        let unmapped = Behavior::builder()
+            .name(BehaviorVocab::CheckForPayload)
+            .attributes_from_value(serde_json::json!({"anything": 1}))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(unmapped.validate_attributes().is_empty());
+    }
+
+    #[test]
+    fn test_bulk_technique_refs() {
+        let injection = ExternalReference::attack_technique("T1055", "Process Injection");
+        let builder = Behavior::builder()
+            .name(BehaviorVocab::InstallBackdoor)
+            .add_technique_ref(injection.clone())
+            .add_technique_refs([
+                ExternalReference::attack_technique("T1543", "Create or Modify System Process"),
+                ExternalReference::attack_technique("T1547", "Boot or Logon Autostart Execution"),
+            ]);
+        assert_eq!(builder.technique_refs().len(), 3);
+
+        let behavior = builder.set_technique_refs(vec![injection]).build().unwrap();
+        assert_eq!(behavior.technique_refs.len(), 1);
+        assert_eq!(
+            behavior.technique_refs[0].external_id.as_deref(),
+            Some("T1055")
+        );
+    }
+
+    #[test]
+    fn test_builder_validates_reference_urls() {
+        let mut reference = ExternalReference::new("mitre-attack");
+        reference.url = Some("hxxp://attack.mitre.org/techniques/T1486".to_string());
+        let builder = Behavior::builder()
+            .name(BehaviorVocab::EncryptFiles)
+            .add_technique_ref(reference);
+
+        assert!(builder.peek().is_ok());
+        assert!(matches!(
+            builder.validate_reference_urls(true).build(),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+}
@@ -1,109 +1,653 @@
-//! MAEC Capability type implementation
-
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-use crate::common::ExternalReference;
-use crate::error::Result;
-
-/// MAEC Capability
-///
-/// Captures details of a Capability that may be implemented in the malware instance.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub struct Capability {
-    /// Name of the capability
-    pub name: String,
-
-    /// Refined sub-capabilities
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub refined_capabilities: Vec<Capability>,
-
-    /// Textual description
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-
-    /// Capability attributes as key/value pairs
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub attributes: Option<HashMap<String, serde_json::Value>>,
-
-    /// References to behaviors implementing this capability
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub behavior_refs: Vec<String>,
-
-    /// External references (ATT&CK tactics, etc.)
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub references: Vec<ExternalReference>,
-}
-
-impl Capability {
-    /// Creates a new Capability with just a name
-    pub fn new(name: impl Into<String>) -> Self {
-        Self {
-            name: name.into(),
-            refined_capabilities: vec![],
-            description: None,
-            attributes: None,
-            behavior_refs: vec![],
-            references: vec![],
-        }
-    }
-
-    /// Creates a new Capability builder
-    pub fn builder() -> CapabilityBuilder {
-        CapabilityBuilder::default()
-    }
-}
-
-/// Builder for Capability objects
-#[derive(Debug, Default)]
-pub struct CapabilityBuilder {
-    name: Option<String>,
-    refined_capabilities: Vec<Capability>,
-    description: Option<String>,
-    attributes: Option<HashMap<String, serde_json::Value>>,
-    behavior_refs: Vec<String>,
-    references: Vec<ExternalReference>,
-}
-
-impl CapabilityBuilder {
-    pub fn name(mut self, name: impl Into<String>) -> Self {
-        self.name = Some(name.into());
-        self
-    }
-
-    pub fn description(mut self, desc: impl Into<String>) -> Self {
-        self.description = Some(desc.into());
-        self
-    }
-
-    pub fn add_refined_capability(mut self, capability: Capability) -> Self {
-        self.refined_capabilities.push(capability);
-        self
-    }
-
-    pub fn add_behavior_ref(mut self, ref_id: impl Into<String>) -> Self {
-        self.behavior_refs.push(ref_id.into());
-        self
-    }
-
-    pub fn add_reference(mut self, reference: ExternalReference) -> Self {
-        self.references.push(reference);
-        self
-    }
-
-    pub fn build(self) -> Result<Capability> {
-        let name = self
-            .name
-            .ok_or(crate::error::MaecError::MissingField("name"))?;
-
-        Ok(Capability {
-            name,
-            refined_capabilities: self.refined_capabilities,
-            description: self.description,
-            attributes: self.attributes,
-            behavior_refs: self.behavior_refs,
-            references: self.references,
-        })
-    }
-}
+//! MAEC Capability type implementation
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::common::ExternalReference;
+use crate::error::Result;
+
+/// MAEC Capability
+///
+/// Captures details of a Capability that may be implemented in the malware instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct Capability {
+    /// Name of the capability
+    pub name: String,
+
+    /// Refined sub-capabilities
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "refinedCapabilities"
+    )]
+    pub refined_capabilities: Vec<Capability>,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Capability attributes as key/value pairs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<BTreeMap<String, serde_json::Value>>,
+
+    /// References to behaviors implementing this capability
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "behaviorRefs")]
+    pub behavior_refs: Vec<String>,
+
+    /// External references (ATT&CK tactics, etc.)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ExternalReference>,
+}
+
+impl Capability {
+    /// Creates a new Capability with just a name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            refined_capabilities: vec![],
+            description: None,
+            attributes: None,
+            behavior_refs: vec![],
+            references: vec![],
+        }
+    }
+
+    /// Creates a Capability with the given refinements
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Capability;
+    ///
+    /// let tree = Capability::tree(
+    ///     "persistence",
+    ///     vec![
+    ///         Capability::new("continuous-execution"),
+    ///         Capability::new("system-re-infection"),
+    ///     ],
+    /// );
+    /// assert_eq!(tree.refined_capabilities.len(), 2);
+    /// ```
+    pub fn tree(name: impl Into<String>, children: Vec<Capability>) -> Self {
+        Self {
+            refined_capabilities: children,
+            ..Self::new(name)
+        }
+    }
+
+    /// Creates a chain of capabilities from a dotted path
+    ///
+    /// Each `.`-separated segment becomes a capability refined by the next
+    /// one; this is the inverse of [`flatten_paths`](Self::flatten_paths) for
+    /// a single leaf. Names containing `.` therefore cannot be represented,
+    /// and empty segments yield capabilities that fail
+    /// [`validate`](Self::validate).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Capability;
+    ///
+    /// let capability = Capability::from_path("command-and-control.determine-c2-server");
+    /// assert_eq!(capability.name, "command-and-control");
+    /// assert_eq!(
+    ///     capability.flatten_paths(),
+    ///     vec!["command-and-control.determine-c2-server"]
+    /// );
+    /// ```
+    pub fn from_path(path: &str) -> Self {
+        let mut segments = path.rsplit('.');
+        let leaf = Capability::new(segments.next().unwrap_or_default());
+        segments.fold(leaf, |child, name| Capability::tree(name, vec![child]))
+    }
+
+    /// Returns the dotted path from this capability to each leaf refinement
+    ///
+    /// Paths are listed depth-first in refinement order. A capability without
+    /// refinements yields just its own name.
+    pub fn flatten_paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        let mut pending = vec![(self, self.name.clone())];
+        while let Some((capability, path)) = pending.pop() {
+            if capability.refined_capabilities.is_empty() {
+                paths.push(path);
+                continue;
+            }
+            pending.extend(
+                capability
+                    .refined_capabilities
+                    .iter()
+                    .rev()
+                    .map(|child| (child, format!("{}.{}", path, child.name))),
+            );
+        }
+        paths
+    }
+
+    /// Creates a new Capability builder
+    pub fn builder() -> CapabilityBuilder {
+        CapabilityBuilder::default()
+    }
+
+    /// Returns the name as a [capability vocabulary](crate::CapabilityVocab)
+    /// value, or `None` for custom names
+    pub fn typed_name(&self) -> Option<crate::CapabilityVocab> {
+        self.name.parse().ok()
+    }
+
+    /// Returns whether the name is defined by the capability vocabulary
+    pub fn is_standard(&self) -> bool {
+        self.typed_name().is_some()
+    }
+
+    /// Validates that this capability and all refinements have a name
+    ///
+    /// Returns `MaecError::ValidationError` for the first blank name found.
+    pub fn validate(&self) -> Result<()> {
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            if capability.name.trim().is_empty() {
+                return Err(crate::error::MaecError::ValidationError(
+                    "capability name must not be empty".to_string(),
+                ));
+            }
+            pending.extend(capability.refined_capabilities.iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Checks that referenced behaviors fit the capability
+    ///
+    /// Applies to this capability and all refinements. A capability named in
+    /// the [capability vocabulary](crate::CapabilityVocab) is flagged when
+    /// none of its referenced behaviors is related to it per
+    /// [`related_capabilities`](crate::BehaviorVocab::related_capabilities).
+    /// Behaviors that are missing from `package`, have custom names or have no
+    /// entry in the affinity table are not judged. Returns one warning per
+    /// mismatched capability.
+    pub fn validate_behavior_affinity(&self, package: &crate::Package) -> Vec<String> {
+        let behaviors: HashMap<&str, &crate::Behavior> = package
+            .behaviors()
+            .into_iter()
+            .map(|behavior| (behavior.common.id.as_str(), behavior))
+            .collect();
+
+        let mut warnings = vec![];
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            pending.extend(capability.refined_capabilities.iter().rev());
+
+            let name = match capability.typed_name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let judged: Vec<&crate::vocab_large::Behavior> = capability
+                .behavior_refs
+                .iter()
+                .filter_map(|r| behaviors.get(r.as_str())?.name.known())
+                .filter(|behavior| !behavior.related_capabilities().is_empty())
+                .collect();
+            if !judged.is_empty()
+                && !judged
+                    .iter()
+                    .any(|behavior| behavior.related_capabilities().contains(&name))
+            {
+                warnings.push(format!(
+                    "capability '{}' references no behavior related to it",
+                    capability.name
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Collects the behavior references of this capability and all refinements
+    ///
+    /// Uses an explicit work-list so arbitrarily deep refinement trees cannot
+    /// overflow the stack.
+    pub(crate) fn collect_behavior_refs<'a>(&'a self, refs: &mut Vec<(&'static str, &'a str)>) {
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            refs.extend(
+                capability
+                    .behavior_refs
+                    .iter()
+                    .map(|r| ("behavior_refs", r.as_str())),
+            );
+            pending.extend(capability.refined_capabilities.iter().rev());
+        }
+    }
+
+    /// Iterates over the `behavior_refs` of `capabilities` and all their
+    /// refinements, in the same order as
+    /// [`collect_behavior_refs`](Self::collect_behavior_refs)
+    pub(crate) fn behavior_refs_of(capabilities: &[Capability]) -> BehaviorRefs<'_> {
+        BehaviorRefs {
+            stack: vec![],
+            capabilities: capabilities.iter(),
+            refs: [].iter(),
+        }
+    }
+
+    /// Replaces behavior references in this capability and all refinements
+    ///
+    /// References found in `replacements` are rewritten to the mapped ID, and
+    /// references that become duplicates are dropped.
+    pub(crate) fn rewrite_behavior_refs(&mut self, replacements: &HashMap<String, String>) {
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            for r in &mut capability.behavior_refs {
+                if let Some(replacement) = replacements.get(r) {
+                    r.clone_from(replacement);
+                }
+            }
+            let mut seen = HashSet::new();
+            capability.behavior_refs.retain(|r| seen.insert(r.clone()));
+            pending.extend(&mut capability.refined_capabilities);
+        }
+    }
+
+    /// Collects the external references of this capability and all refinements
+    pub(crate) fn collect_references<'a>(&'a self, references: &mut Vec<&'a ExternalReference>) {
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            references.extend(&capability.references);
+            pending.extend(capability.refined_capabilities.iter().rev());
+        }
+    }
+
+    /// Collects the names of this capability and all refinements
+    pub(crate) fn collect_names<'a>(&'a self, names: &mut HashSet<&'a str>) {
+        let mut pending = vec![self];
+        while let Some(capability) = pending.pop() {
+            names.insert(capability.name.as_str());
+            pending.extend(&capability.refined_capabilities);
+        }
+    }
+}
+
+impl From<String> for Capability {
+    fn from(name: String) -> Self {
+        Capability::new(name)
+    }
+}
+
+impl From<&str> for Capability {
+    fn from(name: &str) -> Self {
+        Capability::new(name)
+    }
+}
+
+impl From<crate::CapabilityVocab> for Capability {
+    fn from(name: crate::CapabilityVocab) -> Self {
+        Capability::new(name.as_ref())
+    }
+}
+
+/// Builder for Capability objects
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityBuilder {
+    name: Option<String>,
+    refined_capabilities: Vec<Capability>,
+    description: Option<String>,
+    attributes: Option<BTreeMap<String, serde_json::Value>>,
+    behavior_refs: Vec<String>,
+    references: Vec<ExternalReference>,
+    validate_reference_urls: bool,
+}
+
+impl CapabilityBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the name from the capability vocabulary
+    pub fn typed_name(self, name: crate::CapabilityVocab) -> Self {
+        self.name(name.as_ref())
+    }
+
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    pub fn add_refined_capability(mut self, capability: impl Into<Capability>) -> Self {
+        self.refined_capabilities.push(capability.into());
+        self
+    }
+
+    /// Sets all refined capabilities
+    pub fn refined_capabilities(
+        mut self,
+        capabilities: impl IntoIterator<Item = Capability>,
+    ) -> Self {
+        self.refined_capabilities = capabilities.into_iter().collect();
+        self
+    }
+
+    /// Copies the key/value pairs of a JSON object into `attributes`
+    ///
+    /// Nested values are copied as they are, and keys set by an earlier call
+    /// are overwritten. Returns an error if the value is not a JSON object.
+    pub fn attributes_from_value(mut self, value: serde_json::Value) -> Result<Self> {
+        let attributes = crate::common::attributes_from_value(value)?;
+        self.attributes
+            .get_or_insert_with(BTreeMap::new)
+            .extend(attributes);
+        Ok(self)
+    }
+
+    pub fn add_behavior_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.behavior_refs.push(ref_id.into());
+        self
+    }
+
+    pub fn add_reference(mut self, reference: ExternalReference) -> Self {
+        self.references.push(reference);
+        self
+    }
+
+    /// Adds several external references
+    pub fn add_references(
+        mut self,
+        references: impl IntoIterator<Item = ExternalReference>,
+    ) -> Self {
+        self.references.extend(references);
+        self
+    }
+
+    /// Sets all external references
+    pub fn references(mut self, references: Vec<ExternalReference>) -> Self {
+        self.references = references;
+        self
+    }
+
+    /// Checks the URLs of external references when building (default: off)
+    ///
+    /// Only the references of this capability are checked, not those of its
+    /// refined capabilities. See [`ExternalReference::validate_url`].
+    pub fn validate_reference_urls(mut self, validate: bool) -> Self {
+        self.validate_reference_urls = validate;
+        self
+    }
+
+    /// Builds a preview of the Capability without consuming the builder
+    pub fn peek(&self) -> Result<Capability> {
+        self.clone().build()
+    }
+
+    pub fn build(self) -> Result<Capability> {
+        let name = self
+            .name
+            .ok_or(crate::error::MaecError::MissingField("name"))?;
+        if self.validate_reference_urls {
+            for reference in &self.references {
+                reference.validate_url()?;
+            }
+        }
+
+        let capability = Capability {
+            name,
+            refined_capabilities: self.refined_capabilities,
+            description: self.description,
+            attributes: self.attributes,
+            behavior_refs: self.behavior_refs,
+            references: self.references,
+        };
+        capability.validate()?;
+        Ok(capability)
+    }
+}
+
+/// Iterator over the behavior references of a capability forest
+///
+/// Only refinements that have siblings left to visit are kept on the stack,
+/// so forests without refinements are walked without allocating.
+pub(crate) struct BehaviorRefs<'a> {
+    stack: Vec<std::slice::Iter<'a, Capability>>,
+    capabilities: std::slice::Iter<'a, Capability>,
+    refs: std::slice::Iter<'a, String>,
+}
+
+impl<'a> Iterator for BehaviorRefs<'a> {
+    type Item = (&'static str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(r) = self.refs.next() {
+                return Some(("behavior_refs", r.as_str()));
+            }
+            let capability = loop {
+                match self.capabilities.next() {
+                    Some(capability) => break capability,
+                    None => self.capabilities = self.stack.pop()?,
+                }
+            };
+            self.refs = capability.behavior_refs.iter();
+            if !capability.refined_capabilities.is_empty() {
+                let siblings = std::mem::replace(
+                    &mut self.capabilities,
+                    capability.refined_capabilities.iter(),
+                );
+                if siblings.len() > 0 {
+                    self.stack.push(siblings);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BehaviorVocab;
+
+    #[test]
+    fn test_typed_name() {
+        let capability = Capability::builder()
+            .typed_name(crate::CapabilityVocab::AntiBehavioralAnalysis)
+            .build()
+            .unwrap();
+        assert_eq!(capability.name, "anti-behavioral-analysis");
+        assert_eq!(
+            capability.typed_name(),
+            Some(crate::CapabilityVocab::AntiBehavioralAnalysis)
+        );
+        assert_eq!(
+            Capability::new("anti-behavioral-anlysis").typed_name(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_name_checks() {
+        assert!(Capability::new("persistence").is_standard());
+        assert!(!Capability::new("mine-cryptocurrency").is_standard());
+
+        for name in ["", "  \t"] {
+            assert!(matches!(
+                Capability::builder().name(name).build(),
+                Err(crate::error::MaecError::ValidationError(_))
+            ));
+        }
+
+        let blank_refinement = Capability::builder()
+            .name("persistence")
+            .add_refined_capability(Capability::tree(
+                "continuous-execution",
+                vec![Capability::new(" ")],
+            ))
+            .build();
+        assert!(matches!(
+            blank_refinement,
+            Err(crate::error::MaecError::ValidationError(_))
+        ));
+
+        assert!(Capability::new("").validate().is_err());
+        assert!(Capability::from_path("").validate().is_err());
+        assert!(Capability::from_path("persistence..x").validate().is_err());
+        assert!(Capability::from_path("persistence.x").validate().is_ok());
+    }
+
+    #[test]
+    fn test_refined_capabilities() {
+        let capability = Capability::builder()
+            .name("anti-detection")
+            .add_refined_capability(Capability::new("dropped"))
+            .refined_capabilities(
+                ["hide-executing-code", "hide-malware-artifacts"].map(Capability::new),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            capability,
+            Capability::tree(
+                "anti-detection",
+                vec![
+                    Capability::new("hide-executing-code"),
+                    Capability::new("hide-malware-artifacts"),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_bulk_references() {
+        let tactic = |id: &str| {
+            let mut reference = ExternalReference::new("mitre-attack");
+            reference.external_id = Some(id.to_string());
+            reference
+        };
+        let capability = Capability::builder()
+            .name("persistence")
+            .add_reference(tactic("TA0001"))
+            .references(vec![tactic("TA0003")])
+            .add_references([tactic("TA0004"), tactic("TA0005")])
+            .build()
+            .unwrap();
+        let ids: Vec<_> = capability
+            .references
+            .iter()
+            .filter_map(|r| r.external_id.as_deref())
+            .collect();
+        assert_eq!(ids, vec!["TA0003", "TA0004", "TA0005"]);
+    }
+
+    #[test]
+    fn test_validate_behavior_affinity() {
+        let beacon = crate::Behavior::new(BehaviorVocab::SendBeacon);
+        let encrypt = crate::Behavior::new(BehaviorVocab::EncryptFiles);
+        let custom = crate::Behavior::new(crate::OpenVocab::Custom("phone-home".to_string()));
+        let package = crate::Package::new()
+            .with_object(crate::MaecObjectType::Behavior(beacon.clone()))
+            .with_object(crate::MaecObjectType::Behavior(encrypt.clone()))
+            .with_object(crate::MaecObjectType::Behavior(custom.clone()));
+        let c2 = |refs: &[&crate::Behavior]| {
+            let mut capability = Capability::new("command-and-control");
+            capability.behavior_refs = refs.iter().map(|b| b.common.id.clone()).collect();
+            capability
+        };
+
+        assert!(c2(&[&encrypt, &beacon])
+            .validate_behavior_affinity(&package)
+            .is_empty());
+        assert!(c2(&[&custom])
+            .validate_behavior_affinity(&package)
+            .is_empty());
+        assert!(c2(&[]).validate_behavior_affinity(&package).is_empty());
+
+        let miswired = Capability::tree("anti-detection", vec![c2(&[&encrypt, &custom])]);
+        assert_eq!(
+            miswired.validate_behavior_affinity(&package),
+            vec!["capability 'command-and-control' references no behavior related to it"]
+        );
+    }
+
+    #[test]
+    fn test_dotted_paths() {
+        let capability = Capability::tree(
+            "persistence",
+            vec![
+                Capability::tree(
+                    "continuous-execution",
+                    vec![Capability::new("run-at-startup")],
+                ),
+                Capability::new("system-re-infection"),
+            ],
+        );
+        assert_eq!(
+            capability.flatten_paths(),
+            vec![
+                "persistence.continuous-execution.run-at-startup",
+                "persistence.system-re-infection",
+            ]
+        );
+        assert_eq!(
+            Capability::from_path("persistence.continuous-execution.run-at-startup"),
+            Capability::tree(
+                "persistence",
+                vec![Capability::tree(
+                    "continuous-execution",
+                    vec![Capability::new("run-at-startup")]
+                )]
+            )
+        );
+        assert_eq!(
+            Capability::from_path("persistence"),
+            Capability::new("persistence")
+        );
+        assert_eq!(
+            Capability::new("persistence").flatten_paths(),
+            vec!["persistence"]
+        );
+    }
+
+    #[test]
+    fn test_attributes_from_value() {
+        let capability = Capability::builder()
+            .name("command-and-control")
+            .attributes_from_value(serde_json::json!({
+                "protocol": "https",
+                "servers": [{"host": "example.com", "ports": [443, 8443]}],
+            }))
+            .unwrap()
+            .attributes_from_value(serde_json::json!({"protocol": "dns", "jitter": null}))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let attributes = capability.attributes.unwrap();
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes["protocol"], "dns");
+        assert_eq!(attributes["servers"][0]["ports"][1], 8443);
+        assert!(attributes["jitter"].is_null());
+
+        for invalid in [
+            serde_json::json!(["protocol", "https"]),
+            serde_json::json!("protocol=https"),
+            serde_json::json!(null),
+        ] {
+            assert!(matches!(
+                Capability::builder().attributes_from_value(invalid),
+                Err(crate::error::MaecError::ValidationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_builder_validates_reference_urls() {
+        let mut reference = ExternalReference::new("vendor-report");
+        reference.url = Some("hxxp://example.com/report".to_string());
+        let builder = Capability::builder()
+            .name("persistence")
+            .add_reference(reference);
+
+        assert!(builder.peek().is_ok());
+        assert!(matches!(
+            builder.validate_reference_urls(true).build(),
+            Err(crate::error::MaecError::ValidationError(_))
+        ));
+    }
+}
@@ -1,119 +1,240 @@
-//! MAEC Collection object
-
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-
-use crate::common::{CommonProperties, MaecObject};
-use crate::error::{MaecError, Result};
-
-/// MAEC Collection
-///
-/// Represents a grouping of related MAEC objects.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub struct Collection {
-    /// Common MAEC properties
-    #[serde(flatten)]
-    pub common: CommonProperties,
-
-    /// Name of the collection
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-
-    /// Textual description
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-}
-
-impl Collection {
-    /// Creates a new Collection builder
-    pub fn builder() -> CollectionBuilder {
-        CollectionBuilder::default()
-    }
-
-    /// Creates a minimal Collection
-    pub fn new() -> Self {
-        Self {
-            common: CommonProperties::new("collection", None),
-            name: None,
-            description: None,
-        }
-    }
-
-    /// Validates the Collection structure
-    pub fn validate(&self) -> Result<()> {
-        if self.common.r#type != "collection" {
-            return Err(MaecError::ValidationError(format!(
-                "type must be 'collection', got '{}'",
-                self.common.r#type
-            )));
-        }
-
-        if !crate::common::is_valid_maec_id(&self.common.id) {
-            return Err(MaecError::InvalidId(self.common.id.clone()));
-        }
-
-        Ok(())
-    }
-}
-
-impl Default for Collection {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl MaecObject for Collection {
-    fn id(&self) -> &str {
-        &self.common.id
-    }
-
-    fn type_(&self) -> &str {
-        &self.common.r#type
-    }
-
-    fn created(&self) -> DateTime<Utc> {
-        self.common.created
-    }
-}
-
-/// Builder for Collection objects
-#[derive(Debug, Default)]
-pub struct CollectionBuilder {
-    id: Option<String>,
-    name: Option<String>,
-    description: Option<String>,
-}
-
-impl CollectionBuilder {
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.id = Some(id.into());
-        self
-    }
-
-    pub fn name(mut self, name: impl Into<String>) -> Self {
-        self.name = Some(name.into());
-        self
-    }
-
-    pub fn description(mut self, desc: impl Into<String>) -> Self {
-        self.description = Some(desc.into());
-        self
-    }
-
-    pub fn build(self) -> Result<Collection> {
-        let mut common = CommonProperties::new("collection", None);
-        if let Some(id) = self.id {
-            common.id = id;
-        }
-
-        let collection = Collection {
-            common,
-            name: self.name,
-            description: self.description,
-        };
-
-        collection.validate()?;
-        Ok(collection)
-    }
-}
+//! MAEC Collection object
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::common::{CommonProperties, MaecObject};
+use crate::error::{MaecError, Result};
+
+/// MAEC Collection
+///
+/// Represents a grouping of related MAEC objects.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct Collection {
+    /// Common MAEC properties
+    #[serde(flatten)]
+    pub common: CommonProperties,
+
+    /// Name of the collection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Ordered references to the MAEC objects in the collection
+    ///
+    /// Order is significant (e.g. the steps of a kill chain) and each object
+    /// may appear only once.
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "entityRefs")]
+    pub entity_refs: Vec<String>,
+}
+
+impl Collection {
+    /// Creates a new Collection builder
+    pub fn builder() -> CollectionBuilder {
+        CollectionBuilder::default()
+    }
+
+    /// Creates a minimal Collection
+    pub fn new() -> Self {
+        Self {
+            common: CommonProperties::new("collection", None),
+            name: None,
+            description: None,
+            entity_refs: vec![],
+        }
+    }
+
+    /// Validates the Collection structure
+    pub fn validate(&self) -> Result<()> {
+        if self.common.r#type != "collection" {
+            return Err(MaecError::ValidationError(format!(
+                "type must be 'collection', got '{}'",
+                self.common.r#type
+            )));
+        }
+
+        if !crate::common::is_valid_maec_id(&self.common.id) {
+            return Err(MaecError::InvalidId(self.common.id.clone()));
+        }
+        self.common.validate_timestamps()?;
+
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = self.entity_refs.iter().find(|r| !seen.insert(r.as_str())) {
+            return Err(MaecError::ValidationError(format!(
+                "collection {} lists '{}' more than once",
+                self.common.id, duplicate
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a member reference at `index`, shifting later members back
+    ///
+    /// Returns an error if the reference is already a member or if `index`
+    /// is greater than the number of members.
+    pub fn insert_at(&mut self, index: usize, entity_ref: impl Into<String>) -> Result<()> {
+        let entity_ref = entity_ref.into();
+        if self.position_of(&entity_ref).is_some() {
+            return Err(MaecError::ValidationError(format!(
+                "'{}' is already a member of collection {}",
+                entity_ref, self.common.id
+            )));
+        }
+        if index > self.entity_refs.len() {
+            return Err(MaecError::ValidationError(format!(
+                "index {} is out of bounds for a collection of {} members",
+                index,
+                self.entity_refs.len()
+            )));
+        }
+
+        self.entity_refs.insert(index, entity_ref);
+        Ok(())
+    }
+
+    /// Returns the position of a member reference, if present
+    pub fn position_of(&self, entity_ref: &str) -> Option<usize> {
+        self.entity_refs.iter().position(|r| r == entity_ref)
+    }
+
+    /// Applies a mutation to the collection and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
+}
+
+impl Default for Collection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+crate::common::impl_json_methods!(Collection);
+
+impl MaecObject for Collection {
+    fn id(&self) -> &str {
+        &self.common.id
+    }
+
+    fn type_(&self) -> &str {
+        &self.common.r#type
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.common.created
+    }
+}
+
+/// Builder for Collection objects
+#[derive(Debug, Clone, Default)]
+pub struct CollectionBuilder {
+    id: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    entity_refs: Vec<String>,
+}
+
+impl CollectionBuilder {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5("collection", namespace, name))
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    /// Appends a member reference
+    pub fn add_entity_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.entity_refs.push(ref_id.into());
+        self
+    }
+
+    /// Builds a preview of the Collection without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<Collection> {
+        self.clone().build()
+    }
+
+    pub fn build(self) -> Result<Collection> {
+        let mut common = CommonProperties::new("collection", None);
+        if let Some(id) = self.id {
+            common.id = id;
+        }
+
+        let collection = Collection {
+            common,
+            name: self.name,
+            description: self.description,
+            entity_refs: self.entity_refs,
+        };
+
+        collection.validate()?;
+        Ok(collection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = "behavior--00000000-0000-4000-8000-000000000001";
+    const SECOND: &str = "behavior--00000000-0000-4000-8000-000000000002";
+    const THIRD: &str = "behavior--00000000-0000-4000-8000-000000000003";
+
+    #[test]
+    fn test_collection_ordering() {
+        let mut collection = Collection::builder()
+            .add_entity_ref(FIRST)
+            .add_entity_ref(THIRD)
+            .build()
+            .unwrap();
+
+        collection.insert_at(1, SECOND).unwrap();
+        assert_eq!(collection.entity_refs, vec![FIRST, SECOND, THIRD]);
+        assert_eq!(collection.position_of(THIRD), Some(2));
+        assert_eq!(collection.position_of("missing"), None);
+
+        assert!(collection.insert_at(0, THIRD).is_err());
+        assert!(collection.insert_at(9, "behavior--x").is_err());
+
+        let json = collection.to_json().unwrap();
+        assert_eq!(
+            Collection::from_json(&json).unwrap().entity_refs,
+            collection.entity_refs
+        );
+    }
+
+    #[test]
+    fn test_collection_rejects_duplicate_members() {
+        let result = Collection::builder()
+            .add_entity_ref(FIRST)
+            .add_entity_ref(FIRST)
+            .build();
+        assert!(matches!(result, Err(MaecError::ValidationError(_))));
+    }
+}
@@ -1,115 +1,195 @@
-//! MAEC Malware Action object
-
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-
-use crate::common::{CommonProperties, MaecObject};
-use crate::error::{MaecError, Result};
-use crate::vocab_large::MalwareAction as MalwareActionVocab;
-
-/// MAEC Malware Action
-///
-/// Represents a low-level action taken by malware (e.g., file operations, network connections).
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub struct MalwareAction {
-    /// Common MAEC properties
-    #[serde(flatten)]
-    pub common: CommonProperties,
-
-    /// Name of the action
-    pub name: MalwareActionVocab,
-
-    /// Textual description
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-}
-
-impl MalwareAction {
-    /// Creates a new MalwareAction builder
-    pub fn builder() -> MalwareActionBuilder {
-        MalwareActionBuilder::default()
-    }
-
-    /// Creates a minimal MalwareAction with just a name
-    pub fn new(name: MalwareActionVocab) -> Self {
-        Self {
-            common: CommonProperties::new("malware-action", None),
-            name,
-            description: None,
-        }
-    }
-
-    /// Validates the MalwareAction structure
-    pub fn validate(&self) -> Result<()> {
-        if self.common.r#type != "malware-action" {
-            return Err(MaecError::ValidationError(format!(
-                "type must be 'malware-action', got '{}'",
-                self.common.r#type
-            )));
-        }
-
-        if !crate::common::is_valid_maec_id(&self.common.id) {
-            return Err(MaecError::InvalidId(self.common.id.clone()));
-        }
-
-        Ok(())
-    }
-}
-
-impl MaecObject for MalwareAction {
-    fn id(&self) -> &str {
-        &self.common.id
-    }
-
-    fn type_(&self) -> &str {
-        &self.common.r#type
-    }
-
-    fn created(&self) -> DateTime<Utc> {
-        self.common.created
-    }
-}
-
-/// Builder for MalwareAction objects
-#[derive(Debug, Default)]
-pub struct MalwareActionBuilder {
-    id: Option<String>,
-    name: Option<MalwareActionVocab>,
-    description: Option<String>,
-}
-
-impl MalwareActionBuilder {
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.id = Some(id.into());
-        self
-    }
-
-    pub fn name(mut self, name: MalwareActionVocab) -> Self {
-        self.name = Some(name);
-        self
-    }
-
-    pub fn description(mut self, desc: impl Into<String>) -> Self {
-        self.description = Some(desc.into());
-        self
-    }
-
-    pub fn build(self) -> Result<MalwareAction> {
-        let name = self.name.ok_or(MaecError::MissingField("name"))?;
-
-        let mut common = CommonProperties::new("malware-action", None);
-        if let Some(id) = self.id {
-            common.id = id;
-        }
-
-        let action = MalwareAction {
-            common,
-            name,
-            description: self.description,
-        };
-
-        action.validate()?;
-        Ok(action)
-    }
-}
+//! MAEC Malware Action object
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{CommonProperties, MaecObject};
+use crate::error::{MaecError, Result};
+use crate::vocab_large::MalwareAction as MalwareActionVocab;
+
+/// MAEC Malware Action
+///
+/// Represents a low-level action taken by malware (e.g., file operations, network connections).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct MalwareAction {
+    /// Common MAEC properties
+    #[serde(flatten)]
+    pub common: CommonProperties,
+
+    /// Name of the action
+    pub name: MalwareActionVocab,
+
+    /// Timestamp when the action was executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// References to the observable objects the action operated on
+    ///
+    /// These are keys of the package's `observable_objects`, not MAEC IDs.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "inputObjectRefs"
+    )]
+    pub input_object_refs: Vec<String>,
+
+    /// References to the observable objects the action produced
+    ///
+    /// These are keys of the package's `observable_objects`, not MAEC IDs.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "outputObjectRefs"
+    )]
+    pub output_object_refs: Vec<String>,
+}
+
+impl MalwareAction {
+    /// Creates a new MalwareAction builder
+    pub fn builder() -> MalwareActionBuilder {
+        MalwareActionBuilder::default()
+    }
+
+    /// Creates a minimal MalwareAction with just a name
+    pub fn new(name: MalwareActionVocab) -> Self {
+        Self {
+            common: CommonProperties::new("malware-action", None),
+            name,
+            timestamp: None,
+            description: None,
+            input_object_refs: vec![],
+            output_object_refs: vec![],
+        }
+    }
+
+    /// Validates the MalwareAction structure
+    pub fn validate(&self) -> Result<()> {
+        if self.common.r#type != "malware-action" {
+            return Err(MaecError::ValidationError(format!(
+                "type must be 'malware-action', got '{}'",
+                self.common.r#type
+            )));
+        }
+
+        if !crate::common::is_valid_maec_id(&self.common.id) {
+            return Err(MaecError::InvalidId(self.common.id.clone()));
+        }
+        self.common.validate_timestamps()?;
+
+        Ok(())
+    }
+
+    /// Applies a mutation to the malware action and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
+}
+
+crate::common::impl_json_methods!(MalwareAction);
+
+impl MaecObject for MalwareAction {
+    fn id(&self) -> &str {
+        &self.common.id
+    }
+
+    fn type_(&self) -> &str {
+        &self.common.r#type
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.common.created
+    }
+}
+
+/// Builder for MalwareAction objects
+#[derive(Debug, Clone, Default)]
+pub struct MalwareActionBuilder {
+    id: Option<String>,
+    name: Option<MalwareActionVocab>,
+    timestamp: Option<DateTime<Utc>>,
+    description: Option<String>,
+    input_object_refs: Vec<String>,
+    output_object_refs: Vec<String>,
+}
+
+impl MalwareActionBuilder {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5(
+            "malware-action",
+            namespace,
+            name,
+        ))
+    }
+
+    pub fn name(mut self, name: impl Into<MalwareActionVocab>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: impl Into<DateTime<Utc>>) -> Self {
+        self.timestamp = Some(timestamp.into());
+        self
+    }
+
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    /// Adds a reference to an observable object the action operated on
+    pub fn add_input_object_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.input_object_refs.push(ref_id.into());
+        self
+    }
+
+    /// Adds a reference to an observable object the action produced
+    pub fn add_output_object_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.output_object_refs.push(ref_id.into());
+        self
+    }
+
+    /// Builds a preview of the MalwareAction without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<MalwareAction> {
+        self.clone().build()
+    }
+
+    pub fn build(self) -> Result<MalwareAction> {
+        let name = self.name.ok_or(MaecError::MissingField("name"))?;
+
+        let mut common = CommonProperties::new("malware-action", None);
+        if let Some(id) = self.id {
+            common.id = id;
+        }
+
+        let action = MalwareAction {
+            common,
+            name,
+            timestamp: self.timestamp,
+            description: self.description,
+            input_object_refs: self.input_object_refs,
+            output_object_refs: self.output_object_refs,
+        };
+
+        action.validate()?;
+        Ok(action)
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use crate::common::{ExternalReference, MaecObject};
 use crate::error::{MaecError, Result};
 use crate::objects::types::{FieldData, Name};
+use crate::vocab::ConfidenceMeasure;
 use crate::Capability;
 use chrono::{DateTime, Utc};
 
@@ -30,7 +31,7 @@ use chrono::{DateTime, Utc};
 ///
 /// assert_eq!(family.name.value, "WannaCry");
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub struct MalwareFamily {
     /// Common MAEC properties
@@ -53,23 +54,39 @@ pub struct MalwareFamily {
     pub description: Option<String>,
 
     /// Field data (delivery vectors, timestamps)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "fieldData")]
     pub field_data: Option<FieldData>,
 
     /// Strings common to all family members
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "commonStrings"
+    )]
     pub common_strings: Vec<String>,
 
     /// Capabilities common to all family members
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "commonCapabilities"
+    )]
     pub common_capabilities: Vec<Capability>,
 
     /// References to common code artifacts (STIX artifact IDs)
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "commonCodeRefs"
+    )]
     pub common_code_refs: Vec<String>,
 
     /// References to common behavior IDs
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "commonBehaviorRefs"
+    )]
     pub common_behavior_refs: Vec<String>,
 
     /// External references (ATT&CK, research papers, etc.)
@@ -112,11 +129,49 @@ impl MalwareFamily {
         if !crate::common::is_valid_maec_id(&self.common.id) {
             return Err(MaecError::InvalidId(self.common.id.clone()));
         }
+        self.common.validate_timestamps()?;
+
+        for name in std::iter::once(&self.name).chain(&self.aliases) {
+            name.validate()?;
+        }
+        for capability in &self.common_capabilities {
+            capability.validate()?;
+        }
 
         Ok(())
     }
+
+    /// Returns the name followed by all aliases
+    pub fn all_names(&self) -> Vec<&Name> {
+        std::iter::once(&self.name).chain(&self.aliases).collect()
+    }
+
+    /// Returns whether the name or any alias matches `name`
+    ///
+    /// See [`Name::matches`] for the meaning of `normalized`.
+    pub fn matches_name(&self, name: &str, normalized: bool) -> bool {
+        self.all_names()
+            .into_iter()
+            .any(|candidate| candidate.matches(name, normalized))
+    }
+
+    /// Returns the confidence stored under `x_maec_confidence`, if any
+    pub fn confidence(&self) -> Option<ConfidenceMeasure> {
+        self.common.confidence()
+    }
+
+    /// Applies a mutation to the malware family and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
 }
 
+crate::common::impl_json_methods!(MalwareFamily);
+
 impl MaecObject for MalwareFamily {
     fn id(&self) -> &str {
         &self.common.id
@@ -132,7 +187,7 @@ impl MaecObject for MalwareFamily {
 }
 
 /// Builder for MalwareFamily objects
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct MalwareFamilyBuilder {
     id: Option<String>,
     name: Option<Name>,
@@ -145,6 +200,8 @@ pub struct MalwareFamilyBuilder {
     common_code_refs: Vec<String>,
     common_behavior_refs: Vec<String>,
     references: Vec<ExternalReference>,
+    confidence: Option<ConfidenceMeasure>,
+    validate_reference_urls: bool,
 }
 
 impl MalwareFamilyBuilder {
@@ -154,6 +211,17 @@ impl MalwareFamilyBuilder {
         self
     }
 
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5(
+            "malware-family",
+            namespace,
+            name,
+        ))
+    }
+
     /// Sets the family name (required)
     pub fn name(mut self, name: impl Into<Name>) -> Self {
         self.name = Some(name.into());
@@ -209,8 +277,8 @@ impl MalwareFamilyBuilder {
     }
 
     /// Adds a common capability
-    pub fn add_capability(mut self, capability: Capability) -> Self {
-        self.common_capabilities.push(capability);
+    pub fn add_capability(mut self, capability: impl Into<Capability>) -> Self {
+        self.common_capabilities.push(capability.into());
         self
     }
 
@@ -244,14 +312,61 @@ impl MalwareFamilyBuilder {
         self
     }
 
+    /// Sets the confidence, stored under `x_maec_confidence`
+    pub fn confidence(mut self, confidence: ConfidenceMeasure) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Checks the URLs of external references when building (default: off)
+    ///
+    /// See [`ExternalReference::validate_url`].
+    pub fn validate_reference_urls(mut self, validate: bool) -> Self {
+        self.validate_reference_urls = validate;
+        self
+    }
+
+    /// Builds a preview of the MalwareFamily without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<MalwareFamily> {
+        self.clone().build()
+    }
+
     /// Builds the MalwareFamily
-    pub fn build(self) -> Result<MalwareFamily> {
-        let name = self.name.ok_or(MaecError::MissingField("name"))?;
+    ///
+    /// A family needs a non-blank name or at least one alias: if the name is
+    /// missing or blank, the first non-blank alias is promoted to the name.
+    /// Returns `MaecError::MissingField("name")` if there is neither, and
+    /// `MaecError::ValidationError` for blank aliases.
+    pub fn build(mut self) -> Result<MalwareFamily> {
+        let name = match self.name.take().filter(|name| name.validate().is_ok()) {
+            Some(name) => name,
+            None => {
+                let index = self
+                    .aliases
+                    .iter()
+                    .position(|alias| alias.validate().is_ok())
+                    .ok_or(MaecError::MissingField("name"))?;
+                self.aliases.remove(index)
+            }
+        };
+        for alias in &self.aliases {
+            alias.validate()?;
+        }
+        if self.validate_reference_urls {
+            for reference in &self.references {
+                reference.validate_url()?;
+            }
+        }
 
         let mut common = crate::common::CommonProperties::new("malware-family", None);
         if let Some(id) = self.id {
             common.id = id;
         }
+        if let Some(confidence) = self.confidence {
+            common.set_confidence(confidence);
+        }
 
         let family = MalwareFamily {
             common,
@@ -276,6 +391,29 @@ impl MalwareFamilyBuilder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_builder_conversions() {
+        let family = MalwareFamily::builder()
+            .name("Emotet")
+            .add_capability("persistence")
+            .add_capability(crate::CapabilityVocab::AntiDetection)
+            .add_capability(Capability::new("mine-cryptocurrency"))
+            .build()
+            .unwrap();
+        let names: Vec<&str> = family
+            .common_capabilities
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["persistence", "anti-detection", "mine-cryptocurrency"]
+        );
+
+        let package = crate::Package::new().with_object(family);
+        assert_eq!(package.malware_families().len(), 1);
+    }
+
     #[test]
     fn test_malware_family_new() {
         let family = MalwareFamily::new("WannaCry");
@@ -303,8 +441,26 @@ mod tests {
 
     #[test]
     fn test_malware_family_validation() {
-        let family = MalwareFamily::new("Test");
+        let mut family = MalwareFamily::new("Test");
         assert!(family.validate().is_ok());
+
+        family.aliases.push(Name::new(" "));
+        assert!(family.validate().is_err());
+        family.aliases.clear();
+
+        let mut blank = family.clone();
+        blank.name = Name::new("");
+        assert!(blank.validate().is_err());
+        let json = serde_json::to_string(&blank).unwrap();
+        assert!(MalwareFamily::from_json(&json).is_err());
+
+        family
+            .common_capabilities
+            .push(Capability::from_path("persistence..continuous-execution"));
+        assert!(matches!(
+            family.validate(),
+            Err(MaecError::ValidationError(_))
+        ));
     }
 
     #[test]
@@ -321,4 +477,192 @@ mod tests {
         let deserialized: MalwareFamily = serde_json::from_str(&json).unwrap();
         assert_eq!(family, deserialized);
     }
+
+    #[test]
+    fn test_malware_family_pointer_access() {
+        let mut family = MalwareFamily::new("Emotet");
+        assert_eq!(
+            family.get_pointer("/name/value"),
+            Some(serde_json::json!("Emotet"))
+        );
+        assert_eq!(family.get_pointer("/missing"), None);
+
+        family
+            .set_pointer("/description", serde_json::json!("Banking trojan"))
+            .unwrap();
+        assert_eq!(family.description.as_deref(), Some("Banking trojan"));
+
+        let result = family.set_pointer("/type", serde_json::json!("package"));
+        assert!(result.is_err());
+        assert_eq!(family.common.r#type, "malware-family");
+    }
+
+    #[test]
+    fn test_apply_patch_preserves_custom_properties() {
+        let mut family = MalwareFamily::new("Emotet");
+        let extensions = serde_json::json!({
+            "x_acme_score": 87,
+            "x_acme_tags": ["banking", "loader"],
+            "x_acme_meta": {"feed": "alpha", "nested": {"seen": 3}},
+            "x_other_vendor": null
+        });
+        for (key, value) in extensions.as_object().unwrap() {
+            family
+                .common
+                .custom_properties
+                .insert(key.clone(), value.clone());
+        }
+        let before = family.common.custom_properties.clone();
+        let created = Utc::now() - chrono::Duration::hours(1);
+        family.common.created = created;
+        family.common.modified = created;
+
+        family
+            .apply_patch(&serde_json::json!({"description": "Banking trojan"}))
+            .unwrap();
+        assert_eq!(family.description.as_deref(), Some("Banking trojan"));
+        assert_eq!(family.common.custom_properties, before);
+        assert_eq!(family.common.created, created);
+        assert!(family.common.modified > created);
+        assert!(family.common.validate_timestamps().is_ok());
+
+        family
+            .apply_patch(&serde_json::json!({
+                "x_acme_meta": {"feed": "beta"},
+                "x_acme_score": null
+            }))
+            .unwrap();
+        let properties = &family.common.custom_properties;
+        assert!(!properties.contains_key("x_acme_score"));
+        assert_eq!(
+            properties["x_acme_meta"],
+            serde_json::json!({"feed": "beta", "nested": {"seen": 3}})
+        );
+        assert_eq!(properties["x_acme_tags"], before["x_acme_tags"]);
+
+        let result = family.apply_patch(&serde_json::json!({"type": "package"}));
+        assert!(result.is_err());
+        assert_eq!(family.common.r#type, "malware-family");
+    }
+
+    #[test]
+    fn test_malware_family_update_bumps_modified() {
+        let mut family = MalwareFamily::new("Emotet");
+        let original_created = family.common.created;
+        let original_modified = family.common.modified;
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        family.update(|f| f.labels.push("trojan".to_string()));
+
+        assert_eq!(family.labels, vec!["trojan".to_string()]);
+        assert_eq!(family.common.created, original_created);
+        assert!(family.common.modified > original_modified);
+    }
+
+    #[test]
+    fn test_malware_family_confidence() {
+        let family = MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .confidence(ConfidenceMeasure::High)
+            .build()
+            .unwrap();
+
+        assert_eq!(family.confidence(), Some(ConfidenceMeasure::High));
+        assert_eq!(
+            family.common.custom_properties.get("x_maec_confidence"),
+            Some(&serde_json::json!("high"))
+        );
+        assert_eq!(MalwareFamily::new("Emotet").confidence(), None);
+    }
+
+    #[test]
+    fn test_malware_family_builder_rejects_empty_names() {
+        assert!(matches!(
+            MalwareFamily::builder().name(Name::new(" ")).build(),
+            Err(MaecError::MissingField("name"))
+        ));
+        assert!(MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .add_alias(Name::new(""))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_malware_family_builder_requires_a_name() {
+        assert!(matches!(
+            MalwareFamily::builder().build(),
+            Err(MaecError::MissingField("name"))
+        ));
+        assert!(matches!(
+            MalwareFamily::builder().name(Name::new("  ")).build(),
+            Err(MaecError::MissingField("name"))
+        ));
+
+        assert!(matches!(
+            MalwareFamily::builder().add_alias(Name::new(" ")).build(),
+            Err(MaecError::MissingField("name"))
+        ));
+
+        // The first non-blank alias stands in for a missing or blank name
+        let family = MalwareFamily::builder()
+            .add_alias(Name::new("Geodo"))
+            .add_alias(Name::new("Heodo"))
+            .build()
+            .unwrap();
+        assert_eq!(family.name.value, "Geodo");
+        assert_eq!(family.aliases, vec![Name::new("Heodo")]);
+
+        let family = MalwareFamily::builder()
+            .name(Name::new(""))
+            .add_alias(Name::new("Geodo"))
+            .build()
+            .unwrap();
+        assert_eq!(family.name.value, "Geodo");
+        assert!(family.aliases.is_empty());
+
+        // Blank aliases left over after promotion are still rejected
+        assert!(matches!(
+            MalwareFamily::builder()
+                .add_alias(Name::new(""))
+                .add_alias(Name::new("Geodo"))
+                .build(),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_builder_validates_reference_urls() {
+        let mut reference = ExternalReference::new("vendor-report");
+        reference.url = Some("hxxp://example.com/report".to_string());
+        let builder = MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .add_reference(reference);
+
+        assert!(builder.peek().is_ok());
+        assert!(matches!(
+            builder.validate_reference_urls(true).build(),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_all_names() {
+        let family = MalwareFamily::builder()
+            .name(Name::new("Emotet"))
+            .add_alias(Name::new("Geodo"))
+            .build()
+            .unwrap();
+        let names: Vec<&str> = family
+            .all_names()
+            .iter()
+            .map(|n| n.value.as_str())
+            .collect();
+        assert_eq!(names, vec!["Emotet", "Geodo"]);
+
+        assert!(family.matches_name("Geodo", false));
+        assert!(!family.matches_name(" geodo", false));
+        assert!(family.matches_name(" geodo", true));
+        assert!(!family.matches_name("Heodo", true));
+    }
 }
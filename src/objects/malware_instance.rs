@@ -1,204 +1,789 @@
-//! MAEC Malware Instance object implementation
-
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-
-use crate::common::MaecObject;
-use crate::error::{MaecError, Result};
-use crate::objects::types::{FieldData, Name};
-use crate::Capability;
-
-/// MAEC Malware Instance
-///
-/// A Malware Instance can be thought of as a single member of a Malware Family
-/// that is typically packaged as a binary.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub struct MalwareInstance {
-    /// Common MAEC properties
-    #[serde(flatten)]
-    pub common: crate::common::CommonProperties,
-
-    /// References to observable objects (typically STIX file objects)
-    pub instance_object_refs: Vec<String>,
-
-    /// Name of the malware instance
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<Name>,
-
-    /// Alternative names/aliases
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub aliases: Vec<Name>,
-
-    /// Labels describing the instance (e.g., "worm", "ransomware")
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub labels: Vec<String>,
-
-    /// Textual description
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-
-    /// Field data (delivery vectors, timestamps)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub field_data: Option<FieldData>,
-
-    /// Operating systems the malware executes on
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub os_execution_envs: Vec<String>,
-
-    /// Processor architectures the malware executes on
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub architecture_execution_envs: Vec<String>,
-
-    /// Capabilities possessed by the malware
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub capabilities: Vec<Capability>,
-
-    /// OS-specific features used
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub os_features: Vec<String>,
-}
-
-impl MalwareInstance {
-    /// Creates a new MalwareInstance builder
-    pub fn builder() -> MalwareInstanceBuilder {
-        MalwareInstanceBuilder::default()
-    }
-
-    /// Creates a minimal MalwareInstance with object refs
-    pub fn new(instance_object_refs: Vec<String>) -> Self {
-        Self {
-            common: crate::common::CommonProperties::new("malware-instance", None),
-            instance_object_refs,
-            name: None,
-            aliases: vec![],
-            labels: vec![],
-            description: None,
-            field_data: None,
-            os_execution_envs: vec![],
-            architecture_execution_envs: vec![],
-            capabilities: vec![],
-            os_features: vec![],
-        }
-    }
-
-    /// Validates the MalwareInstance structure
-    pub fn validate(&self) -> Result<()> {
-        if self.common.r#type != "malware-instance" {
-            return Err(MaecError::ValidationError(format!(
-                "type must be 'malware-instance', got '{}'",
-                self.common.r#type
-            )));
-        }
-
-        if !crate::common::is_valid_maec_id(&self.common.id) {
-            return Err(MaecError::InvalidId(self.common.id.clone()));
-        }
-
-        if self.instance_object_refs.is_empty() {
-            return Err(MaecError::MissingField("instance_object_refs"));
-        }
-
-        Ok(())
-    }
-}
-
-impl MaecObject for MalwareInstance {
-    fn id(&self) -> &str {
-        &self.common.id
-    }
-
-    fn type_(&self) -> &str {
-        &self.common.r#type
-    }
-
-    fn created(&self) -> DateTime<Utc> {
-        self.common.created
-    }
-}
-
-/// Builder for MalwareInstance objects
-#[derive(Debug, Default)]
-pub struct MalwareInstanceBuilder {
-    id: Option<String>,
-    instance_object_refs: Vec<String>,
-    name: Option<Name>,
-    aliases: Vec<Name>,
-    labels: Vec<String>,
-    description: Option<String>,
-    field_data: Option<FieldData>,
-    os_execution_envs: Vec<String>,
-    architecture_execution_envs: Vec<String>,
-    capabilities: Vec<Capability>,
-    os_features: Vec<String>,
-}
-
-impl MalwareInstanceBuilder {
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.id = Some(id.into());
-        self
-    }
-
-    pub fn add_instance_object_ref(mut self, ref_id: impl Into<String>) -> Self {
-        self.instance_object_refs.push(ref_id.into());
-        self
-    }
-
-    pub fn instance_object_refs(mut self, refs: Vec<String>) -> Self {
-        self.instance_object_refs = refs;
-        self
-    }
-
-    pub fn name(mut self, name: impl Into<Name>) -> Self {
-        self.name = Some(name.into());
-        self
-    }
-
-    pub fn description(mut self, desc: impl Into<String>) -> Self {
-        self.description = Some(desc.into());
-        self
-    }
-
-    pub fn add_label(mut self, label: impl Into<String>) -> Self {
-        self.labels.push(label.into());
-        self
-    }
-
-    pub fn field_data(mut self, field_data: FieldData) -> Self {
-        self.field_data = Some(field_data);
-        self
-    }
-
-    pub fn add_capability(mut self, capability: Capability) -> Self {
-        self.capabilities.push(capability);
-        self
-    }
-
-    pub fn build(self) -> Result<MalwareInstance> {
-        if self.instance_object_refs.is_empty() {
-            return Err(MaecError::MissingField("instance_object_refs"));
-        }
-
-        let mut common = crate::common::CommonProperties::new("malware-instance", None);
-        if let Some(id) = self.id {
-            common.id = id;
-        }
-
-        let instance = MalwareInstance {
-            common,
-            instance_object_refs: self.instance_object_refs,
-            name: self.name,
-            aliases: self.aliases,
-            labels: self.labels,
-            description: self.description,
-            field_data: self.field_data,
-            os_execution_envs: self.os_execution_envs,
-            architecture_execution_envs: self.architecture_execution_envs,
-            capabilities: self.capabilities,
-            os_features: self.os_features,
-        };
-
-        instance.validate()?;
-        Ok(instance)
-    }
-}
+//! MAEC Malware Instance object implementation
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::common::MaecObject;
+use crate::error::{MaecError, Result};
+use crate::objects::types::{AnalysisMetadata, FieldData, Name, StaticFeatures, StringFeature};
+use crate::vocab::{ConfidenceMeasure, MalwareLabel};
+use crate::vocab_large::Capability as CapabilityVocab;
+use crate::Capability;
+
+/// Custom property holding field-level provenance
+const PROVENANCE_PROPERTY: &str = "x_maec_provenance";
+
+/// MAEC Malware Instance
+///
+/// A Malware Instance can be thought of as a single member of a Malware Family
+/// that is typically packaged as a binary.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct MalwareInstance {
+    /// Common MAEC properties
+    #[serde(flatten)]
+    pub common: crate::common::CommonProperties,
+
+    /// References to observable objects (typically STIX file objects)
+    #[serde(alias = "instanceObjectRefs")]
+    pub instance_object_refs: Vec<String>,
+
+    /// Name of the malware instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<Name>,
+
+    /// Alternative names/aliases
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<Name>,
+
+    /// Labels describing the instance (e.g., "worm", "ransomware")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Field data (delivery vectors, timestamps)
+    #[serde(skip_serializing_if = "Option::is_none", alias = "fieldData")]
+    pub field_data: Option<FieldData>,
+
+    /// Operating systems the malware executes on
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "osExecutionEnvs"
+    )]
+    pub os_execution_envs: Vec<String>,
+
+    /// Processor architectures the malware executes on
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "architectureExecutionEnvs"
+    )]
+    pub architecture_execution_envs: Vec<String>,
+
+    /// Capabilities possessed by the malware
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<Capability>,
+
+    /// OS-specific features used
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "osFeatures")]
+    pub os_features: Vec<String>,
+
+    /// Metadata about the analyses performed on the instance
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        alias = "analysisMetadata"
+    )]
+    pub analysis_metadata: Vec<AnalysisMetadata>,
+
+    /// Features obtained through static analysis
+    #[serde(skip_serializing_if = "Option::is_none", alias = "staticFeatures")]
+    pub static_features: Option<StaticFeatures>,
+}
+
+impl MalwareInstance {
+    /// Creates a new MalwareInstance builder
+    pub fn builder() -> MalwareInstanceBuilder {
+        MalwareInstanceBuilder::default()
+    }
+
+    /// Creates a minimal MalwareInstance with object refs
+    pub fn new(instance_object_refs: Vec<String>) -> Self {
+        Self {
+            common: crate::common::CommonProperties::new("malware-instance", None),
+            instance_object_refs,
+            name: None,
+            aliases: vec![],
+            labels: vec![],
+            description: None,
+            field_data: None,
+            os_execution_envs: vec![],
+            architecture_execution_envs: vec![],
+            capabilities: vec![],
+            os_features: vec![],
+            analysis_metadata: vec![],
+            static_features: None,
+        }
+    }
+
+    /// Validates the MalwareInstance structure
+    pub fn validate(&self) -> Result<()> {
+        if self.common.r#type != "malware-instance" {
+            return Err(MaecError::ValidationError(format!(
+                "type must be 'malware-instance', got '{}'",
+                self.common.r#type
+            )));
+        }
+
+        if !crate::common::is_valid_maec_id(&self.common.id) {
+            return Err(MaecError::InvalidId(self.common.id.clone()));
+        }
+        self.common.validate_timestamps()?;
+
+        if self.instance_object_refs.is_empty() {
+            return Err(MaecError::MissingField("instance_object_refs"));
+        }
+        for name in self.name.iter().chain(&self.aliases) {
+            name.validate()?;
+        }
+        for capability in &self.capabilities {
+            capability.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies a mutation to the malware instance and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
+
+    /// Creates a `variant-of` relationship from this instance to `other`
+    ///
+    /// Returns an error if either object is not a malware instance or if both
+    /// are the same instance.
+    pub fn variant_of(&self, other: &MalwareInstance) -> Result<crate::Relationship> {
+        self.relationship_to("variant-of", other)
+    }
+
+    /// Creates a `derived-from` relationship from this instance to `other`
+    ///
+    /// Returns an error if either object is not a malware instance or if both
+    /// are the same instance.
+    pub fn derived_from(&self, other: &MalwareInstance) -> Result<crate::Relationship> {
+        self.relationship_to("derived-from", other)
+    }
+
+    fn relationship_to(
+        &self,
+        relationship_type: &str,
+        other: &MalwareInstance,
+    ) -> Result<crate::Relationship> {
+        for instance in [self, other] {
+            if !crate::common::is_valid_ref_for_type(&instance.common.id, "malware-instance") {
+                return Err(MaecError::InvalidReference(format!(
+                    "'{}' relationships must link malware instances, got '{}'",
+                    relationship_type, instance.common.id
+                )));
+            }
+        }
+
+        crate::Relationship::builder()
+            .source_ref(&self.common.id)
+            .relationship_type(relationship_type)
+            .target_ref(&other.common.id)
+            .build()
+    }
+
+    /// Returns the name, if any, followed by all aliases
+    pub fn all_names(&self) -> Vec<&Name> {
+        self.name.iter().chain(&self.aliases).collect()
+    }
+
+    /// Returns whether the name or any alias matches `name`
+    ///
+    /// See [`Name::matches`] for the meaning of `normalized`.
+    pub fn matches_name(&self, name: &str, normalized: bool) -> bool {
+        self.all_names()
+            .into_iter()
+            .any(|candidate| candidate.matches(name, normalized))
+    }
+
+    /// Returns all capabilities, including refinements at any depth
+    ///
+    /// Capabilities are listed depth-first, each followed by its refinements.
+    /// The tree is walked with an explicit work-list, so deeply nested input
+    /// cannot overflow the stack.
+    pub fn all_capabilities(&self) -> Vec<&Capability> {
+        let mut all = vec![];
+        let mut pending: Vec<&Capability> = self.capabilities.iter().rev().collect();
+        while let Some(capability) = pending.pop() {
+            all.push(capability);
+            pending.extend(capability.refined_capabilities.iter().rev());
+        }
+        all
+    }
+
+    /// Returns the set of capability names, including all refined capabilities
+    pub fn capability_names(&self) -> HashSet<&str> {
+        let mut names = HashSet::new();
+        for capability in &self.capabilities {
+            capability.collect_names(&mut names);
+        }
+        names
+    }
+
+    /// Computes the Jaccard similarity of two instances' capability names
+    ///
+    /// Names are collected from top-level and refined capabilities alike.
+    /// Returns a value between `0.0` (nothing shared) and `1.0` (identical
+    /// sets); two instances without any capabilities score `0.0`.
+    pub fn capability_similarity(&self, other: &MalwareInstance) -> f64 {
+        let ours = self.capability_names();
+        let theirs = other.capability_names();
+
+        let union = ours.union(&theirs).count();
+        if union == 0 {
+            return 0.0;
+        }
+        ours.intersection(&theirs).count() as f64 / union as f64
+    }
+
+    /// Returns the confidence stored under `x_maec_confidence`, if any
+    pub fn confidence(&self) -> Option<ConfidenceMeasure> {
+        self.common.confidence()
+    }
+
+    /// Records which source contributed the value at a field path
+    ///
+    /// Provenance is stored in `custom_properties` under `x_maec_provenance`
+    /// as a map from field path (e.g. `"labels"`) to source identifier.
+    pub fn set_field_source(&mut self, path: impl Into<String>, source: impl Into<String>) {
+        let provenance = self
+            .common
+            .custom_properties
+            .entry(PROVENANCE_PROPERTY.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+
+        if !provenance.is_object() {
+            *provenance = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(map) = provenance.as_object_mut() {
+            map.insert(path.into(), serde_json::Value::String(source.into()));
+        }
+    }
+
+    /// Returns the source recorded for a field path, if any
+    pub fn field_source(&self, path: &str) -> Option<&str> {
+        self.common
+            .custom_properties
+            .get(PROVENANCE_PROPERTY)?
+            .get(path)?
+            .as_str()
+    }
+
+    /// Returns all recorded field provenance as a map from field path to source
+    pub fn provenance(&self) -> HashMap<String, String> {
+        self.common
+            .custom_properties
+            .get(PROVENANCE_PROPERTY)
+            .and_then(|value| value.as_object())
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(path, source)| {
+                        source.as_str().map(|s| (path.clone(), s.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Attributes every populated field without a recorded source to `source`
+    pub(crate) fn record_provenance(&mut self, source: &str) {
+        for field in self.populated_fields() {
+            if self.field_source(field).is_none() {
+                self.set_field_source(field, source);
+            }
+        }
+    }
+
+    /// Returns the names of the top-level fields that currently hold a value
+    fn populated_fields(&self) -> Vec<&'static str> {
+        let mut fields = vec![];
+        if !self.instance_object_refs.is_empty() {
+            fields.push("instance_object_refs");
+        }
+        if self.name.is_some() {
+            fields.push("name");
+        }
+        if !self.aliases.is_empty() {
+            fields.push("aliases");
+        }
+        if !self.labels.is_empty() {
+            fields.push("labels");
+        }
+        if self.description.is_some() {
+            fields.push("description");
+        }
+        if self.field_data.is_some() {
+            fields.push("field_data");
+        }
+        if !self.os_execution_envs.is_empty() {
+            fields.push("os_execution_envs");
+        }
+        if !self.architecture_execution_envs.is_empty() {
+            fields.push("architecture_execution_envs");
+        }
+        if !self.capabilities.is_empty() {
+            fields.push("capabilities");
+        }
+        if !self.os_features.is_empty() {
+            fields.push("os_features");
+        }
+        if !self.analysis_metadata.is_empty() {
+            fields.push("analysis_metadata");
+        }
+        fields
+    }
+}
+
+crate::common::impl_json_methods!(MalwareInstance);
+
+impl MaecObject for MalwareInstance {
+    fn id(&self) -> &str {
+        &self.common.id
+    }
+
+    fn type_(&self) -> &str {
+        &self.common.r#type
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.common.created
+    }
+}
+
+/// Builder for MalwareInstance objects
+#[derive(Debug, Clone, Default)]
+pub struct MalwareInstanceBuilder {
+    id: Option<String>,
+    instance_object_refs: Vec<String>,
+    name: Option<Name>,
+    aliases: Vec<Name>,
+    labels: Vec<String>,
+    description: Option<String>,
+    field_data: Option<FieldData>,
+    os_execution_envs: Vec<String>,
+    architecture_execution_envs: Vec<String>,
+    capabilities: Vec<Capability>,
+    os_features: Vec<String>,
+    analysis_metadata: Vec<AnalysisMetadata>,
+    strings: Vec<StringFeature>,
+    confidence: Option<ConfidenceMeasure>,
+}
+
+impl MalwareInstanceBuilder {
+    /// Starts a builder with the given labels and capabilities
+    fn preset(labels: &[MalwareLabel], capabilities: &[CapabilityVocab]) -> Self {
+        Self {
+            labels: labels.iter().map(|l| l.as_ref().to_string()).collect(),
+            capabilities: capabilities
+                .iter()
+                .map(|c| Capability::new(c.as_ref()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Starts a builder for a typical ransomware instance
+    ///
+    /// Pre-populates the `ransomware` label and the `integrity-violation`
+    /// (file encryption) and `command-and-control` capabilities. Like every
+    /// preset, the result still needs its instance object references.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::MalwareInstanceBuilder;
+    ///
+    /// let instance = MalwareInstanceBuilder::ransomware_preset()
+    ///     .add_instance_object_ref("file--6ce09d9c-0ad3-5ebf-900c-e3cb288955b5")
+    ///     .name("WannaCry")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(instance.labels, vec!["ransomware"]);
+    /// assert_eq!(instance.capabilities.len(), 2);
+    /// ```
+    pub fn ransomware_preset() -> Self {
+        Self::preset(
+            &[MalwareLabel::Ransomware],
+            &[
+                CapabilityVocab::IntegrityViolation,
+                CapabilityVocab::CommandAndControl,
+            ],
+        )
+    }
+
+    /// Starts a builder for a typical banking trojan
+    ///
+    /// Pre-populates the `trojan-horse` and `password-stealer` labels and the
+    /// `data-theft`, `command-and-control` and `persistence` capabilities.
+    pub fn banking_trojan_preset() -> Self {
+        Self::preset(
+            &[MalwareLabel::TrojanHorse, MalwareLabel::PasswordStealer],
+            &[
+                CapabilityVocab::DataTheft,
+                CapabilityVocab::CommandAndControl,
+                CapabilityVocab::Persistence,
+            ],
+        )
+    }
+
+    /// Starts a builder for a typical remote access trojan
+    ///
+    /// Pre-populates the `trojan-horse` and `backdoor` labels and the
+    /// `machine-access-control`, `command-and-control` and `persistence`
+    /// capabilities.
+    pub fn rat_preset() -> Self {
+        Self::preset(
+            &[MalwareLabel::TrojanHorse, MalwareLabel::Backdoor],
+            &[
+                CapabilityVocab::MachineAccessControl,
+                CapabilityVocab::CommandAndControl,
+                CapabilityVocab::Persistence,
+            ],
+        )
+    }
+
+    /// Starts a builder for a typical wiper
+    ///
+    /// Pre-populates the `wiper` label and the `destruction` and
+    /// `availability-violation` capabilities.
+    pub fn wiper_preset() -> Self {
+        Self::preset(
+            &[MalwareLabel::Wiper],
+            &[
+                CapabilityVocab::Destruction,
+                CapabilityVocab::AvailabilityViolation,
+            ],
+        )
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5(
+            "malware-instance",
+            namespace,
+            name,
+        ))
+    }
+
+    pub fn add_instance_object_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.instance_object_refs.push(ref_id.into());
+        self
+    }
+
+    pub fn instance_object_refs(mut self, refs: Vec<String>) -> Self {
+        self.instance_object_refs = refs;
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<Name>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    pub fn add_label(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+
+    pub fn field_data(mut self, field_data: FieldData) -> Self {
+        self.field_data = Some(field_data);
+        self
+    }
+
+    pub fn add_capability(mut self, capability: impl Into<Capability>) -> Self {
+        self.capabilities.push(capability.into());
+        self
+    }
+
+    pub fn add_analysis_metadata(mut self, metadata: AnalysisMetadata) -> Self {
+        self.analysis_metadata.push(metadata);
+        self
+    }
+
+    /// Adds an extracted string to the static features
+    pub fn add_string(mut self, string: impl Into<StringFeature>) -> Self {
+        self.strings.push(string.into());
+        self
+    }
+
+    /// Sets the confidence, stored under `x_maec_confidence`
+    pub fn confidence(mut self, confidence: ConfidenceMeasure) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Builds a preview of the MalwareInstance without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<MalwareInstance> {
+        self.clone().build()
+    }
+
+    pub fn build(self) -> Result<MalwareInstance> {
+        if self.instance_object_refs.is_empty() {
+            return Err(MaecError::MissingField("instance_object_refs"));
+        }
+
+        let mut common = crate::common::CommonProperties::new("malware-instance", None);
+        if let Some(id) = self.id {
+            common.id = id;
+        }
+        if let Some(confidence) = self.confidence {
+            common.set_confidence(confidence);
+        }
+
+        let instance = MalwareInstance {
+            common,
+            instance_object_refs: self.instance_object_refs,
+            name: self.name,
+            aliases: self.aliases,
+            labels: self.labels,
+            description: self.description,
+            field_data: self.field_data,
+            os_execution_envs: self.os_execution_envs,
+            architecture_execution_envs: self.architecture_execution_envs,
+            capabilities: self.capabilities,
+            os_features: self.os_features,
+            analysis_metadata: self.analysis_metadata,
+            static_features: (!self.strings.is_empty()).then_some(StaticFeatures {
+                strings: self.strings,
+            }),
+        };
+
+        instance.validate()?;
+        Ok(instance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_capabilities() {
+        let mut deep = Capability::new("level-0");
+        for level in 1..1000 {
+            deep = Capability::tree(format!("level-{}", level), vec![deep]);
+        }
+        let instance = MalwareInstance::builder()
+            .add_instance_object_ref("file--1")
+            .add_capability(Capability::tree(
+                "persistence",
+                vec![Capability::new("continuous-execution")],
+            ))
+            .add_capability(deep)
+            .build()
+            .unwrap();
+
+        let all = instance.all_capabilities();
+        assert_eq!(all.len(), 1002);
+        let names: Vec<&str> = all.iter().take(3).map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["persistence", "continuous-execution", "level-999"]
+        );
+        assert_eq!(all[1001].name, "level-0");
+    }
+
+    #[test]
+    fn test_validation_rejects_blank_capability_names() {
+        let mut instance = MalwareInstance::builder()
+            .add_instance_object_ref("file--1")
+            .add_capability(Capability::new("persistence"))
+            .build()
+            .unwrap();
+        assert!(instance.validate().is_ok());
+
+        instance.capabilities.push(Capability::from_path(""));
+        assert!(instance.validate().is_err());
+
+        instance.capabilities[1] = Capability::tree("persistence", vec![Capability::new("")]);
+        assert!(instance.validate().is_err());
+    }
+
+    #[test]
+    fn test_deterministic_id() {
+        let build = |hash: &str| {
+            MalwareInstance::builder()
+                .add_instance_object_ref("file--1")
+                .deterministic_id(&crate::MAEC_NAMESPACE, hash)
+                .build()
+                .unwrap()
+        };
+        let first = build("sha256:3f79bb7b");
+        assert_eq!(first.common.id, build("sha256:3f79bb7b").common.id);
+        assert_ne!(first.common.id, build("sha256:0000").common.id);
+        assert!(first.common.id.starts_with("malware-instance--"));
+    }
+
+    #[test]
+    fn test_variant_and_derived_relationships() {
+        let original = MalwareInstance::new(vec!["0".to_string()]);
+        let variant = MalwareInstance::new(vec!["1".to_string()]);
+
+        let relationship = variant.variant_of(&original).unwrap();
+        assert_eq!(relationship.source_ref, variant.common.id);
+        assert_eq!(relationship.target_ref, original.common.id);
+        assert_eq!(relationship.relationship_type, "variant-of");
+
+        let relationship = variant.derived_from(&original).unwrap();
+        assert_eq!(relationship.relationship_type, "derived-from");
+
+        assert!(variant.variant_of(&variant).is_err());
+
+        let mut mislabeled = original.clone();
+        mislabeled.common.id = "malware-family--550e8400-e29b-41d4-a716-446655440000".to_string();
+        assert!(matches!(
+            variant.derived_from(&mislabeled),
+            Err(MaecError::InvalidReference(_))
+        ));
+    }
+
+    #[test]
+    fn test_static_strings() {
+        let instance = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_string("WanaDecryptor")
+            .add_string(
+                StringFeature::new("Ooops, your files have been encrypted!")
+                    .with_encoding("utf-16le")
+                    .with_language("en"),
+            )
+            .build()
+            .unwrap();
+
+        let strings = &instance.static_features.as_ref().unwrap().strings;
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings[1].encoding.as_deref(), Some("utf-16le"));
+
+        let json = serde_json::to_value(&instance).unwrap();
+        assert_eq!(
+            json["static_features"]["strings"][0]["value"],
+            "WanaDecryptor"
+        );
+        assert!(json["static_features"]["strings"][0]
+            .get("encoding")
+            .is_none());
+        let parsed: MalwareInstance = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, instance);
+
+        let bare = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .build()
+            .unwrap();
+        assert!(bare.static_features.is_none());
+    }
+
+    #[test]
+    fn test_hash_dedup() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let instance = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .name("WannaCry")
+            .build()
+            .unwrap();
+        let mut reissued = instance.clone();
+        reissued.common.id = crate::generate_maec_id("malware-instance");
+        reissued.common.new_version();
+
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(&instance), hasher.hash_one(&reissued));
+        let mut renamed = instance.clone();
+        renamed.name = Some(Name::new("WannaCry 2.0"));
+        assert_ne!(hasher.hash_one(&instance), hasher.hash_one(&renamed));
+
+        let exact: HashSet<&MalwareInstance> = [&instance, &instance, &reissued].into();
+        assert_eq!(exact.len(), 2);
+
+        let unique: HashSet<crate::ContentKey<MalwareInstance>> =
+            [&instance, &instance, &reissued, &renamed]
+                .into_iter()
+                .map(crate::ContentKey::new)
+                .collect();
+        assert_eq!(unique.len(), 2);
+        assert!(unique.contains(&crate::ContentKey::new(&reissued)));
+    }
+
+    #[test]
+    fn test_presets() {
+        let presets = [
+            MalwareInstanceBuilder::ransomware_preset(),
+            MalwareInstanceBuilder::banking_trojan_preset(),
+            MalwareInstanceBuilder::rat_preset(),
+            MalwareInstanceBuilder::wiper_preset(),
+        ];
+        for preset in presets {
+            assert!(preset.peek().is_err());
+            let instance = preset
+                .add_instance_object_ref("0")
+                .add_label("custom")
+                .build()
+                .unwrap();
+            assert_eq!(instance.labels.last().map(String::as_str), Some("custom"));
+            assert!(instance.capabilities.iter().all(|c| {
+                serde_json::from_value::<CapabilityVocab>(serde_json::json!(c.name)).is_ok()
+            }));
+        }
+
+        let rat = MalwareInstanceBuilder::rat_preset()
+            .add_instance_object_ref("0")
+            .build()
+            .unwrap();
+        assert!(rat.capability_names().contains("machine-access-control"));
+    }
+
+    #[test]
+    fn test_capability_similarity() {
+        let mut persistence = Capability::new("persistence");
+        persistence
+            .refined_capabilities
+            .push(Capability::new("persist-after-system-reboot"));
+
+        let first = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_capability(persistence)
+            .add_capability(Capability::new("command-and-control"))
+            .build()
+            .unwrap();
+        let second = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_capability(Capability::new("persistence"))
+            .add_capability(Capability::new("persist-after-system-reboot"))
+            .add_capability(Capability::new("anti-detection"))
+            .build()
+            .unwrap();
+
+        assert_eq!(first.capability_similarity(&second), 0.5);
+        assert_eq!(first.capability_similarity(&first), 1.0);
+
+        let empty = MalwareInstance::new(vec!["0".to_string()]);
+        assert_eq!(empty.capability_similarity(&empty), 0.0);
+    }
+
+    #[test]
+    fn test_all_names() {
+        let mut instance = MalwareInstance::new(vec!["0".to_string()]);
+        instance.aliases.push(Name::new("WCry"));
+        assert_eq!(instance.all_names(), vec![&Name::new("WCry")]);
+
+        instance.name = Some(Name::new("WannaCry"));
+        assert_eq!(instance.all_names().len(), 2);
+        assert!(instance.matches_name("WCRY", true));
+        assert!(!instance.matches_name("WCRY", false));
+    }
+}
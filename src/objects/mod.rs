@@ -19,6 +19,11 @@ pub use collection::Collection;
 pub use malware_action::MalwareAction;
 pub use malware_family::{MalwareFamily, MalwareFamilyBuilder};
 pub use malware_instance::{MalwareInstance, MalwareInstanceBuilder};
-pub use package::{MaecObjectType, Package, PackageBuilder};
+pub use package::{
+    object_from_value, BuildReport, MaecObjectType, ObjectKind, Package, PackageBuilder,
+    TimelineEvent, TimelineEventKind, ValidationProfile,
+};
 pub use relationship::{Relationship, RelationshipBuilder};
-pub use types::{FieldData, FieldDataBuilder, Name};
+pub use types::{
+    AnalysisMetadata, FieldData, FieldDataBuilder, Name, StaticFeatures, StringFeature,
+};
@@ -1,12 +1,44 @@
 //! MAEC Package object implementation
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 use crate::common::{CommonProperties, MaecObject};
 use crate::error::{MaecError, Result};
 use chrono::{DateTime, Utc};
 
+/// Custom property holding the package title
+const TITLE_PROPERTY: &str = "x_maec_title";
+
+/// Custom property holding the package description
+const DESCRIPTION_PROPERTY: &str = "x_maec_description";
+
+/// Custom property holding the original document the package was built from
+const SOURCE_DOCUMENT_PROPERTY: &str = "x_maec_source_document";
+
+/// STIX 2.1 Cyber Observable Object types accepted in `observable_objects`
+const STIX_OBSERVABLE_TYPES: &[&str] = &[
+    "artifact",
+    "autonomous-system",
+    "directory",
+    "domain-name",
+    "email-addr",
+    "email-message",
+    "file",
+    "ipv4-addr",
+    "ipv6-addr",
+    "mac-addr",
+    "mutex",
+    "network-traffic",
+    "process",
+    "software",
+    "url",
+    "user-account",
+    "windows-registry-key",
+    "x509-certificate",
+];
+
 /// Top-level MAEC Package
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -16,20 +48,55 @@ pub struct Package {
     pub common: CommonProperties,
 
     /// MAEC objects contained in this package
-    #[serde(default)]
+    #[serde(default, alias = "maecObjects")]
     pub maec_objects: Vec<MaecObjectType>,
 
     /// STIX Cyber Observable Objects relevant to the package
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub observable_objects: Option<HashMap<String, serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none", alias = "observableObjects")]
+    pub observable_objects: Option<BTreeMap<String, serde_json::Value>>,
 
     /// Relationships between objects in the package
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub relationships: Vec<crate::Relationship>,
 }
 
+/// How thoroughly a Package is validated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValidationProfile {
+    /// Structural checks of the package itself ([`Package::validate`])
+    #[default]
+    Basic,
+    /// Checks of the package and everything it contains ([`Package::validate_deep`])
+    Deep,
+}
+
+/// Kind of event in a reconstructed execution timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimelineEventKind {
+    /// Event derived from a Behavior
+    Behavior,
+    /// Event derived from a MalwareAction
+    Action,
+}
+
+/// A single timestamped event in a Package timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEvent {
+    /// When the event occurred
+    pub timestamp: DateTime<Utc>,
+    /// Whether the event came from a behavior or an action
+    pub kind: TimelineEventKind,
+    /// ID of the object the event was derived from
+    pub object_id: String,
+    /// Vocabulary name of the behavior or action
+    pub label: String,
+}
+
 /// MAEC object types that can be contained in a Package
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// Deserialization dispatches on the object's `type` property, which every
+/// MAEC object must carry.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 #[serde(untagged)]
 pub enum MaecObjectType {
     /// Behavior object
@@ -44,6 +111,74 @@ pub enum MaecObjectType {
     MalwareInstance(crate::MalwareInstance),
 }
 
+/// The kind of a top-level MAEC object, without its data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectKind {
+    /// Behavior object
+    Behavior,
+    /// Collection object
+    Collection,
+    /// Malware Action object
+    MalwareAction,
+    /// Malware Family object
+    MalwareFamily,
+    /// Malware Instance object
+    MalwareInstance,
+}
+
+macro_rules! impl_from_object {
+    ($($variant:ident),* $(,)?) => {
+        $(
+            impl From<crate::$variant> for MaecObjectType {
+                fn from(object: crate::$variant) -> Self {
+                    MaecObjectType::$variant(object)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_object!(
+    Behavior,
+    Collection,
+    MalwareAction,
+    MalwareFamily,
+    MalwareInstance
+);
+
+impl<'de> Deserialize<'de> for MaecObjectType {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        use serde_json::from_value;
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let object_type = value
+            .get("type")
+            .ok_or_else(|| D::Error::custom("MAEC object is missing its 'type'"))?
+            .as_str()
+            .ok_or_else(|| D::Error::custom("MAEC object 'type' must be a string"))?
+            .to_string();
+
+        match object_type.as_str() {
+            "behavior" => from_value(value).map(MaecObjectType::Behavior),
+            "collection" => from_value(value).map(MaecObjectType::Collection),
+            "malware-action" => from_value(value).map(MaecObjectType::MalwareAction),
+            "malware-family" => from_value(value).map(MaecObjectType::MalwareFamily),
+            "malware-instance" => from_value(value).map(MaecObjectType::MalwareInstance),
+            other => {
+                return Err(D::Error::custom(format!(
+                    "unknown MAEC object type '{}'",
+                    other
+                )))
+            }
+        }
+        .map_err(D::Error::custom)
+    }
+}
+
 impl Package {
     /// Creates a new Package builder
     pub fn builder() -> PackageBuilder {
@@ -60,6 +195,55 @@ impl Package {
         }
     }
 
+    /// Returns the human-readable title stored under `x_maec_title`, if any
+    pub fn title(&self) -> Option<&str> {
+        self.common
+            .custom_properties
+            .get(TITLE_PROPERTY)
+            .and_then(|value| value.as_str())
+    }
+
+    /// Returns the description stored under `x_maec_description`, if any
+    pub fn description(&self) -> Option<&str> {
+        self.common
+            .custom_properties
+            .get(DESCRIPTION_PROPERTY)
+            .and_then(|value| value.as_str())
+    }
+
+    /// Attaches the raw document the package was derived from
+    ///
+    /// The bytes are stored base64-encoded together with their media type
+    /// under `x_maec_source_document`, replacing any earlier document.
+    pub fn set_source_document(&mut self, bytes: &[u8], media_type: &str) {
+        use base64::Engine;
+
+        self.common.custom_properties.insert(
+            SOURCE_DOCUMENT_PROPERTY.to_string(),
+            serde_json::json!({
+                "media_type": media_type,
+                "data": base64::engine::general_purpose::STANDARD.encode(bytes),
+            }),
+        );
+    }
+
+    /// Returns the attached source document and its media type, if any
+    ///
+    /// Yields `None` as well when `x_maec_source_document` is malformed.
+    pub fn source_document(&self) -> Option<(Vec<u8>, String)> {
+        use base64::Engine;
+
+        let document = self
+            .common
+            .custom_properties
+            .get(SOURCE_DOCUMENT_PROPERTY)?;
+        let media_type = document.get("media_type")?.as_str()?;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(document.get("data")?.as_str()?)
+            .ok()?;
+        Some((data, media_type.to_string()))
+    }
+
     /// Validates the Package structure
     pub fn validate(&self) -> Result<()> {
         if self.common.r#type != "package" {
@@ -79,10 +263,709 @@ impl Package {
         if !crate::common::is_valid_maec_id(&self.common.id) {
             return Err(MaecError::InvalidId(self.common.id.clone()));
         }
+        self.common.validate_timestamps()?;
 
         Ok(())
     }
 
+    /// Appends an object to `maec_objects`
+    ///
+    /// Returns `&mut Self` so that edits can be chained; nothing is validated
+    /// until [`validate`](Self::validate) is called.
+    pub fn push_object(&mut self, object: MaecObjectType) -> &mut Self {
+        self.maec_objects.push(object);
+        self
+    }
+
+    /// Appends a relationship to `relationships`
+    ///
+    /// Returns `&mut Self` so that edits can be chained.
+    pub fn push_relationship(&mut self, relationship: crate::Relationship) -> &mut Self {
+        self.relationships.push(relationship);
+        self
+    }
+
+    /// Returns the package with an object appended
+    ///
+    /// Owned counterpart of [`push_object`](Self::push_object).
+    pub fn with_object(mut self, object: impl Into<MaecObjectType>) -> Self {
+        self.push_object(object.into());
+        self
+    }
+
+    /// Returns the package with a relationship appended
+    ///
+    /// Owned counterpart of [`push_relationship`](Self::push_relationship).
+    pub fn with_relationship(mut self, relationship: crate::Relationship) -> Self {
+        self.push_relationship(relationship);
+        self
+    }
+
+    /// Applies a mutation to the package and records it as a new version
+    ///
+    /// The closure receives mutable access to the object; once it returns, the
+    /// `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
+
+    /// Parses and validates a package from a reader, reporting error positions
+    ///
+    /// If the input is not a well-formed package, the error is returned
+    /// together with the 1-based `(line, column)` at which parsing failed.
+    /// Errors without a position in the input (I/O failures and validation
+    /// errors) carry `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Package;
+    ///
+    /// let input = "{\n  \"type\": \"package\"\n  \"id\": \"package--1\"\n}";
+    /// let (_, position) = Package::from_reader_with_position(input.as_bytes()).unwrap_err();
+    /// assert_eq!(position, Some((3, 3)));
+    /// ```
+    pub fn from_reader_with_position<R: std::io::Read>(
+        reader: R,
+    ) -> std::result::Result<Package, (MaecError, Option<(usize, usize)>)> {
+        let package: Package = serde_json::from_reader(reader).map_err(|e| {
+            let position = (e.line() > 0).then(|| (e.line(), e.column()));
+            (MaecError::from(e), position)
+        })?;
+        package.validate().map_err(|e| (e, None))?;
+        Ok(package)
+    }
+
+    /// Returns the lowest schema version used in the package
+    ///
+    /// The versions of the package itself, its objects and its relationships
+    /// are compared as parsed by [`CommonProperties::schema_semver`]; absent
+    /// or unparseable versions are ignored.
+    pub fn min_schema_version(&self) -> Option<(u64, u64, u64)> {
+        std::iter::once(&self.common)
+            .chain(self.maec_objects.iter().map(MaecObjectType::common))
+            .chain(self.relationships.iter().map(|rel| &rel.common))
+            .filter_map(CommonProperties::schema_semver)
+            .min()
+    }
+
+    /// Returns a copy of the package for consumers of an older schema version
+    ///
+    /// Every `schema_version` in the package is set to `version`. Fields
+    /// introduced after the target version are removed, and each removal is
+    /// reported in the returned warning list. The object model only covers
+    /// MAEC 5.0, the only supported [`SchemaVersion`](crate::SchemaVersion),
+    /// so the list is empty for now.
+    ///
+    /// Returns `MaecError::ValidationError` for versions other than those in
+    /// [`SchemaVersion::ALL`](crate::SchemaVersion::ALL).
+    pub fn downgrade_to(&self, version: &str) -> Result<(Package, Vec<String>)> {
+        let target = crate::SchemaVersion::from_name(version).ok_or_else(|| {
+            MaecError::ValidationError(format!("unknown MAEC schema version '{}'", version))
+        })?;
+        let warnings = vec![];
+
+        let mut package = self.clone();
+        let set_version = |common: &mut CommonProperties| {
+            if common.schema_version.is_some() {
+                common.schema_version = Some(target.as_ref().to_string());
+            }
+        };
+        set_version(&mut package.common);
+        for relationship in &mut package.relationships {
+            set_version(&mut relationship.common);
+        }
+        for object in &mut package.maec_objects {
+            set_version(object.common_mut());
+        }
+        Ok((package, warnings))
+    }
+
+    /// Validates the package according to a [`ValidationProfile`]
+    ///
+    /// Warnings produced by the deep profile are discarded.
+    pub fn validate_with(&self, profile: ValidationProfile) -> Result<()> {
+        match profile {
+            ValidationProfile::Basic => self.validate(),
+            ValidationProfile::Deep => self.validate_deep().map(|_| ()),
+        }
+    }
+
+    /// Serializes the package in the given format
+    ///
+    /// Returns `MaecError::UnsupportedFormat` if the format is not available
+    /// in this build.
+    pub fn serialize_as(&self, format: crate::SerializationFormat) -> Result<String> {
+        format.serialize(self)
+    }
+
+    /// Serializes the package to pretty-printed JSON indented with `indent`
+    ///
+    /// Use e.g. `"    "` for four spaces or `"\t"` for tabs;
+    /// [`to_json_pretty`](Self::to_json_pretty) indents with two spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Package;
+    ///
+    /// let json = Package::new().to_json_indented("\t").unwrap();
+    /// assert!(json.contains("\n\t\"type\": \"package\""));
+    /// ```
+    pub fn to_json_indented(&self, indent: &str) -> Result<String> {
+        let mut output = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+        self.serialize(&mut serializer)?;
+        // serde_json writes UTF-8 and the indent comes from a &str
+        Ok(String::from_utf8(output).expect("JSON output is valid UTF-8"))
+    }
+
+    /// Serializes the package as a flat JSON array of objects
+    ///
+    /// The array holds the `maec_objects` followed by the `relationships`;
+    /// the package envelope (ID, schema version, observables) is dropped.
+    /// [`from_objects_json`](Self::from_objects_json) reads it back.
+    pub fn objects_to_json(&self) -> Result<String> {
+        let mut items = Vec::with_capacity(self.maec_objects.len() + self.relationships.len());
+        for object in &self.maec_objects {
+            items.push(serde_json::to_value(object)?);
+        }
+        for relationship in &self.relationships {
+            items.push(serde_json::to_value(relationship)?);
+        }
+        Ok(serde_json::to_string(&items)?)
+    }
+
+    /// Wraps a flat JSON array of objects into a new package
+    ///
+    /// Items of type `relationship` become relationships, everything else a
+    /// MAEC object; each item is validated. The package itself gets a fresh
+    /// ID and no observables.
+    pub fn from_objects_json(json: &str) -> Result<Package> {
+        let items: Vec<serde_json::Value> = serde_json::from_str(json)?;
+        let mut package = Package::new();
+        for item in items {
+            if item.get("type").and_then(|t| t.as_str()) == Some("relationship") {
+                let relationship = crate::Relationship::deserialize(item)?;
+                relationship.validate()?;
+                package.relationships.push(relationship);
+            } else {
+                let object = MaecObjectType::deserialize(item)?;
+                object.validate()?;
+                package.maec_objects.push(object);
+            }
+        }
+        Ok(package)
+    }
+
+    /// Serializes the package to XML
+    ///
+    /// Returns `MaecError::UnsupportedFormat` unless the `xml` feature is
+    /// enabled.
+    pub fn to_xml(&self) -> Result<String> {
+        self.serialize_as(crate::SerializationFormat::Xml)
+    }
+
+    /// Deserializes and validates a package from XML produced by [`Package::to_xml`]
+    ///
+    /// Returns `MaecError::UnsupportedFormat` unless the `xml` feature is
+    /// enabled.
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let package: Self = crate::SerializationFormat::Xml.deserialize(xml)?;
+        package.validate()?;
+        Ok(package)
+    }
+
+    /// Serializes the package to YAML
+    ///
+    /// Returns `MaecError::UnsupportedFormat` unless the `yaml` feature is
+    /// enabled.
+    pub fn to_yaml(&self) -> Result<String> {
+        self.serialize_as(crate::SerializationFormat::Yaml)
+    }
+
+    /// Deserializes and validates a package from YAML
+    ///
+    /// Returns `MaecError::UnsupportedFormat` unless the `yaml` feature is
+    /// enabled.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let package: Self = crate::SerializationFormat::Yaml.deserialize(yaml)?;
+        package.validate()?;
+        Ok(package)
+    }
+
+    /// Performs a thorough validation of the package and everything it contains
+    ///
+    /// In addition to [`Package::validate`], this validates every contained
+    /// object and relationship, checks that attribute and custom property
+    /// values stay within [`DEFAULT_MAX_VALUE_DEPTH`](crate::common::DEFAULT_MAX_VALUE_DEPTH)
+    /// and [`DEFAULT_MAX_VALUE_SIZE`](crate::common::DEFAULT_MAX_VALUE_SIZE),
+    /// checks that no collection transitively contains itself, and checks
+    /// that observable keys and MAEC object IDs form disjoint namespaces.
+    /// Observable objects that are not referenced by any object are reported
+    /// as warnings in the returned list rather than as errors.
+    ///
+    /// Self-referential relationships are rejected; use
+    /// [`Package::validate_deep_allowing_self_loops`] to permit them for
+    /// specific relationship types.
+    pub fn validate_deep(&self) -> Result<Vec<String>> {
+        self.validate_deep_allowing_self_loops(&[])
+    }
+
+    /// Performs [`Package::validate_deep`], permitting self-referential
+    /// relationships of the given types
+    ///
+    /// Each relationship is checked with
+    /// [`validate_allowing_self_loops`](crate::Relationship::validate_allowing_self_loops).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                package = %self.common.id,
+                objects = self.maec_objects.len(),
+                relationships = self.relationships.len(),
+            )
+        )
+    )]
+    pub fn validate_deep_allowing_self_loops(&self, allowed_types: &[&str]) -> Result<Vec<String>> {
+        self.validate()?;
+
+        for object in &self.maec_objects {
+            object.validate()?;
+        }
+        for relationship in &self.relationships {
+            relationship.validate_allowing_self_loops(allowed_types)?;
+        }
+
+        self.validate_value_limits()?;
+
+        if let Some(cycle) = self.collection_cycle() {
+            return Err(MaecError::ValidationError(format!(
+                "collection membership forms a cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let mut warnings = vec![];
+        if let Some(observables) = &self.observable_objects {
+            let mut object_ids: HashSet<&str> = self
+                .maec_objects
+                .iter()
+                .map(|obj| obj.id())
+                .chain(self.relationships.iter().map(|rel| rel.common.id.as_str()))
+                .collect();
+            object_ids.insert(&self.common.id);
+
+            let mut colliding: Vec<&str> = observables
+                .keys()
+                .map(String::as_str)
+                .filter(|key| object_ids.contains(key))
+                .collect();
+            if !colliding.is_empty() {
+                colliding.sort_unstable();
+                return Err(MaecError::ValidationError(format!(
+                    "observable keys collide with MAEC object ids: {}",
+                    colliding.join(", ")
+                )));
+            }
+
+            let mut referenced: HashSet<&str> = self
+                .maec_objects
+                .iter()
+                .flat_map(|obj| obj.embedded_refs())
+                .map(|(_, r)| r)
+                .collect();
+            for observable in observables.values() {
+                collect_observable_refs(observable, &mut referenced);
+            }
+
+            let mut orphans: Vec<&str> = observables
+                .keys()
+                .map(String::as_str)
+                .filter(|key| !referenced.contains(key))
+                .collect();
+            orphans.sort_unstable();
+            warnings.extend(
+                orphans
+                    .into_iter()
+                    .map(|key| format!("observable '{}' is not referenced by any object", key)),
+            );
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(warnings = warnings.len(), "deep validation finished");
+        Ok(warnings)
+    }
+
+    /// Validates that every entry in `observable_objects` is a STIX Cyber Observable
+    ///
+    /// Each value must be a JSON object whose `type` names a known STIX 2.1
+    /// observable type, and every `*_ref`/`*_refs` property inside it must
+    /// point at another key of `observable_objects`. All offending keys are
+    /// reported together in a single `MaecError::ValidationError`.
+    pub fn validate_observables(&self) -> Result<()> {
+        let Some(observables) = &self.observable_objects else {
+            return Ok(());
+        };
+
+        let mut keys: Vec<&String> = observables.keys().collect();
+        keys.sort_unstable();
+
+        let mut problems = vec![];
+        for key in keys {
+            let observable = &observables[key];
+            let Some(map) = observable.as_object() else {
+                problems.push(format!("observable '{}' is not a JSON object", key));
+                continue;
+            };
+
+            match map.get("type").and_then(|t| t.as_str()) {
+                None => problems.push(format!("observable '{}' has no string 'type'", key)),
+                Some(t) if !STIX_OBSERVABLE_TYPES.contains(&t) => problems.push(format!(
+                    "observable '{}' has unknown STIX observable type '{}'",
+                    key, t
+                )),
+                Some(_) => {}
+            }
+
+            let mut refs = HashSet::new();
+            collect_observable_refs(observable, &mut refs);
+            let mut dangling: Vec<&str> = refs
+                .into_iter()
+                .filter(|r| !observables.contains_key(*r))
+                .collect();
+            dangling.sort_unstable();
+            for r in dangling {
+                problems.push(format!(
+                    "observable '{}' references unknown observable '{}'",
+                    key, r
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MaecError::ValidationError(problems.join("; ")))
+        }
+    }
+
+    /// Validates that malware action object references resolve to observables
+    ///
+    /// The `input_object_refs` and `output_object_refs` of every malware
+    /// action must be keys of `observable_objects`, since actions operate on
+    /// STIX Cyber Observables rather than MAEC objects. References shaped like
+    /// MAEC IDs (`type--uuid`) are called out as such. All problems are
+    /// reported together in a single `MaecError::ValidationError`.
+    /// References between MAEC objects are checked by
+    /// [`validate_references`](Self::validate_references).
+    pub fn validate_observable_refs(&self) -> Result<()> {
+        let mut problems = vec![];
+        for action in self.malware_actions() {
+            let refs = action
+                .input_object_refs
+                .iter()
+                .map(|r| ("input_object_refs", r))
+                .chain(
+                    action
+                        .output_object_refs
+                        .iter()
+                        .map(|r| ("output_object_refs", r)),
+                );
+            for (field, r) in refs {
+                let resolved = self
+                    .observable_objects
+                    .as_ref()
+                    .is_some_and(|observables| observables.contains_key(r));
+                if resolved {
+                    continue;
+                }
+                let hint = if crate::common::is_valid_maec_id(r) {
+                    " (looks like a MAEC object id; action refs must point into observable_objects)"
+                } else {
+                    ""
+                };
+                problems.push(format!(
+                    "malware action '{}' {} references unknown observable '{}'{}",
+                    action.common.id, field, r, hint
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MaecError::ValidationError(problems.join("; ")))
+        }
+    }
+
+    /// Validates that references between MAEC objects resolve
+    ///
+    /// Every `action_refs` entry must name a malware action in
+    /// `maec_objects`, every `behavior_refs` (including those of nested
+    /// capabilities) and `common_behavior_refs` entry a behavior, and every
+    /// collection `entity_refs` entry and relationship `source_ref` /
+    /// `target_ref` some object in the package. Reference types are checked
+    /// with [`is_valid_ref_for_type`](crate::is_valid_ref_for_type).
+    /// References into `observable_objects` are covered by
+    /// [`validate_observable_refs`](Self::validate_observable_refs) and ignored here.
+    ///
+    /// All dangling references are reported together in a single
+    /// `MaecError::InvalidReference`, each naming the reference and the object
+    /// holding it.
+    pub fn validate_references(&self) -> Result<()> {
+        let declared: HashSet<&str> = self.maec_objects.iter().map(|obj| obj.id()).collect();
+
+        let mut problems = vec![];
+        let mut check = |holder: &str, field: &str, r: &str, expected: Option<&str>| {
+            let typed = expected.is_none_or(|t| crate::common::is_valid_ref_for_type(r, t));
+            if !typed || !declared.contains(r) {
+                problems.push(format!(
+                    "'{}' in {} of '{}' does not resolve to {}",
+                    r,
+                    field,
+                    holder,
+                    expected.map_or("an object in the package".to_string(), |t| format!(
+                        "a {} in the package",
+                        t
+                    ))
+                ));
+            }
+        };
+
+        for object in &self.maec_objects {
+            for (field, r) in object.embedded_refs() {
+                let expected = match field {
+                    "action_refs" => Some("malware-action"),
+                    "behavior_refs" | "common_behavior_refs" => Some("behavior"),
+                    "entity_refs" => None,
+                    _ => continue,
+                };
+                check(object.id(), field, r, expected);
+            }
+        }
+        for relationship in &self.relationships {
+            let holder = relationship.common.id.as_str();
+            check(holder, "source_ref", &relationship.source_ref, None);
+            check(holder, "target_ref", &relationship.target_ref, None);
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MaecError::InvalidReference(problems.join("; ")))
+        }
+    }
+
+    /// Returns every edge of the package graph as `(source, type, target)`
+    ///
+    /// Explicit relationships come first, with their relationship type; they
+    /// are followed by the references between MAEC objects embedded in each
+    /// object, typed by the referencing field (e.g. `"action_refs"`). These
+    /// are the fields checked by [`validate_references`](Self::validate_references);
+    /// references into `observable_objects`, such as `instance_object_refs`,
+    /// are not edges. All strings are borrowed from the package and the
+    /// reference fields are walked in place, so nothing is allocated unless
+    /// capabilities are refined.
+    pub fn relationship_edges(&self) -> impl Iterator<Item = (&str, &str, &str)> + '_ {
+        let explicit = self.relationships.iter().map(|rel| {
+            (
+                rel.source_ref.as_str(),
+                rel.relationship_type.as_str(),
+                rel.target_ref.as_str(),
+            )
+        });
+        let embedded = self.maec_objects.iter().flat_map(|object| {
+            let (action_refs, entity_refs, behavior_refs, capabilities): (
+                &[String],
+                &[String],
+                &[String],
+                &[crate::Capability],
+            ) = match object {
+                MaecObjectType::Behavior(behavior) => (&behavior.action_refs, &[], &[], &[]),
+                MaecObjectType::Collection(collection) => (&[], &collection.entity_refs, &[], &[]),
+                MaecObjectType::MalwareAction(_) => (&[], &[], &[], &[]),
+                MaecObjectType::MalwareFamily(family) => (
+                    &[],
+                    &[],
+                    &family.common_behavior_refs,
+                    &family.common_capabilities,
+                ),
+                MaecObjectType::MalwareInstance(instance) => {
+                    (&[], &[], &[], &instance.capabilities)
+                }
+            };
+            fn tagged<'a>(
+                field: &'static str,
+                refs: &'a [String],
+            ) -> impl Iterator<Item = (&'static str, &'a str)> {
+                refs.iter().map(move |r| (field, r.as_str()))
+            }
+            tagged("action_refs", action_refs)
+                .chain(tagged("entity_refs", entity_refs))
+                .chain(tagged("common_behavior_refs", behavior_refs))
+                .chain(crate::Capability::behavior_refs_of(capabilities))
+                .map(move |(field, target)| (object.id(), field, target))
+        });
+        explicit.chain(embedded)
+    }
+
+    /// Builds the unbounded [`PackageGraph`](crate::PackageGraph) of the package
+    ///
+    /// Prefer [`PackageGraph::with_limits`](crate::PackageGraph::with_limits)
+    /// for packages from untrusted sources.
+    pub fn graph(&self) -> crate::PackageGraph {
+        crate::PackageGraph::new(self)
+    }
+
+    /// Finds the cycles formed by the package's relationships
+    ///
+    /// Relationships are treated as directed edges from `source_ref` to
+    /// `target_ref`; embedded references are not considered. Each elementary
+    /// cycle is returned once as the list of IDs along it, starting at its
+    /// lexicographically smallest ID, and the cycles are sorted, so the result
+    /// is deterministic. A self-relationship yields a single-element cycle.
+    /// An empty vector means the relationships are acyclic.
+    ///
+    /// Acyclic parts of the graph are skipped in linear time and no search
+    /// recurses, so hostile input cannot blow up the running time or the
+    /// stack; only the number of cycles that exist bounds the work.
+    pub fn find_relationship_cycles(&self) -> Vec<Vec<String>> {
+        let ids: Vec<&str> = self
+            .relationships
+            .iter()
+            .flat_map(|rel| [rel.source_ref.as_str(), rel.target_ref.as_str()])
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        // Sorted IDs make index order agree with lexicographic order
+        let index = |id: &str| ids.binary_search(&id).expect("ID was collected");
+
+        let mut adjacency = vec![vec![]; ids.len()];
+        for rel in &self.relationships {
+            adjacency[index(&rel.source_ref)].push(index(&rel.target_ref));
+        }
+        for targets in &mut adjacency {
+            targets.sort_unstable();
+            targets.dedup();
+        }
+
+        let mut cycles: Vec<Vec<String>> = crate::graph::elementary_cycles(&adjacency)
+            .into_iter()
+            .map(|cycle| {
+                cycle
+                    .into_iter()
+                    .map(|node| ids[node].to_string())
+                    .collect()
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// Lists the family and instance labels outside the MAEC label vocabulary
+    ///
+    /// Returns `(object ID, label)` pairs in package order for every label
+    /// that is not a [`MalwareLabel`](crate::MalwareLabel) value.
+    pub fn non_standard_labels(&self) -> Vec<(String, String)> {
+        self.maec_objects
+            .iter()
+            .flat_map(|object| {
+                let labels: &[String] = match object {
+                    MaecObjectType::MalwareFamily(family) => &family.labels,
+                    MaecObjectType::MalwareInstance(instance) => &instance.labels,
+                    _ => &[],
+                };
+                labels
+                    .iter()
+                    .filter(|label| label.parse::<crate::MalwareLabel>().is_err())
+                    .map(move |label| (object.id().to_string(), label.clone()))
+            })
+            .collect()
+    }
+
+    /// Groups behaviors under the capabilities that reference them
+    ///
+    /// Every capability of the package's families and instances, including
+    /// refinements, that references at least one behavior in the package is
+    /// listed in package order with the behaviors its `behavior_refs` resolve
+    /// to. Behaviors referenced by no capability are listed last under a
+    /// synthetic capability named `"uncategorized"`, which is omitted when
+    /// empty.
+    pub fn behaviors_by_capability(&self) -> Vec<(&crate::Capability, Vec<&crate::Behavior>)> {
+        static UNCATEGORIZED: std::sync::OnceLock<crate::Capability> = std::sync::OnceLock::new();
+
+        let behaviors: HashMap<&str, &crate::Behavior> = self
+            .behaviors()
+            .into_iter()
+            .map(|behavior| (behavior.common.id.as_str(), behavior))
+            .collect();
+
+        let mut pending: Vec<&crate::Capability> = self
+            .maec_objects
+            .iter()
+            .flat_map(|object| match object {
+                MaecObjectType::MalwareFamily(family) => family.common_capabilities.as_slice(),
+                MaecObjectType::MalwareInstance(instance) => instance.capabilities.as_slice(),
+                _ => &[],
+            })
+            .rev()
+            .collect();
+        let mut groups = vec![];
+        let mut categorized = HashSet::new();
+        while let Some(capability) = pending.pop() {
+            pending.extend(capability.refined_capabilities.iter().rev());
+            let members: Vec<&crate::Behavior> = capability
+                .behavior_refs
+                .iter()
+                .filter_map(|r| behaviors.get(r.as_str()).copied())
+                .collect();
+            if !members.is_empty() {
+                categorized.extend(members.iter().map(|behavior| behavior.common.id.as_str()));
+                groups.push((capability, members));
+            }
+        }
+
+        let uncategorized: Vec<&crate::Behavior> = self
+            .behaviors()
+            .into_iter()
+            .filter(|behavior| !categorized.contains(behavior.common.id.as_str()))
+            .collect();
+        if !uncategorized.is_empty() {
+            let bucket = UNCATEGORIZED.get_or_init(|| crate::Capability::new("uncategorized"));
+            groups.push((bucket, uncategorized));
+        }
+        groups
+    }
+
+    /// Maps each object type (e.g. `"behavior"`) to the IDs of its objects
+    ///
+    /// IDs are listed in package order. Relationships are not included.
+    pub fn ids_by_type(&self) -> HashMap<String, Vec<String>> {
+        let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+        for object in &self.maec_objects {
+            ids.entry(object.type_().to_string())
+                .or_default()
+                .push(object.id().to_string());
+        }
+        ids
+    }
+
+    /// Returns the IDs of all objects followed by those of all relationships
+    ///
+    /// The package's own ID is not included.
+    pub fn all_ids(&self) -> Vec<&str> {
+        self.maec_objects
+            .iter()
+            .map(|obj| obj.id())
+            .chain(self.relationships.iter().map(|rel| rel.common.id.as_str()))
+            .collect()
+    }
+
     pub fn malware_families(&self) -> Vec<&crate::MalwareFamily> {
         self.maec_objects
             .iter()
@@ -113,6 +996,14 @@ impl Package {
             .collect()
     }
 
+    /// Returns the behaviors whose names are not in the MAEC behavior vocabulary
+    pub fn non_spec_behaviors(&self) -> Vec<&crate::Behavior> {
+        self.behaviors()
+            .into_iter()
+            .filter(|behavior| !behavior.is_spec_behavior())
+            .collect()
+    }
+
     pub fn malware_actions(&self) -> Vec<&crate::MalwareAction> {
         self.maec_objects
             .iter()
@@ -122,112 +1013,3267 @@ impl Package {
             })
             .collect()
     }
-}
 
-impl MaecObject for Package {
-    fn id(&self) -> &str {
-        &self.common.id
-    }
-    fn type_(&self) -> &str {
-        &self.common.r#type
-    }
-    fn created(&self) -> DateTime<Utc> {
-        self.common.created
-    }
-}
+    /// Reconstructs the execution timeline from behaviors and malware actions
+    ///
+    /// Returns one event per timestamped Behavior or MalwareAction, sorted in
+    /// ascending order of time. Objects without a timestamp are omitted.
+    pub fn timeline(&self) -> Vec<TimelineEvent> {
+        let mut events: Vec<TimelineEvent> = self
+            .maec_objects
+            .iter()
+            .filter_map(|obj| match obj {
+                MaecObjectType::Behavior(behavior) => Some(TimelineEvent {
+                    timestamp: behavior.timestamp?,
+                    kind: TimelineEventKind::Behavior,
+                    object_id: behavior.common.id.clone(),
+                    label: behavior.name.as_ref().to_string(),
+                }),
+                MaecObjectType::MalwareAction(action) => Some(TimelineEvent {
+                    timestamp: action.timestamp?,
+                    kind: TimelineEventKind::Action,
+                    object_id: action.common.id.clone(),
+                    label: action.name.as_ref().to_string(),
+                }),
+                _ => None,
+            })
+            .collect();
 
-impl Default for Package {
-    fn default() -> Self {
-        Self::new()
+        events.sort_by_key(|event| event.timestamp);
+        events
     }
-}
 
-/// Builder for Package objects
-#[derive(Debug, Default)]
-pub struct PackageBuilder {
-    id: Option<String>,
-    schema_version: Option<String>,
-    maec_objects: Vec<MaecObjectType>,
-    observable_objects: Option<HashMap<String, serde_json::Value>>,
-    relationships: Vec<crate::Relationship>,
-}
+    /// Merges another package into this one
+    ///
+    /// Objects and relationships whose IDs are not yet present are appended;
+    /// on ID collisions the existing object is kept, except that the field
+    /// data of colliding malware families and instances is combined with
+    /// [`FieldData::merge`](crate::FieldData::merge). Observable objects are
+    /// unioned, again preferring existing entries.
+    ///
+    /// Before merging, field-level provenance is recorded on the malware
+    /// instances of both packages from each package's `created_by_ref`, so the
+    /// source of every field survives the merge.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                package = %self.common.id,
+                other = %other.common.id,
+                objects = self.maec_objects.len(),
+                other_objects = other.maec_objects.len(),
+            )
+        )
+    )]
+    pub fn merge(&mut self, other: Package) {
+        if let Some(source) = self.common.created_by_ref.clone() {
+            self.record_instance_provenance(&source);
+        }
 
-impl PackageBuilder {
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.id = Some(id.into());
-        self
+        let mut other = other;
+        if let Some(source) = other.common.created_by_ref.clone() {
+            other.record_instance_provenance(&source);
+        }
+
+        let mut object_positions: HashMap<String, usize> = self
+            .maec_objects
+            .iter()
+            .enumerate()
+            .map(|(position, obj)| (obj.id().to_string(), position))
+            .collect();
+        for object in other.maec_objects {
+            match object_positions.get(object.id()) {
+                Some(&position) => merge_field_data(&mut self.maec_objects[position], &object),
+                None => {
+                    object_positions.insert(object.id().to_string(), self.maec_objects.len());
+                    self.maec_objects.push(object);
+                }
+            }
+        }
+
+        let mut relationship_ids: HashSet<String> = self
+            .relationships
+            .iter()
+            .map(|rel| rel.common.id.clone())
+            .collect();
+        for relationship in other.relationships {
+            if relationship_ids.insert(relationship.common.id.clone()) {
+                self.relationships.push(relationship);
+            }
+        }
+
+        if let Some(observables) = other.observable_objects {
+            let existing = self.observable_objects.get_or_insert_with(BTreeMap::new);
+            for (key, value) in observables {
+                existing.entry(key).or_insert(value);
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            objects = self.maec_objects.len(),
+            relationships = self.relationships.len(),
+            "merge finished"
+        );
     }
 
-    pub fn schema_version(mut self, version: impl Into<String>) -> Self {
-        self.schema_version = Some(version.into());
-        self
+    /// Finds the shortest relationship path between two objects
+    ///
+    /// Relationships are followed from `source_ref` to `target_ref`. When
+    /// `rel_types` is given, only relationships of those types are traversed.
+    /// Returns the IDs along the path, including both endpoints, or `None` if
+    /// `to` is not reachable from `from`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, rel_types))
+    )]
+    pub fn path(&self, from: &str, to: &str, rel_types: Option<&[&str]>) -> Option<Vec<String>> {
+        if from == to {
+            return Some(vec![from.to_string()]);
+        }
+
+        let adjacency = self.relationship_adjacency(rel_types);
+        let mut predecessors: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(current) = queue.pop_front() {
+            for &next in adjacency.get(current).into_iter().flatten() {
+                if next == from || predecessors.contains_key(next) {
+                    continue;
+                }
+                predecessors.insert(next, current);
+
+                if next == to {
+                    let mut path = vec![to.to_string()];
+                    let mut step = to;
+                    while let Some(&previous) = predecessors.get(step) {
+                        path.push(previous.to_string());
+                        step = previous;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(next);
+            }
+        }
+
+        None
     }
 
-    pub fn add_object(mut self, object: MaecObjectType) -> Self {
-        self.maec_objects.push(object);
-        self
+    /// Returns the IDs of every object reachable from `id` via relationships
+    ///
+    /// The starting ID itself is only included if it lies on a cycle.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn reachable_from(&self, id: &str) -> HashSet<String> {
+        let adjacency = self.relationship_adjacency(None);
+        let mut reachable: HashSet<&str> = HashSet::new();
+        let mut queue = VecDeque::from([id]);
+
+        while let Some(current) = queue.pop_front() {
+            for &next in adjacency.get(current).into_iter().flatten() {
+                if reachable.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        reachable.into_iter().map(str::to_string).collect()
     }
 
-    pub fn add_malware_family(mut self, family: crate::MalwareFamily) -> Self {
-        self.maec_objects
-            .push(MaecObjectType::MalwareFamily(family));
-        self
+    /// Returns a copy of the package keeping only objects of the given kinds
+    ///
+    /// Relationships are kept only if both their source and target are among
+    /// the remaining objects. Package properties and observable objects are
+    /// copied unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::{MalwareFamily, ObjectKind, Package};
+    ///
+    /// let package = Package::builder()
+    ///     .add_malware_family(MalwareFamily::new("WannaCry"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(package.project(&[ObjectKind::Behavior]).maec_objects.is_empty());
+    /// ```
+    pub fn project(&self, kinds: &[ObjectKind]) -> Package {
+        let maec_objects: Vec<MaecObjectType> = self
+            .maec_objects
+            .iter()
+            .filter(|obj| kinds.contains(&obj.kind()))
+            .cloned()
+            .collect();
+
+        let kept: HashSet<&str> = maec_objects.iter().map(|obj| obj.id()).collect();
+        let relationships = self
+            .relationships
+            .iter()
+            .filter(|rel| {
+                kept.contains(rel.source_ref.as_str()) && kept.contains(rel.target_ref.as_str())
+            })
+            .cloned()
+            .collect();
+
+        Package {
+            common: self.common.clone(),
+            observable_objects: self.observable_objects.clone(),
+            relationships,
+            maec_objects,
+        }
     }
 
-    pub fn add_malware_instance(mut self, instance: crate::MalwareInstance) -> Self {
-        self.maec_objects
-            .push(MaecObjectType::MalwareInstance(instance));
-        self
+    /// Keeps only the objects for which `f` returns `true`
+    ///
+    /// As with [`Package::project`], relationships are kept only if both their
+    /// source and target are among the retained objects. Returns the number of
+    /// removed objects and removed relationships.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::{Behavior, BehaviorVocab, MaecObjectType, MalwareFamily, ObjectKind, Package};
+    ///
+    /// let mut package = Package::builder()
+    ///     .add_malware_family(MalwareFamily::new("WannaCry"))
+    ///     .add_behavior(Behavior::new(BehaviorVocab::EncryptFiles))
+    ///     .build()
+    ///     .unwrap();
+    /// let removed = package.retain(|obj| obj.kind() == ObjectKind::MalwareFamily);
+    /// assert_eq!(removed, (1, 0));
+    /// assert_eq!(package.maec_objects.len(), 1);
+    /// ```
+    pub fn retain(&mut self, f: impl Fn(&MaecObjectType) -> bool) -> (usize, usize) {
+        let object_count = self.maec_objects.len();
+        self.maec_objects.retain(|obj| f(obj));
+
+        let kept: HashSet<&str> = self.maec_objects.iter().map(|obj| obj.id()).collect();
+        let relationship_count = self.relationships.len();
+        self.relationships.retain(|rel| {
+            kept.contains(rel.source_ref.as_str()) && kept.contains(rel.target_ref.as_str())
+        });
+
+        (
+            object_count - self.maec_objects.len(),
+            relationship_count - self.relationships.len(),
+        )
     }
 
-    pub fn add_behavior(mut self, behavior: crate::Behavior) -> Self {
-        self.maec_objects.push(MaecObjectType::Behavior(behavior));
-        self
+    /// Returns the references that do not resolve within the package
+    ///
+    /// Considered are the embedded references of every object (see
+    /// [`MaecObjectType::embedded_refs`]), relationship endpoints, the
+    /// `created_by_ref` of the package, its objects and relationships, and
+    /// the `*_ref`/`*_refs` properties inside observable objects. A reference
+    /// resolves if it names the package, a contained object or relationship,
+    /// or a key of `observable_objects`. The result is sorted and free of
+    /// duplicates.
+    pub fn external_refs(&self) -> Vec<String> {
+        let mut known: HashSet<&str> = self
+            .maec_objects
+            .iter()
+            .map(|obj| obj.id())
+            .chain(self.relationships.iter().map(|rel| rel.common.id.as_str()))
+            .collect();
+        known.insert(&self.common.id);
+        if let Some(observables) = &self.observable_objects {
+            known.extend(observables.keys().map(String::as_str));
+        }
+
+        let mut refs: HashSet<&str> = HashSet::new();
+        for object in &self.maec_objects {
+            refs.extend(object.embedded_refs().into_iter().map(|(_, r)| r));
+            refs.extend(object.common().created_by_ref.as_deref());
+        }
+        for relationship in &self.relationships {
+            refs.insert(&relationship.source_ref);
+            refs.insert(&relationship.target_ref);
+            refs.extend(relationship.common.created_by_ref.as_deref());
+        }
+        refs.extend(self.common.created_by_ref.as_deref());
+        for observable in self.observable_objects.iter().flat_map(|o| o.values()) {
+            collect_observable_refs(observable, &mut refs);
+        }
+
+        let mut external: Vec<String> = refs
+            .into_iter()
+            .filter(|r| !known.contains(r))
+            .map(str::to_string)
+            .collect();
+        external.sort_unstable();
+        external
     }
 
-    pub fn add_malware_action(mut self, action: crate::MalwareAction) -> Self {
-        self.maec_objects
-            .push(MaecObjectType::MalwareAction(action));
-        self
+    /// Returns whether every reference resolves within the package
+    ///
+    /// See [`external_refs`](Self::external_refs).
+    pub fn is_self_contained(&self) -> bool {
+        self.external_refs().is_empty()
+    }
+
+    /// Computes a stable SHA-256 fingerprint of the package content
+    ///
+    /// The fingerprint covers every object, relationship and observable
+    /// object, but ignores the order in which they appear, the package's own
+    /// properties, and the `id`, `created` and `modified` properties of the
+    /// objects and relationships. Unordered collections inside objects, such
+    /// as labels, aliases, capabilities and reference lists other than
+    /// `entity_refs`, are sorted as well. References between objects, i.e.
+    /// the values of `*_ref` and `*_refs` properties, are replaced by a hash
+    /// of the referenced object's content, so regenerating IDs leaves the
+    /// fingerprint unchanged. Returns the hash as lowercase hex.
+    pub fn fingerprint(&self) -> String {
+        let objects: Vec<(&str, serde_json::Value)> = self
+            .maec_objects
+            .iter()
+            .map(|obj| (obj.id(), serde_json::to_value(obj).unwrap_or_default()))
+            .collect();
+        let relationships: Vec<serde_json::Value> = self
+            .relationships
+            .iter()
+            .map(|rel| serde_json::to_value(rel).unwrap_or_default())
+            .collect();
+
+        // First pass: hash each object with all in-package IDs blanked out
+        let mut blanked: HashMap<&str, String> = objects
+            .iter()
+            .map(|(id, _)| (*id, String::new()))
+            .chain(
+                self.relationships
+                    .iter()
+                    .map(|rel| (rel.common.id.as_str(), String::new())),
+            )
+            .collect();
+        blanked.insert(&self.common.id, String::new());
+        let content_ids: HashMap<&str, String> = objects
+            .iter()
+            .map(|(id, value)| (*id, sha256_hex(&content_form(value, &blanked))))
+            .collect();
+
+        // Second pass: encode every item with references resolved to content
+        let mut resolved = blanked;
+        resolved.extend(content_ids.iter().map(|(id, hash)| (*id, hash.clone())));
+        let mut items: Vec<String> = objects
+            .iter()
+            .map(|(_, value)| value)
+            .chain(&relationships)
+            .map(|value| content_form(value, &resolved))
+            .collect();
+        if let Some(observables) = &self.observable_objects {
+            let observables: serde_json::Map<_, _> = observables
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            items.push(crate::common::canonical_json(&serde_json::Value::Object(
+                observables,
+            )));
+        }
+        items.sort_unstable();
+
+        sha256_hex(&items.join("\n"))
+    }
+
+    /// Returns every external reference in the package, in package order
+    ///
+    /// Covers family and instance references and name sources, capability
+    /// references (including refinements), analysis metadata references and
+    /// behavior technique references.
+    pub fn external_references(&self) -> Vec<&crate::common::ExternalReference> {
+        let mut references = vec![];
+        for object in &self.maec_objects {
+            match object {
+                MaecObjectType::Behavior(behavior) => {
+                    references.extend(&behavior.technique_refs);
+                }
+                MaecObjectType::MalwareFamily(family) => {
+                    references.extend(
+                        family
+                            .all_names()
+                            .into_iter()
+                            .filter_map(|n| n.source.as_ref()),
+                    );
+                    references.extend(&family.references);
+                    for capability in &family.common_capabilities {
+                        capability.collect_references(&mut references);
+                    }
+                }
+                MaecObjectType::MalwareInstance(instance) => {
+                    references.extend(
+                        instance
+                            .all_names()
+                            .into_iter()
+                            .filter_map(|n| n.source.as_ref()),
+                    );
+                    for capability in &instance.capabilities {
+                        capability.collect_references(&mut references);
+                    }
+                    for metadata in &instance.analysis_metadata {
+                        references.extend(&metadata.references);
+                    }
+                }
+                MaecObjectType::Collection(_) | MaecObjectType::MalwareAction(_) => {}
+            }
+        }
+        references
+    }
+
+    /// Resolves every external reference with `resolver`
+    ///
+    /// Returns the resolved data keyed by
+    /// [`ExternalReference::key`](crate::ExternalReference::key); references
+    /// the resolver does not know are left out. Each distinct key is resolved
+    /// once.
+    pub fn resolve_external_refs(
+        &self,
+        resolver: &impl crate::common::ReferenceResolver,
+    ) -> HashMap<String, serde_json::Value> {
+        let mut resolved = HashMap::new();
+        let mut attempted = HashSet::new();
+        for reference in self.external_references() {
+            let key = reference.key();
+            if attempted.insert(key.clone()) {
+                if let Some(value) = resolver.resolve(reference) {
+                    resolved.insert(key, value);
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Returns a display card for every object, in package order
+    ///
+    /// See [`MaecObjectType::to_card`].
+    pub fn cards(&self) -> Vec<serde_json::Value> {
+        self.maec_objects
+            .iter()
+            .map(MaecObjectType::to_card)
+            .collect()
+    }
+
+    /// Counts the relationships of each relationship type
+    pub fn relationship_type_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for relationship in &self.relationships {
+            *counts
+                .entry(relationship.relationship_type.as_str())
+                .or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Checks relationship endpoints against the kinds their type allows
+    ///
+    /// Each relationship with a MAEC-defined type (see
+    /// [`RelationshipType::valid_endpoints`](crate::RelationshipType::valid_endpoints))
+    /// whose source and target resolve to objects in the package is checked;
+    /// custom types and endpoints outside the package are skipped. Returns one
+    /// `MaecError::ValidationError` per offending relationship.
+    pub fn validate_relationship_semantics(&self) -> Vec<MaecError> {
+        let kinds: HashMap<&str, ObjectKind> = self
+            .maec_objects
+            .iter()
+            .map(|obj| (obj.id(), obj.kind()))
+            .collect();
+
+        let mut errors = vec![];
+        for relationship in &self.relationships {
+            let Some(rel_type) = relationship.known_type() else {
+                continue;
+            };
+            let (Some(source), Some(target)) = (
+                kinds.get(relationship.source_ref.as_str()),
+                kinds.get(relationship.target_ref.as_str()),
+            ) else {
+                continue;
+            };
+
+            let (sources, targets) = rel_type.valid_endpoints();
+            if !sources.contains(source) || !targets.contains(target) {
+                errors.push(MaecError::ValidationError(format!(
+                    "relationship '{}' of type '{}' cannot connect {:?} to {:?}",
+                    relationship.common.id, relationship.relationship_type, source, target
+                )));
+            }
+        }
+        errors
+    }
+
+    /// Flags timestamps later than `now + tolerance`
+    ///
+    /// Checks `created` and `modified` of the package, its objects and
+    /// relationships, behavior and action `timestamp`s, `first_seen` and
+    /// `last_seen` of field data, and the times of analysis metadata. Returns
+    /// one `MaecError::ValidationError` per offending timestamp, naming the
+    /// object ID and property.
+    pub fn validate_no_future_timestamps(
+        &self,
+        now: DateTime<Utc>,
+        tolerance: chrono::Duration,
+    ) -> Vec<MaecError> {
+        let commons = std::iter::once(&self.common)
+            .chain(self.relationships.iter().map(|rel| &rel.common))
+            .chain(self.maec_objects.iter().map(|obj| obj.common()));
+        let mut timestamps: Vec<(&str, &'static str, DateTime<Utc>)> = vec![];
+        for common in commons {
+            timestamps.push((&common.id, "created", common.created));
+            timestamps.push((&common.id, "modified", common.modified));
+        }
+        for object in &self.maec_objects {
+            let id = object.id();
+            let (timestamp, field_data) = match object {
+                MaecObjectType::Behavior(behavior) => (behavior.timestamp, None),
+                MaecObjectType::MalwareAction(action) => (action.timestamp, None),
+                MaecObjectType::MalwareFamily(family) => (None, family.field_data.as_ref()),
+                MaecObjectType::MalwareInstance(instance) => {
+                    for metadata in &instance.analysis_metadata {
+                        timestamps.extend(
+                            [
+                                ("analysis_metadata.start_time", metadata.start_time),
+                                ("analysis_metadata.end_time", metadata.end_time),
+                                (
+                                    "analysis_metadata.last_update_time",
+                                    metadata.last_update_time,
+                                ),
+                            ]
+                            .into_iter()
+                            .filter_map(|(field, time)| Some((id, field, time?))),
+                        );
+                    }
+                    (None, instance.field_data.as_ref())
+                }
+                MaecObjectType::Collection(_) => (None, None),
+            };
+            timestamps.extend(timestamp.map(|time| (id, "timestamp", time)));
+            if let Some(field_data) = field_data {
+                timestamps.extend(
+                    field_data
+                        .first_seen
+                        .map(|t| (id, "field_data.first_seen", t)),
+                );
+                timestamps.extend(
+                    field_data
+                        .last_seen
+                        .map(|t| (id, "field_data.last_seen", t)),
+                );
+            }
+        }
+
+        let limit = now + tolerance;
+        timestamps
+            .into_iter()
+            .filter(|(_, _, time)| *time > limit)
+            .map(|(id, field, time)| {
+                MaecError::ValidationError(format!(
+                    "'{}' of '{}' is in the future: {}",
+                    field,
+                    id,
+                    time.to_rfc3339()
+                ))
+            })
+            .collect()
+    }
+
+    /// Groups malware instances by capability similarity
+    ///
+    /// Instances are linked when their
+    /// [`capability_similarity`](crate::MalwareInstance::capability_similarity)
+    /// is greater than `threshold`, and clusters are the connected components
+    /// of those links (single-linkage clustering). A threshold of `0.0` thus
+    /// links only instances sharing a capability. Every instance appears in
+    /// exactly one cluster, so unrelated instances form singleton clusters.
+    /// Clusters and the IDs within them follow package order.
+    pub fn cluster_instances(&self, threshold: f64) -> Vec<Vec<String>> {
+        let instances = self.malware_instances();
+
+        // Union-find over instance indices
+        let mut parent: Vec<usize> = (0..instances.len()).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for i in 0..instances.len() {
+            for j in (i + 1)..instances.len() {
+                if instances[i].capability_similarity(instances[j]) > threshold {
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    if a != b {
+                        parent[b.max(a)] = a.min(b);
+                    }
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<String>> = vec![];
+        let mut cluster_of_root: HashMap<usize, usize> = HashMap::new();
+        for (i, instance) in instances.iter().enumerate() {
+            let root = find(&mut parent, i);
+            let index = *cluster_of_root.entry(root).or_insert_with(|| {
+                clusters.push(vec![]);
+                clusters.len() - 1
+            });
+            clusters[index].push(instance.common.id.clone());
+        }
+        clusters
+    }
+
+    /// Builds a directed adjacency list from the package's relationships
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(relationships = self.relationships.len())
+        )
+    )]
+    fn relationship_adjacency(&self, rel_types: Option<&[&str]>) -> HashMap<&str, Vec<&str>> {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for relationship in &self.relationships {
+            let allowed = rel_types
+                .map(|types| types.contains(&relationship.relationship_type.as_str()))
+                .unwrap_or(true);
+            if allowed {
+                adjacency
+                    .entry(relationship.source_ref.as_str())
+                    .or_default()
+                    .push(relationship.target_ref.as_str());
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(nodes = adjacency.len(), "relationship graph built");
+        adjacency
+    }
+
+    /// Merges relationships with the same source, type and target
+    ///
+    /// The first relationship of each group is kept and its
+    /// [`count`](crate::Relationship::count) becomes the sum of the group's
+    /// counts, where a relationship without a count counts once. Counts of
+    /// relationships without duplicates are left as they are. Returns the
+    /// number of relationships removed.
+    pub fn dedup_relationships(&mut self) -> usize {
+        let mut survivors: HashMap<(String, String, String), usize> = HashMap::new();
+        let mut kept: Vec<crate::Relationship> = Vec::with_capacity(self.relationships.len());
+        let mut removed = 0;
+
+        for relationship in std::mem::take(&mut self.relationships) {
+            let key = (
+                relationship.source_ref.clone(),
+                relationship.relationship_type.clone(),
+                relationship.target_ref.clone(),
+            );
+            match survivors.get(&key) {
+                Some(&index) => {
+                    let survivor = &mut kept[index];
+                    let total = survivor
+                        .count()
+                        .unwrap_or(1)
+                        .saturating_add(relationship.count().unwrap_or(1));
+                    survivor.set_count(total);
+                    removed += 1;
+                }
+                None => {
+                    survivors.insert(key, kept.len());
+                    kept.push(relationship);
+                }
+            }
+        }
+        self.relationships = kept;
+        removed
+    }
+
+    /// Merges behaviors that share a name and attributes
+    ///
+    /// For every group of behaviors with the same `name` and equal
+    /// `attributes` (absent attributes equal empty ones), the first behavior
+    /// is kept and receives the union of the group's `action_refs` and
+    /// `technique_refs`. The others are removed, and references to them from
+    /// capabilities, malware families, collections and relationships are
+    /// rewritten to the kept behavior. Reference lists that end up naming the
+    /// kept behavior twice are deduplicated, and relationships between two
+    /// merged behaviors, which would become self-loops, are dropped. Returns
+    /// the number of behaviors removed.
+    pub fn dedup_behaviors(&mut self) -> usize {
+        let mut survivors: HashMap<String, usize> = HashMap::new();
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        let mut kept = Vec::with_capacity(self.maec_objects.len());
+
+        for object in std::mem::take(&mut self.maec_objects) {
+            let MaecObjectType::Behavior(behavior) = object else {
+                kept.push(object);
+                continue;
+            };
+            let empty = BTreeMap::new();
+            let key = crate::common::canonical_json(&serde_json::json!([
+                behavior.name,
+                behavior.attributes.as_ref().unwrap_or(&empty),
+            ]));
+
+            match survivors.get(&key) {
+                Some(&index) => {
+                    let MaecObjectType::Behavior(survivor) = &mut kept[index] else {
+                        unreachable!("survivor indices always point at behaviors");
+                    };
+                    for r in behavior.action_refs {
+                        if !survivor.action_refs.contains(&r) {
+                            survivor.action_refs.push(r);
+                        }
+                    }
+                    for r in behavior.technique_refs {
+                        if !survivor.technique_refs.contains(&r) {
+                            survivor.technique_refs.push(r);
+                        }
+                    }
+                    replacements.insert(behavior.common.id, survivor.common.id.clone());
+                }
+                None => {
+                    survivors.insert(key, kept.len());
+                    kept.push(MaecObjectType::Behavior(behavior));
+                }
+            }
+        }
+        self.maec_objects = kept;
+
+        if !replacements.is_empty() {
+            self.rewrite_behavior_refs(&replacements);
+        }
+        replacements.len()
+    }
+
+    /// Rewrites references to behaviors according to `replacements`
+    fn rewrite_behavior_refs(&mut self, replacements: &HashMap<String, String>) {
+        let rewrite = |r: &mut String| {
+            if let Some(replacement) = replacements.get(r) {
+                r.clone_from(replacement);
+            }
+        };
+
+        for object in &mut self.maec_objects {
+            match object {
+                MaecObjectType::Collection(collection) => {
+                    collection.entity_refs.iter_mut().for_each(rewrite);
+                    let mut seen = HashSet::new();
+                    collection.entity_refs.retain(|r| seen.insert(r.clone()));
+                }
+                MaecObjectType::MalwareFamily(family) => {
+                    family.common_behavior_refs.iter_mut().for_each(rewrite);
+                    let mut seen = HashSet::new();
+                    family
+                        .common_behavior_refs
+                        .retain(|r| seen.insert(r.clone()));
+                    for capability in &mut family.common_capabilities {
+                        capability.rewrite_behavior_refs(replacements);
+                    }
+                }
+                MaecObjectType::MalwareInstance(instance) => {
+                    for capability in &mut instance.capabilities {
+                        capability.rewrite_behavior_refs(replacements);
+                    }
+                }
+                MaecObjectType::Behavior(_) | MaecObjectType::MalwareAction(_) => {}
+            }
+        }
+        // Relationships between merged behaviors would become self-loops
+        self.relationships.retain_mut(|relationship| {
+            let was_loop = relationship.source_ref == relationship.target_ref;
+            rewrite(&mut relationship.source_ref);
+            rewrite(&mut relationship.target_ref);
+            was_loop || relationship.source_ref != relationship.target_ref
+        });
+    }
+
+    /// Checks every attribute and custom property value against the default
+    /// depth and size limits
+    fn validate_value_limits(&self) -> Result<()> {
+        let mut maps: Vec<(&str, String, &BTreeMap<String, serde_json::Value>)> = vec![(
+            &self.common.id,
+            "/custom_properties".to_string(),
+            &self.common.custom_properties,
+        )];
+        for relationship in &self.relationships {
+            maps.push((
+                &relationship.common.id,
+                "/custom_properties".to_string(),
+                &relationship.common.custom_properties,
+            ));
+        }
+        for object in &self.maec_objects {
+            let id = object.id();
+            maps.push((
+                id,
+                "/custom_properties".to_string(),
+                &object.common().custom_properties,
+            ));
+
+            let (field, capabilities) = match object {
+                MaecObjectType::Behavior(behavior) => {
+                    maps.extend(
+                        behavior
+                            .attributes
+                            .as_ref()
+                            .map(|attributes| (id, "/attributes".to_string(), attributes)),
+                    );
+                    continue;
+                }
+                MaecObjectType::MalwareFamily(family) => {
+                    ("/common_capabilities", &family.common_capabilities)
+                }
+                MaecObjectType::MalwareInstance(instance) => {
+                    ("/capabilities", &instance.capabilities)
+                }
+                MaecObjectType::Collection(_) | MaecObjectType::MalwareAction(_) => continue,
+            };
+
+            let mut pending: Vec<(String, &crate::Capability)> = capabilities
+                .iter()
+                .enumerate()
+                .map(|(i, capability)| (format!("{}/{}", field, i), capability))
+                .collect();
+            while let Some((path, capability)) = pending.pop() {
+                if let Some(attributes) = &capability.attributes {
+                    maps.push((id, format!("{}/attributes", path), attributes));
+                }
+                pending.extend(
+                    capability
+                        .refined_capabilities
+                        .iter()
+                        .enumerate()
+                        .map(|(i, refined)| {
+                            (format!("{}/refined_capabilities/{}", path, i), refined)
+                        }),
+                );
+            }
+        }
+
+        for (owner, path, map) in maps {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort_unstable();
+            for key in keys {
+                let base = format!("{}/{}", path, crate::common::escape_pointer_token(key));
+                crate::common::check_value_limits(
+                    &map[key],
+                    &base,
+                    crate::common::DEFAULT_MAX_VALUE_DEPTH,
+                    crate::common::DEFAULT_MAX_VALUE_SIZE,
+                )
+                .map_err(|e| match e {
+                    MaecError::ValidationError(msg) => {
+                        MaecError::ValidationError(format!("object '{}': {}", owner, msg))
+                    }
+                    other => other,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds a collection that transitively contains itself
+    ///
+    /// Returns the IDs along the cycle, starting and ending with the same
+    /// collection, or `None` if collection membership is acyclic.
+    fn collection_cycle(&self) -> Option<Vec<&str>> {
+        let members: HashMap<&str, Vec<&str>> = self
+            .maec_objects
+            .iter()
+            .filter_map(|obj| match obj {
+                MaecObjectType::Collection(collection) => Some((
+                    collection.common.id.as_str(),
+                    collection.entity_refs.iter().map(String::as_str).collect(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let mut roots: Vec<&str> = members.keys().copied().collect();
+        roots.sort_unstable();
+
+        // Iterative depth-first search; `path` holds the collections on the
+        // current branch and `done` those already fully explored
+        let mut done: HashSet<&str> = HashSet::new();
+        for root in roots {
+            if done.contains(root) {
+                continue;
+            }
+            let mut path: Vec<&str> = vec![root];
+            let mut stack: Vec<std::slice::Iter<&str>> = vec![members[root].iter()];
+            while let Some(children) = stack.last_mut() {
+                match children.next() {
+                    Some(&child) if members.contains_key(child) && !done.contains(child) => {
+                        if let Some(start) = path.iter().position(|&id| id == child) {
+                            let mut cycle = path[start..].to_vec();
+                            cycle.push(child);
+                            return Some(cycle);
+                        }
+                        path.push(child);
+                        stack.push(members[child].iter());
+                    }
+                    Some(_) => {}
+                    None => {
+                        stack.pop();
+                        done.extend(path.pop());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Attributes the populated fields of every contained instance to `source`
+    fn record_instance_provenance(&mut self, source: &str) {
+        for object in &mut self.maec_objects {
+            if let MaecObjectType::MalwareInstance(instance) = object {
+                instance.record_provenance(source);
+            }
+        }
+    }
+}
+
+/// Deserializes a single object of a package stored as a JSON value
+///
+/// Scans the `maec_objects` array of `package` for an element whose `id` is
+/// `id` and deserializes and validates only that element, leaving the rest of
+/// the package untouched. Returns `Ok(None)` if no such object exists.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{MalwareFamily, Package};
+///
+/// let family = MalwareFamily::new("WannaCry");
+/// let id = family.common.id.clone();
+/// let package = Package::builder().add_malware_family(family).build().unwrap();
+/// let value = serde_json::to_value(&package).unwrap();
+///
+/// let object = maec::object_from_value(&value, &id).unwrap();
+/// assert!(object.is_some());
+/// ```
+pub fn object_from_value(package: &serde_json::Value, id: &str) -> Result<Option<MaecObjectType>> {
+    let objects = match package.get("maec_objects") {
+        None => return Ok(None),
+        Some(serde_json::Value::Array(objects)) => objects,
+        Some(_) => {
+            return Err(MaecError::ValidationError(
+                "maec_objects must be an array".to_string(),
+            ))
+        }
+    };
+
+    let Some(value) = objects
+        .iter()
+        .find(|obj| obj.get("id").and_then(|v| v.as_str()) == Some(id))
+    else {
+        return Ok(None);
+    };
+    let object = MaecObjectType::deserialize(value)?;
+    object.validate()?;
+    Ok(Some(object))
+}
+
+impl MaecObjectType {
+    /// Returns a compact summary of the object for display
+    ///
+    /// The card is a JSON object with the properties:
+    /// - `id` and `type`
+    /// - `name`: the object's name, or `null` if it has none
+    /// - `label_summary`: the labels joined with `", "`, or `null` if there
+    ///   are none
+    /// - `ref_count`: the number of embedded references (see
+    ///   [`embedded_refs`](Self::embedded_refs))
+    pub fn to_card(&self) -> serde_json::Value {
+        let (name, labels): (Option<&str>, &[String]) = match self {
+            MaecObjectType::Behavior(obj) => (Some(obj.name.as_ref()), &[]),
+            MaecObjectType::Collection(obj) => (obj.name.as_deref(), &[]),
+            MaecObjectType::MalwareAction(obj) => (Some(obj.name.as_ref()), &[]),
+            MaecObjectType::MalwareFamily(obj) => (Some(obj.name.value.as_str()), &obj.labels),
+            MaecObjectType::MalwareInstance(obj) => {
+                (obj.name.as_ref().map(|n| n.value.as_str()), &obj.labels)
+            }
+        };
+        let label_summary = (!labels.is_empty()).then(|| labels.join(", "));
+
+        serde_json::json!({
+            "id": self.id(),
+            "type": self.type_(),
+            "name": name,
+            "label_summary": label_summary,
+            "ref_count": self.embedded_refs().len(),
+        })
+    }
+
+    /// Returns the common properties of the wrapped object
+    pub fn common(&self) -> &CommonProperties {
+        match self {
+            MaecObjectType::Behavior(obj) => &obj.common,
+            MaecObjectType::Collection(obj) => &obj.common,
+            MaecObjectType::MalwareAction(obj) => &obj.common,
+            MaecObjectType::MalwareFamily(obj) => &obj.common,
+            MaecObjectType::MalwareInstance(obj) => &obj.common,
+        }
+    }
+
+    /// Returns mutable access to the common properties of the wrapped object
+    pub fn common_mut(&mut self) -> &mut CommonProperties {
+        match self {
+            MaecObjectType::Behavior(obj) => &mut obj.common,
+            MaecObjectType::Collection(obj) => &mut obj.common,
+            MaecObjectType::MalwareAction(obj) => &mut obj.common,
+            MaecObjectType::MalwareFamily(obj) => &mut obj.common,
+            MaecObjectType::MalwareInstance(obj) => &mut obj.common,
+        }
+    }
+
+    /// Returns the kind of the wrapped object
+    pub fn kind(&self) -> ObjectKind {
+        match self {
+            MaecObjectType::Behavior(_) => ObjectKind::Behavior,
+            MaecObjectType::Collection(_) => ObjectKind::Collection,
+            MaecObjectType::MalwareAction(_) => ObjectKind::MalwareAction,
+            MaecObjectType::MalwareFamily(_) => ObjectKind::MalwareFamily,
+            MaecObjectType::MalwareInstance(_) => ObjectKind::MalwareInstance,
+        }
+    }
+
+    /// Validates the wrapped object
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            MaecObjectType::Behavior(obj) => obj.validate(),
+            MaecObjectType::Collection(obj) => obj.validate(),
+            MaecObjectType::MalwareAction(obj) => obj.validate(),
+            MaecObjectType::MalwareFamily(obj) => obj.validate(),
+            MaecObjectType::MalwareInstance(obj) => obj.validate(),
+        }
+    }
+
+    /// Returns every reference held by the object as `(field, referenced id)` pairs
+    ///
+    /// References nested inside capabilities are reported under their
+    /// `behavior_refs` field name.
+    pub fn embedded_refs(&self) -> Vec<(&'static str, &str)> {
+        let mut refs = vec![];
+        match self {
+            MaecObjectType::Behavior(behavior) => {
+                refs.extend(
+                    behavior
+                        .action_refs
+                        .iter()
+                        .map(|r| ("action_refs", r.as_str())),
+                );
+            }
+            MaecObjectType::Collection(collection) => {
+                refs.extend(
+                    collection
+                        .entity_refs
+                        .iter()
+                        .map(|r| ("entity_refs", r.as_str())),
+                );
+            }
+            MaecObjectType::MalwareAction(action) => {
+                refs.extend(
+                    action
+                        .input_object_refs
+                        .iter()
+                        .map(|r| ("input_object_refs", r.as_str())),
+                );
+                refs.extend(
+                    action
+                        .output_object_refs
+                        .iter()
+                        .map(|r| ("output_object_refs", r.as_str())),
+                );
+            }
+            MaecObjectType::MalwareFamily(family) => {
+                refs.extend(
+                    family
+                        .common_code_refs
+                        .iter()
+                        .map(|r| ("common_code_refs", r.as_str())),
+                );
+                refs.extend(
+                    family
+                        .common_behavior_refs
+                        .iter()
+                        .map(|r| ("common_behavior_refs", r.as_str())),
+                );
+                for capability in &family.common_capabilities {
+                    capability.collect_behavior_refs(&mut refs);
+                }
+            }
+            MaecObjectType::MalwareInstance(instance) => {
+                refs.extend(
+                    instance
+                        .instance_object_refs
+                        .iter()
+                        .map(|r| ("instance_object_refs", r.as_str())),
+                );
+                for capability in &instance.capabilities {
+                    capability.collect_behavior_refs(&mut refs);
+                }
+                for metadata in &instance.analysis_metadata {
+                    refs.extend(metadata.tool_refs.iter().map(|r| ("tool_refs", r.as_str())));
+                }
+            }
+        }
+        refs
+    }
+}
+
+/// Combines the field data of `other` into `existing` when both are
+/// malware families or both are malware instances
+fn merge_field_data(existing: &mut MaecObjectType, other: &MaecObjectType) {
+    let (target, source) = match (existing, other) {
+        (MaecObjectType::MalwareFamily(a), MaecObjectType::MalwareFamily(b)) => {
+            (&mut a.field_data, &b.field_data)
+        }
+        (MaecObjectType::MalwareInstance(a), MaecObjectType::MalwareInstance(b)) => {
+            (&mut a.field_data, &b.field_data)
+        }
+        _ => return,
+    };
+    match (target.as_mut(), source) {
+        (Some(target), Some(source)) => target.merge(source),
+        (None, Some(source)) => *target = Some(source.clone()),
+        (_, None) => {}
+    }
+}
+
+/// Properties holding arrays whose order carries no meaning, in addition to
+/// the `*_refs` properties other than `entity_refs`
+const UNORDERED_PROPERTIES: &[&str] = &[
+    "aliases",
+    "capabilities",
+    "common_capabilities",
+    "common_strings",
+    "delivery_vectors",
+    "labels",
+    "refined_capabilities",
+    "references",
+];
+
+/// Encodes an object for fingerprinting
+///
+/// The top-level `id`, `created` and `modified` properties are dropped,
+/// unordered arrays are sorted, and references found in `ids` are replaced by
+/// their mapped value. Only the values of `*_ref` and `*_refs` properties are
+/// references; other strings are kept as they are.
+fn content_form(value: &serde_json::Value, ids: &HashMap<&str, String>) -> String {
+    let mut value = value.clone();
+    if let Some(map) = value.as_object_mut() {
+        for key in ["id", "created", "modified"] {
+            map.remove(key);
+        }
+    }
+    normalize_content(&mut value, "", ids);
+    crate::common::canonical_json(&value)
+}
+
+/// Replaces references and sorts unordered arrays below the property `key`
+fn normalize_content(value: &mut serde_json::Value, key: &str, ids: &HashMap<&str, String>) {
+    match value {
+        serde_json::Value::String(s) if key.ends_with("_ref") || key.ends_with("_refs") => {
+            if let Some(replacement) = ids.get(s.as_str()) {
+                *s = replacement.clone();
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items.iter_mut() {
+                normalize_content(item, key, ids);
+            }
+            let unordered = (key.ends_with("_refs") && key != "entity_refs")
+                || UNORDERED_PROPERTIES.contains(&key);
+            if unordered {
+                items.sort_by_cached_key(crate::common::canonical_json);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                normalize_content(value, key, ids);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn sha256_hex(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Collects the `*_ref` / `*_refs` values found anywhere inside an observable
+fn collect_observable_refs<'a>(value: &'a serde_json::Value, refs: &mut HashSet<&'a str>) {
+    let mut pending = vec![value];
+    while let Some(value) = pending.pop() {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    if key.ends_with("_ref") {
+                        if let Some(r) = value.as_str() {
+                            refs.insert(r);
+                        }
+                    } else if key.ends_with("_refs") {
+                        if let Some(items) = value.as_array() {
+                            refs.extend(items.iter().filter_map(|item| item.as_str()));
+                        }
+                    } else {
+                        pending.push(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => pending.extend(items),
+            _ => {}
+        }
+    }
+}
+
+impl MaecObject for MaecObjectType {
+    fn id(&self) -> &str {
+        match self {
+            MaecObjectType::Behavior(obj) => obj.id(),
+            MaecObjectType::Collection(obj) => obj.id(),
+            MaecObjectType::MalwareAction(obj) => obj.id(),
+            MaecObjectType::MalwareFamily(obj) => obj.id(),
+            MaecObjectType::MalwareInstance(obj) => obj.id(),
+        }
+    }
+
+    fn type_(&self) -> &str {
+        match self {
+            MaecObjectType::Behavior(obj) => obj.type_(),
+            MaecObjectType::Collection(obj) => obj.type_(),
+            MaecObjectType::MalwareAction(obj) => obj.type_(),
+            MaecObjectType::MalwareFamily(obj) => obj.type_(),
+            MaecObjectType::MalwareInstance(obj) => obj.type_(),
+        }
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        match self {
+            MaecObjectType::Behavior(obj) => obj.created(),
+            MaecObjectType::Collection(obj) => obj.created(),
+            MaecObjectType::MalwareAction(obj) => obj.created(),
+            MaecObjectType::MalwareFamily(obj) => obj.created(),
+            MaecObjectType::MalwareInstance(obj) => obj.created(),
+        }
+    }
+}
+
+/// Orders objects by type and ID
+///
+/// This order exists for canonicalization (stable, sortable output) and
+/// carries no semantic meaning. Objects sharing a type and ID are ordered by
+/// their canonical JSON encoding.
+impl Ord for MaecObjectType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.type_(), self.id())
+            .cmp(&(other.type_(), other.id()))
+            .then_with(|| crate::common::canonical_cmp(self, other))
+    }
+}
+
+impl PartialOrd for MaecObjectType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+crate::common::impl_json_methods!(Package);
+
+impl crate::common::CommonPropertiesOwner for Package {
+    fn for_each_common_mut(&mut self, f: &mut dyn FnMut(&mut CommonProperties)) {
+        f(&mut self.common);
+        for object in &mut self.maec_objects {
+            f(object.common_mut());
+        }
+        for relationship in &mut self.relationships {
+            f(&mut relationship.common);
+        }
+    }
+}
+
+impl MaecObject for Package {
+    fn id(&self) -> &str {
+        &self.common.id
+    }
+    fn type_(&self) -> &str {
+        &self.common.r#type
+    }
+    fn created(&self) -> DateTime<Utc> {
+        self.common.created
+    }
+}
+
+impl Default for Package {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for Package objects
+#[derive(Debug, Clone, Default)]
+pub struct PackageBuilder {
+    id: Option<String>,
+    schema_version: Option<String>,
+    maec_objects: Vec<MaecObjectType>,
+    observable_objects: Option<BTreeMap<String, serde_json::Value>>,
+    relationships: Vec<crate::Relationship>,
+    title: Option<String>,
+    description: Option<String>,
+    dedup_on_build: bool,
+    infer_relationships: bool,
+    require_non_empty: bool,
+}
+
+/// Summary of the adjustments made while building a Package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Number of objects dropped because their ID had already been added
+    pub duplicates_removed: usize,
+    /// Number of relationships added by relationship inference
+    pub relationships_inferred: usize,
+}
+
+impl PackageBuilder {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5("package", namespace, name))
+    }
+
+    pub fn schema_version(mut self, version: impl Into<String>) -> Self {
+        self.schema_version = Some(version.into());
+        self
+    }
+
+    /// Sets the package title, stored under `x_maec_title`
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the package description, stored under `x_maec_description`
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    pub fn add_object(mut self, object: impl Into<MaecObjectType>) -> Self {
+        self.maec_objects.push(object.into());
+        self
+    }
+
+    pub fn add_malware_family(mut self, family: crate::MalwareFamily) -> Self {
+        self.maec_objects
+            .push(MaecObjectType::MalwareFamily(family));
+        self
+    }
+
+    pub fn add_malware_instance(mut self, instance: crate::MalwareInstance) -> Self {
+        self.maec_objects
+            .push(MaecObjectType::MalwareInstance(instance));
+        self
+    }
+
+    pub fn add_behavior(mut self, behavior: crate::Behavior) -> Self {
+        self.maec_objects.push(MaecObjectType::Behavior(behavior));
+        self
+    }
+
+    pub fn add_malware_action(mut self, action: crate::MalwareAction) -> Self {
+        self.maec_objects
+            .push(MaecObjectType::MalwareAction(action));
+        self
+    }
+
+    /// Drops objects whose ID was already added when building (default: off)
+    ///
+    /// The first object with a given ID is kept.
+    pub fn dedup_on_build(mut self, dedup: bool) -> Self {
+        self.dedup_on_build = dedup;
+        self
+    }
+
+    /// Rejects packages without any MAEC objects when building (default: off)
+    ///
+    /// An empty package is valid MAEC but usually indicates a mistake upstream.
+    pub fn require_non_empty(mut self, require: bool) -> Self {
+        self.require_non_empty = require;
+        self
+    }
+
+    /// Adds a relationship
+    pub fn add_relationship(mut self, relationship: crate::Relationship) -> Self {
+        self.relationships.push(relationship);
+        self
+    }
+
+    /// Infers relationships from embedded references when building (default: off)
+    ///
+    /// The following edges are added, in this order:
+    /// - `exhibits` from a malware instance to every behavior in the package
+    ///   that one of its capabilities references through `behavior_refs`
+    /// - `variant-of` from a malware instance to every malware family whose
+    ///   name or aliases match the instance's name or aliases (ignoring case)
+    /// - `related-to` from a malware instance to every other malware family
+    ///   sharing a behavior with it, i.e. where a behavior the instance
+    ///   exhibits appears in the family's `common_behavior_refs` or in the
+    ///   `behavior_refs` of its common capabilities
+    ///
+    /// `exhibits` is a custom type outside
+    /// [`RelationshipType`](crate::RelationshipType), since MAEC 5.0 defines no
+    /// relationship between instances and behaviors; the other types are
+    /// MAEC-defined. An edge is never added if a relationship with the same source, type and
+    /// target already exists, so inference is idempotent.
+    pub fn infer_relationships(mut self, infer: bool) -> Self {
+        self.infer_relationships = infer;
+        self
+    }
+
+    /// Builds a preview of the Package without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<Package> {
+        self.clone().build()
     }
 
     pub fn build(self) -> Result<Package> {
+        self.build_with_report().map(|(package, _)| package)
+    }
+
+    /// Builds the Package and reports the adjustments made along the way
+    pub fn build_with_report(mut self) -> Result<(Package, BuildReport)> {
+        if self.require_non_empty && self.maec_objects.is_empty() {
+            return Err(MaecError::ValidationError(
+                "package contains no objects".to_string(),
+            ));
+        }
+
+        let mut report = BuildReport::default();
+        if self.dedup_on_build {
+            let mut seen = HashSet::new();
+            let before = self.maec_objects.len();
+            self.maec_objects
+                .retain(|obj| seen.insert(obj.id().to_string()));
+            report.duplicates_removed = before - self.maec_objects.len();
+        }
+
         let mut common = CommonProperties::new("package", None);
         if let Some(id) = self.id {
             common.id = id;
         }
-        if let Some(version) = self.schema_version {
-            common.schema_version = Some(version);
+        if let Some(version) = self.schema_version {
+            common.schema_version = Some(version);
+        }
+        if let Some(title) = self.title {
+            common
+                .custom_properties
+                .insert(TITLE_PROPERTY.to_string(), serde_json::Value::String(title));
+        }
+        if let Some(desc) = self.description {
+            common.custom_properties.insert(
+                DESCRIPTION_PROPERTY.to_string(),
+                serde_json::Value::String(desc),
+            );
+        }
+
+        if self.infer_relationships {
+            let inferred = inferred_relationships(&self.maec_objects, &self.relationships);
+            report.relationships_inferred = inferred.len();
+            self.relationships.extend(inferred);
+        }
+
+        let package = Package {
+            common,
+            maec_objects: self.maec_objects,
+            observable_objects: self.observable_objects,
+            relationships: self.relationships,
+        };
+
+        package.validate()?;
+        Ok((package, report))
+    }
+}
+
+/// Derives relationships from the embedded references between objects
+///
+/// See [`PackageBuilder::infer_relationships`] for the rules applied.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(objects = objects.len(), relationships = existing.len())
+    )
+)]
+fn inferred_relationships(
+    objects: &[MaecObjectType],
+    existing: &[crate::Relationship],
+) -> Vec<crate::Relationship> {
+    let mut seen: HashSet<(String, String, String)> = existing
+        .iter()
+        .map(|rel| {
+            (
+                rel.source_ref.clone(),
+                rel.relationship_type.clone(),
+                rel.target_ref.clone(),
+            )
+        })
+        .collect();
+    let behavior_ids: HashSet<&str> = objects
+        .iter()
+        .filter(|obj| matches!(obj, MaecObjectType::Behavior(_)))
+        .map(|obj| obj.id())
+        .collect();
+    let families: Vec<(&MaecObjectType, &crate::MalwareFamily)> = objects
+        .iter()
+        .filter_map(|obj| match obj {
+            MaecObjectType::MalwareFamily(family) => Some((obj, family)),
+            _ => None,
+        })
+        .collect();
+
+    let mut inferred = vec![];
+    let mut push = |source: &str, relationship_type: &str, target: &str| {
+        let key = (
+            source.to_string(),
+            relationship_type.to_string(),
+            target.to_string(),
+        );
+        if seen.insert(key) {
+            inferred.push(crate::Relationship::new(source, relationship_type, target));
+        }
+    };
+
+    for object in objects {
+        let MaecObjectType::MalwareInstance(instance) = object else {
+            continue;
+        };
+
+        let exhibited: Vec<&str> = object
+            .embedded_refs()
+            .into_iter()
+            .filter(|(key, r)| *key == "behavior_refs" && behavior_ids.contains(r))
+            .map(|(_, r)| r)
+            .collect();
+        for behavior in &exhibited {
+            push(&instance.common.id, "exhibits", behavior);
+        }
+
+        let instance_names: HashSet<String> = instance
+            .name
+            .iter()
+            .chain(&instance.aliases)
+            .map(|name| name.value.to_lowercase())
+            .collect();
+        for (family_object, family) in &families {
+            let shares_name = std::iter::once(&family.name)
+                .chain(&family.aliases)
+                .any(|name| instance_names.contains(&name.value.to_lowercase()));
+            if shares_name {
+                push(&instance.common.id, "variant-of", &family.common.id);
+                continue;
+            }
+
+            let shares_behavior = family_object
+                .embedded_refs()
+                .into_iter()
+                .any(|(key, r)| key != "common_code_refs" && exhibited.contains(&r));
+            if shares_behavior {
+                push(&instance.common.id, "related-to", &family.common.id);
+            }
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(inferred = inferred.len(), "relationship inference finished");
+    inferred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_new() {
+        let package = Package::new();
+        assert_eq!(package.common.r#type, "package");
+        assert_eq!(package.common.schema_version, Some("5.0".to_string()));
+        assert!(package.common.id.starts_with("package--"));
+    }
+
+    #[test]
+    fn test_package_builder() {
+        let package = Package::builder().schema_version("5.0").build().unwrap();
+        assert_eq!(package.common.r#type, "package");
+        assert_eq!(package.common.schema_version, Some("5.0".to_string()));
+    }
+
+    #[test]
+    fn test_validate_deep_collection_cycle() {
+        let id = |n: u32| format!("collection--00000000-0000-4000-8000-00000000000{}", n);
+        let collection = |n: u32, members: &[u32]| {
+            let mut collection = crate::Collection::new();
+            collection.common.id = id(n);
+            collection.entity_refs = members.iter().map(|&m| id(m)).collect();
+            MaecObjectType::Collection(collection)
+        };
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+
+        let mut package = Package::new()
+            .with_object(collection(1, &[2, 3]))
+            .with_object(collection(2, &[3]))
+            .with_object(collection(3, &[]))
+            .with_object(collection(4, &[1]));
+        if let MaecObjectType::Collection(c) = &mut package.maec_objects[2] {
+            c.entity_refs.push(behavior.common.id.clone());
+        }
+        package.push_object(MaecObjectType::Behavior(behavior));
+        assert!(package.validate_deep().is_ok());
+
+        if let MaecObjectType::Collection(c) = &mut package.maec_objects[2] {
+            c.entity_refs.push(id(4));
+        }
+        match package.validate_deep() {
+            Err(MaecError::ValidationError(msg)) => assert_eq!(
+                msg,
+                format!(
+                    "collection membership forms a cycle: {} -> {} -> {} -> {} -> {}",
+                    id(1),
+                    id(2),
+                    id(3),
+                    id(4),
+                    id(1)
+                )
+            ),
+            other => panic!("expected cycle error, got {:?}", other),
+        }
+
+        let self_loop = Package::new().with_object(collection(5, &[5]));
+        assert!(self_loop.validate_deep().is_err());
+    }
+
+    #[test]
+    fn test_validate_deep_allowing_self_loops() {
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let id = behavior.common.id.clone();
+        let package = Package::new()
+            .with_object(behavior)
+            .with_relationship(crate::Relationship::new(&id, "related-to", &id));
+
+        assert!(package.validate_deep().is_err());
+        assert!(package
+            .validate_deep_allowing_self_loops(&["dependent-on"])
+            .is_err());
+        assert!(package
+            .validate_deep_allowing_self_loops(&["related-to"])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_no_future_timestamps() {
+        let now = Utc::now();
+        let tolerance = chrono::Duration::minutes(5);
+        let mut behavior = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        behavior.timestamp = Some(now + chrono::Duration::minutes(1));
+        let mut instance = crate::MalwareInstance::new(vec!["0".to_string()]);
+        instance.field_data = Some(
+            crate::FieldData::builder()
+                .first_seen(now - chrono::Duration::days(3))
+                .last_seen(now + chrono::Duration::days(1))
+                .build()
+                .unwrap(),
+        );
+        instance.common.modified = now + chrono::Duration::hours(2);
+        let instance_id = instance.common.id.clone();
+
+        let package = Package::new()
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_object(MaecObjectType::MalwareInstance(instance));
+        assert!(package
+            .validate_no_future_timestamps(now + chrono::Duration::days(2), tolerance)
+            .is_empty());
+
+        let errors = package.validate_no_future_timestamps(now, tolerance);
+        assert_eq!(errors.len(), 2);
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert!(messages[0].contains(&format!("'modified' of '{}'", instance_id)));
+        assert!(messages[1].contains("'field_data.last_seen'"));
+    }
+
+    #[test]
+    fn test_ids_by_type() {
+        let first = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let family = crate::MalwareFamily::new("WannaCry");
+        let second = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let relationship =
+            crate::Relationship::new(&family.common.id, "related-to", &first.common.id);
+        let package = Package::new()
+            .with_object(MaecObjectType::Behavior(first.clone()))
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::Behavior(second.clone()))
+            .with_relationship(relationship.clone());
+
+        let ids = package.ids_by_type();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(
+            ids["behavior"],
+            vec![first.common.id.clone(), second.common.id.clone()]
+        );
+        assert_eq!(ids["malware-family"], vec![family.common.id.clone()]);
+
+        assert_eq!(
+            package.all_ids(),
+            vec![
+                first.common.id.as_str(),
+                family.common.id.as_str(),
+                second.common.id.as_str(),
+                relationship.common.id.as_str(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_json_indented() {
+        let package = Package::new().with_object(MaecObjectType::MalwareFamily(
+            crate::MalwareFamily::new("Emotet"),
+        ));
+
+        let four = package.to_json_indented("    ").unwrap();
+        assert!(four.contains("\n    \"maec_objects\": [\n        {"));
+        assert_eq!(
+            package.to_json_indented("  ").unwrap(),
+            package.to_json_pretty().unwrap()
+        );
+        assert_eq!(Package::from_json(&four).unwrap(), package);
+    }
+
+    #[test]
+    fn test_behaviors_by_capability() {
+        let beacon = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let dga = crate::Behavior::new(crate::BehaviorVocab::GenerateC2DomainNames);
+        let reboot = crate::Behavior::new(crate::BehaviorVocab::PersistAfterSystemReboot);
+        let hide = crate::Behavior::new(crate::BehaviorVocab::HideProcesses);
+
+        let mut c2 = crate::Capability::new("command-and-control");
+        c2.behavior_refs = vec![beacon.common.id.clone(), dga.common.id.clone()];
+        let mut refinement = crate::Capability::new("persist-after-system-reboot");
+        refinement.behavior_refs = vec![reboot.common.id.clone()];
+        let persistence = crate::Capability::tree("persistence", vec![refinement]);
+        let family = crate::MalwareFamily::builder()
+            .name("Emotet")
+            .add_capability(c2)
+            .add_capability(persistence)
+            .build()
+            .unwrap();
+
+        let mut package = Package::new().with_object(MaecObjectType::MalwareFamily(family));
+        for behavior in [&beacon, &dga, &reboot, &hide] {
+            package.push_object(MaecObjectType::Behavior(behavior.clone()));
+        }
+
+        let groups: Vec<(&str, Vec<&str>)> = package
+            .behaviors_by_capability()
+            .into_iter()
+            .map(|(capability, behaviors)| {
+                (
+                    capability.name.as_str(),
+                    behaviors.iter().map(|b| b.name.as_ref()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "command-and-control",
+                    vec!["send-beacon", "generate-c2-domain-names"]
+                ),
+                (
+                    "persist-after-system-reboot",
+                    vec!["persist-after-system-reboot"]
+                ),
+                ("uncategorized", vec!["hide-processes"]),
+            ]
+        );
+        assert!(Package::new().behaviors_by_capability().is_empty());
+    }
+
+    #[test]
+    fn test_non_standard_labels() {
+        let family = crate::MalwareFamily::builder()
+            .name("Emotet")
+            .add_label("trojan-horse")
+            .add_label("banker")
+            .build()
+            .unwrap();
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_label("Ransomware")
+            .add_label("worm")
+            .build()
+            .unwrap();
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::MalwareInstance(instance.clone()));
+
+        assert_eq!(
+            package.non_standard_labels(),
+            vec![
+                (family.common.id.clone(), "banker".to_string()),
+                (instance.common.id.clone(), "Ransomware".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relationship_edges() {
+        let action = crate::MalwareAction::new(crate::MalwareActionVocab::CreateFile);
+        let behavior = crate::Behavior::builder()
+            .name(crate::BehaviorVocab::WriteCodeIntoFile)
+            .add_action_ref(&action.common.id)
+            .build()
+            .unwrap();
+        let instance = crate::MalwareInstance::new(vec!["0".to_string()]);
+        let relationship =
+            crate::Relationship::new(&instance.common.id, "related-to", &behavior.common.id);
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareAction(action.clone()))
+            .with_object(MaecObjectType::Behavior(behavior.clone()))
+            .with_object(MaecObjectType::MalwareInstance(instance.clone()))
+            .with_relationship(relationship);
+
+        let edges: Vec<(&str, &str, &str)> = package.relationship_edges().collect();
+        assert_eq!(
+            edges,
+            vec![
+                (
+                    instance.common.id.as_str(),
+                    "related-to",
+                    behavior.common.id.as_str()
+                ),
+                (
+                    behavior.common.id.as_str(),
+                    "action_refs",
+                    action.common.id.as_str()
+                ),
+            ]
+        );
+
+        // Behavior references follow the capability tree in pre-order
+        let ids: Vec<String> = (1..=4)
+            .map(|n| format!("behavior--00000000-0000-4000-8000-00000000000{}", n))
+            .collect();
+        let with_refs = |name: &str, refs: &[&String], children: Vec<crate::Capability>| {
+            let mut capability = crate::Capability::tree(name, children);
+            capability.behavior_refs = refs.iter().map(|r| r.to_string()).collect();
+            capability
+        };
+        let family = crate::MalwareFamily::builder()
+            .name("Emotet")
+            .add_common_behavior_ref(&ids[0])
+            .add_capability(with_refs(
+                "persistence",
+                &[&ids[1]],
+                vec![with_refs("continuous-execution", &[&ids[2]], vec![])],
+            ))
+            .add_capability(with_refs("anti-detection", &[&ids[3]], vec![]))
+            .build()
+            .unwrap();
+        let package = Package::new().with_object(family.clone());
+        let edges: Vec<(&str, &str, &str)> = package.relationship_edges().collect();
+        let id = family.common.id.as_str();
+        assert_eq!(
+            edges,
+            vec![
+                (id, "common_behavior_refs", ids[0].as_str()),
+                (id, "behavior_refs", ids[1].as_str()),
+                (id, "behavior_refs", ids[2].as_str()),
+                (id, "behavior_refs", ids[3].as_str()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_combines_field_data() {
+        let now = Utc::now();
+        let mut family = crate::MalwareFamily::new("WannaCry");
+        family.field_data = Some(crate::FieldData::with_timestamps(now, Some(now)));
+        let mut sighting = family.clone();
+        sighting.field_data = Some(crate::FieldData::with_timestamps(
+            now - chrono::Duration::days(2),
+            None,
+        ));
+        sighting.description = Some("Other feed".to_string());
+
+        let mut package = Package::new().with_object(MaecObjectType::MalwareFamily(family));
+        package.merge(Package::new().with_object(MaecObjectType::MalwareFamily(sighting)));
+
+        let merged = package.malware_families()[0];
+        assert_eq!(package.maec_objects.len(), 1);
+        assert!(merged.description.is_none());
+        let field_data = merged.field_data.as_ref().unwrap();
+        assert_eq!(field_data.first_seen, Some(now - chrono::Duration::days(2)));
+        assert_eq!(field_data.last_seen, Some(now));
+    }
+
+    #[test]
+    fn test_dedup_relationships_sums_counts() {
+        let source = "malware-instance--550e8400-e29b-41d4-a716-446655440001";
+        let target = "malware-family--550e8400-e29b-41d4-a716-446655440002";
+        let mut counted = crate::Relationship::new(source, "variant-of", target);
+        counted.set_count(5);
+        let mut package = Package::new()
+            .with_relationship(crate::Relationship::new(source, "variant-of", target))
+            .with_relationship(crate::Relationship::new(source, "related-to", target))
+            .with_relationship(counted)
+            .with_relationship(crate::Relationship::new(source, "variant-of", target));
+
+        assert_eq!(package.dedup_relationships(), 2);
+        assert_eq!(package.relationships.len(), 2);
+        assert_eq!(package.relationships[0].count(), Some(7));
+        assert_eq!(package.relationships[1].count(), None);
+        assert_eq!(package.dedup_relationships(), 0);
+    }
+
+    #[test]
+    fn test_validate_references() {
+        let action = crate::MalwareAction::new(crate::MalwareActionVocab::CreateFile);
+        let behavior = crate::Behavior::builder()
+            .name(crate::BehaviorVocab::WriteCodeIntoFile)
+            .add_action_ref(&action.common.id)
+            .build()
+            .unwrap();
+        let mut capability = crate::Capability::new("persistence");
+        capability.behavior_refs = vec![behavior.common.id.clone()];
+        let family = crate::MalwareFamily::builder()
+            .name("Emotet")
+            .add_capability(capability)
+            .build()
+            .unwrap();
+        let relationship =
+            crate::Relationship::new(&family.common.id, "related-to", &behavior.common.id);
+        let mut package = Package::new()
+            .with_object(MaecObjectType::MalwareAction(action.clone()))
+            .with_object(MaecObjectType::Behavior(behavior.clone()))
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_relationship(relationship);
+        assert!(package.validate_references().is_ok());
+
+        let dangling = "behavior--550e8400-e29b-41d4-a716-446655440000";
+        if let MaecObjectType::MalwareFamily(family) = &mut package.maec_objects[2] {
+            family.common_behavior_refs.push(dangling.to_string());
+            family.common_behavior_refs.push(action.common.id.clone());
+        }
+        package.relationships[0].target_ref = dangling.to_string();
+
+        let Err(MaecError::InvalidReference(message)) = package.validate_references() else {
+            panic!("dangling references must be reported");
+        };
+        assert_eq!(message.matches("does not resolve").count(), 3);
+        assert!(message.contains(&format!(
+            "'{}' in common_behavior_refs of '{}'",
+            action.common.id, family.common.id
+        )));
+        assert!(message.contains(&format!("'{}' in target_ref", dangling)));
+    }
+
+    #[test]
+    fn test_retain() {
+        let family = crate::MalwareFamily::new("WannaCry");
+        let first = crate::MalwareInstance::new(vec!["0".to_string()]);
+        let second = crate::MalwareInstance::new(vec!["1".to_string()]);
+        let mut package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::MalwareInstance(first.clone()))
+            .with_object(MaecObjectType::MalwareInstance(second.clone()))
+            .with_relationship(second.variant_of(&first).unwrap())
+            .with_relationship(crate::Relationship::new(
+                &first.common.id,
+                "variant-of",
+                &family.common.id,
+            ))
+            .with_relationship(crate::Relationship::new(
+                &first.common.id,
+                "related-to",
+                "malware-instance--550e8400-e29b-41d4-a716-446655440000",
+            ));
+
+        let removed = package.retain(|obj| obj.id() != second.common.id);
+        assert_eq!(removed, (1, 2));
+        assert_eq!(package.maec_objects.len(), 2);
+        assert_eq!(package.relationships.len(), 1);
+        assert_eq!(package.relationships[0].target_ref, family.common.id);
+
+        assert_eq!(package.retain(|_| true), (0, 0));
+
+        // Removed elements are counted, not distinct IDs
+        package
+            .maec_objects
+            .push(MaecObjectType::MalwareInstance(second.clone()));
+        package
+            .maec_objects
+            .push(MaecObjectType::MalwareInstance(second.clone()));
+        assert_eq!(package.retain(|obj| obj.id() != second.common.id), (2, 0));
+    }
+
+    #[test]
+    fn test_resolve_external_refs() {
+        struct Techniques;
+        impl crate::common::ReferenceResolver for Techniques {
+            fn resolve(&self, reference: &crate::ExternalReference) -> Option<serde_json::Value> {
+                (reference.source_name == "mitre-attack")
+                    .then(|| serde_json::json!({"technique": reference.external_id}))
+            }
         }
 
-        let package = Package {
-            common,
-            maec_objects: self.maec_objects,
-            observable_objects: self.observable_objects,
-            relationships: self.relationships,
+        let technique = crate::ExternalReference::attack_technique("T1486", "Data Encrypted");
+        let behavior = crate::Behavior::builder()
+            .name(crate::BehaviorVocab::EncryptFiles)
+            .add_technique_ref(technique.clone())
+            .build()
+            .unwrap();
+        let mut capability = crate::Capability::new("impact");
+        capability.refined_capabilities.push(
+            crate::Capability::builder()
+                .name("data-destruction")
+                .add_reference(technique)
+                .add_reference(crate::ExternalReference::new("internal-wiki"))
+                .build()
+                .unwrap(),
+        );
+        let family = crate::MalwareFamily::builder()
+            .name(crate::Name::new("WannaCry"))
+            .add_capability(capability)
+            .build()
+            .unwrap();
+        let package = Package::new()
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_object(MaecObjectType::MalwareFamily(family));
+
+        assert_eq!(package.external_references().len(), 3);
+        let resolved = package.resolve_external_refs(&Techniques);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(
+            resolved["mitre-attack:T1486"],
+            serde_json::json!({"technique": "T1486"})
+        );
+    }
+
+    #[test]
+    fn test_validate_deep_value_limits() {
+        let mut nested = serde_json::json!(1);
+        for _ in 0..crate::common::DEFAULT_MAX_VALUE_DEPTH {
+            nested = serde_json::json!([nested]);
+        }
+        let mut capability = crate::Capability::new("persistence");
+        capability
+            .refined_capabilities
+            .push(crate::Capability::new("continuous-execution"));
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_capability(capability)
+            .build()
+            .unwrap();
+        let mut package = Package::new().with_object(MaecObjectType::MalwareInstance(instance));
+        package.observable_objects = Some(BTreeMap::from([(
+            "0".to_string(),
+            serde_json::json!({"type": "file"}),
+        )]));
+
+        let set_attributes = |package: &mut Package, value: serde_json::Value| {
+            if let MaecObjectType::MalwareInstance(instance) = &mut package.maec_objects[0] {
+                instance.capabilities[0].refined_capabilities[0].attributes =
+                    Some(BTreeMap::from([("x/y".to_string(), value)]));
+            }
         };
+        set_attributes(&mut package, nested.clone());
+        assert!(package.validate_deep().is_ok());
 
-        package.validate()?;
-        Ok(package)
+        set_attributes(&mut package, serde_json::json!([nested]));
+        match package.validate_deep() {
+            Err(MaecError::ValidationError(msg)) => {
+                assert!(msg.contains(package.maec_objects[0].id()), "{}", msg);
+                assert!(
+                    msg.contains("'/capabilities/0/refined_capabilities/0/attributes/x~1y/0/0"),
+                    "{}",
+                    msg
+                );
+            }
+            other => panic!("expected depth error, got {:?}", other),
+        }
+
+        let mut package = Package::new();
+        package.common.custom_properties.insert(
+            "x_blob".to_string(),
+            serde_json::json!("a".repeat(crate::common::DEFAULT_MAX_VALUE_SIZE)),
+        );
+        assert!(package.validate_deep().is_err());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_dedup_behaviors() {
+        let beacon = |action: &str| {
+            crate::Behavior::builder()
+                .name(crate::BehaviorVocab::SendBeacon)
+                .attributes_from_value(serde_json::json!({"port-number": 443}))
+                .unwrap()
+                .add_action_ref(action)
+                .build()
+                .unwrap()
+        };
+        let first = beacon("malware-action--00000000-0000-4000-8000-000000000001");
+        let second = beacon("malware-action--00000000-0000-4000-8000-000000000002");
+        let mut third = beacon("malware-action--00000000-0000-4000-8000-000000000001");
+        third.attributes = None;
+
+        let mut capability = crate::Capability::new("command-and-control");
+        capability.behavior_refs = vec![second.common.id.clone()];
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_capability(capability)
+            .build()
+            .unwrap();
+        let instance_id = instance.common.id.clone();
+
+        let mut package = Package::new()
+            .with_object(MaecObjectType::Behavior(first.clone()))
+            .with_object(MaecObjectType::MalwareInstance(instance))
+            .with_object(MaecObjectType::Behavior(second.clone()))
+            .with_object(MaecObjectType::Behavior(third.clone()))
+            .with_relationship(crate::Relationship::new(
+                &instance_id,
+                "related-to",
+                &second.common.id,
+            ));
+
+        assert_eq!(package.dedup_behaviors(), 1);
+        let behaviors = package.behaviors();
+        assert_eq!(behaviors.len(), 2);
+        assert_eq!(behaviors[0].common.id, first.common.id);
+        assert_eq!(behaviors[0].action_refs.len(), 2);
+        assert_eq!(behaviors[1].common.id, third.common.id);
+
+        let capability = &package.malware_instances()[0].capabilities[0];
+        assert_eq!(capability.behavior_refs, vec![first.common.id.clone()]);
+        assert_eq!(package.relationships[0].target_ref, first.common.id);
+        assert_eq!(package.dedup_behaviors(), 0);
+    }
 
     #[test]
-    fn test_package_new() {
-        let package = Package::new();
-        assert_eq!(package.common.r#type, "package");
-        assert_eq!(package.common.schema_version, Some("5.0".to_string()));
-        assert!(package.common.id.starts_with("package--"));
+    fn test_dedup_behaviors_collapses_refs() {
+        let first = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let second = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let (first_id, second_id) = (first.common.id.clone(), second.common.id.clone());
+
+        let mut refinement = crate::Capability::new("persistence");
+        refinement.behavior_refs = vec![second_id.clone(), first_id.clone()];
+        let mut capability = crate::Capability::new("command-and-control");
+        capability.behavior_refs = vec![first_id.clone(), second_id.clone()];
+        capability.refined_capabilities = vec![refinement];
+        let family = crate::MalwareFamily::builder()
+            .name("zeus")
+            .add_common_behavior_ref(&first_id)
+            .add_common_behavior_ref(&second_id)
+            .add_capability(capability)
+            .build()
+            .unwrap();
+
+        let mut package = Package::new()
+            .with_object(first)
+            .with_object(second)
+            .with_object(family);
+        assert_eq!(package.dedup_behaviors(), 1);
+
+        let family = package.malware_families()[0];
+        assert_eq!(family.common_behavior_refs, vec![first_id.clone()]);
+        let capability = &family.common_capabilities[0];
+        assert_eq!(capability.behavior_refs, vec![first_id.clone()]);
+        assert_eq!(
+            capability.refined_capabilities[0].behavior_refs,
+            vec![first_id.clone()]
+        );
     }
 
     #[test]
-    fn test_package_builder() {
-        let package = Package::builder().schema_version("5.0").build().unwrap();
-        assert_eq!(package.common.r#type, "package");
-        assert_eq!(package.common.schema_version, Some("5.0".to_string()));
+    fn test_dedup_behaviors_drops_new_self_loops() {
+        let first = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let second = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let (first_id, second_id) = (first.common.id.clone(), second.common.id.clone());
+
+        let mut package = Package::new()
+            .with_object(first)
+            .with_object(second)
+            .with_relationship(crate::Relationship::new(
+                &first_id,
+                "related-to",
+                &second_id,
+            ))
+            .with_relationship(crate::Relationship::new(
+                &second_id,
+                "related-to",
+                &first_id,
+            ))
+            .with_relationship(crate::Relationship::new(
+                &first_id,
+                "dependent-on",
+                &first_id,
+            ));
+        assert_eq!(package.dedup_behaviors(), 1);
+
+        // Only the self-loop that existed before the merge remains
+        assert_eq!(package.relationships.len(), 1);
+        assert_eq!(package.relationships[0].relationship_type, "dependent-on");
+        assert_eq!(package.relationships[0].target_ref, first_id);
+    }
+
+    #[test]
+    fn test_min_schema_version() {
+        let mut family = crate::MalwareFamily::new("WannaCry");
+        family.common.schema_version = Some("4.1".to_string());
+        let mut behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        behavior.common.schema_version = Some("4.0.9".to_string());
+        let mut package = Package::new();
+        assert_eq!(package.min_schema_version(), Some((5, 0, 0)));
+
+        package.push_object(MaecObjectType::MalwareFamily(family));
+        assert_eq!(package.min_schema_version(), Some((4, 1, 0)));
+
+        package.push_object(MaecObjectType::Behavior(behavior));
+        assert_eq!(package.min_schema_version(), Some((4, 0, 9)));
+    }
+
+    #[test]
+    fn test_cards() {
+        let family = crate::MalwareFamily::builder()
+            .name(crate::Name::new("WannaCry"))
+            .add_label("ransomware")
+            .add_label("worm")
+            .add_common_behavior_ref("behavior--550e8400-e29b-41d4-a716-446655440000")
+            .build()
+            .unwrap();
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_object(MaecObjectType::Collection(crate::Collection::new()));
+
+        let cards = package.cards();
+        assert_eq!(
+            cards[0],
+            serde_json::json!({
+                "id": family.common.id,
+                "type": "malware-family",
+                "name": "WannaCry",
+                "label_summary": "ransomware, worm",
+                "ref_count": 1,
+            })
+        );
+        assert_eq!(cards[1]["name"], "encrypt-files");
+        assert_eq!(cards[1]["label_summary"], serde_json::Value::Null);
+        assert_eq!(cards[2]["name"], serde_json::Value::Null);
+        assert_eq!(cards[2]["ref_count"], 0);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let build = || {
+            let family = crate::MalwareFamily::new("WannaCry");
+            let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+            let relationship =
+                crate::Relationship::new(&family.common.id, "related-to", &behavior.common.id);
+            (family, behavior, relationship)
+        };
+
+        let (family, behavior, relationship) = build();
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::Behavior(behavior.clone()))
+            .with_relationship(relationship);
+        let fingerprint = package.fingerprint();
+        assert_eq!(fingerprint.len(), 64);
+
+        // Fresh IDs, timestamps and a different order
+        let (family2, behavior2, relationship2) = build();
+        let reordered = Package::new()
+            .with_object(MaecObjectType::Behavior(behavior2))
+            .with_object(MaecObjectType::MalwareFamily(family2))
+            .with_relationship(relationship2);
+        assert_eq!(reordered.fingerprint(), fingerprint);
+
+        // A relationship pointing the other way is a content change
+        let reversed = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::Behavior(behavior.clone()))
+            .with_relationship(crate::Relationship::new(
+                &behavior.common.id,
+                "related-to",
+                &family.common.id,
+            ));
+        assert_ne!(reversed.fingerprint(), fingerprint);
+
+        let mut renamed = package.clone();
+        if let MaecObjectType::MalwareFamily(family) = &mut renamed.maec_objects[0] {
+            family.name = crate::Name::new("WCry");
+        }
+        assert_ne!(renamed.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_fingerprint_normalizes_nested_content() {
+        let beacon = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let encrypt = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let family = |labels: [&str; 2], refs: [&str; 2]| {
+            let mut capability = crate::Capability::new("command-and-control");
+            capability.behavior_refs = refs.iter().map(|r| r.to_string()).collect();
+            crate::MalwareFamily::builder()
+                .name("Emotet")
+                .labels(labels.iter().map(|l| l.to_string()).collect())
+                .add_capability(capability)
+                .add_capability(crate::Capability::new("persistence"))
+                .build()
+                .unwrap()
+        };
+        let (b, e) = (beacon.common.id.as_str(), encrypt.common.id.as_str());
+        let package = |family: crate::MalwareFamily| {
+            Package::new()
+                .with_object(beacon.clone())
+                .with_object(encrypt.clone())
+                .with_object(family)
+        };
+
+        let fingerprint = package(family(["bot", "worm"], [b, e])).fingerprint();
+        let mut reordered = family(["worm", "bot"], [e, b]);
+        reordered.common_capabilities.reverse();
+        assert_eq!(package(reordered).fingerprint(), fingerprint);
+
+        // IDs quoted in free text are content, not references, so they
+        // change the fingerprint when the behaviors are reissued
+        let mention = |beacon: &crate::Behavior| {
+            let id = beacon.common.id.as_str();
+            let mut family = family(["bot", "worm"], [id, e]);
+            family.description = Some(id.to_string());
+            family.common_behavior_refs = vec![id.to_string()];
+            Package::new()
+                .with_object(beacon.clone())
+                .with_object(encrypt.clone())
+                .with_object(family)
+                .fingerprint()
+        };
+        let reissued = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        assert_ne!(mention(&beacon), mention(&reissued));
+
+        let mut reissued_package =
+            package(family(["bot", "worm"], [reissued.common.id.as_str(), e]));
+        reissued_package.maec_objects[0] = reissued.clone().into();
+        assert_eq!(reissued_package.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_from_reader_with_position() {
+        let json = Package::new().to_json_pretty().unwrap();
+        assert!(Package::from_reader_with_position(json.as_bytes()).is_ok());
+
+        let truncated = &json[..json.len() - 2];
+        let (err, position) = Package::from_reader_with_position(truncated.as_bytes()).unwrap_err();
+        assert!(matches!(err, MaecError::SerializationError(_)));
+        let (line, _) = position.unwrap();
+        assert_eq!(line, json.lines().count() - 1);
+
+        let invalid = json.replace("\"5.0\"", "\"4.1\"");
+        let (err, position) = Package::from_reader_with_position(invalid.as_bytes()).unwrap_err();
+        assert!(matches!(err, MaecError::ValidationError(_)));
+        assert_eq!(position, None);
+    }
+
+    #[test]
+    fn test_external_refs() {
+        let instance = crate::MalwareInstance::new(vec!["0".to_string()]);
+        let instance_id = instance.common.id.clone();
+        let mut package = Package::new().with_object(MaecObjectType::MalwareInstance(instance));
+        package.observable_objects = Some(BTreeMap::from([(
+            "0".to_string(),
+            serde_json::json!({"type": "file", "parent_directory_ref": "1"}),
+        )]));
+        assert!(!package.is_self_contained());
+        assert_eq!(package.external_refs(), vec!["1"]);
+
+        let family = "malware-family--550e8400-e29b-41d4-a716-446655440000";
+        let identity = "identity--b0a7c4f2-3f1e-4d8b-9a9e-1f2d3c4b5a69";
+        package.common.created_by_ref = Some(identity.to_string());
+        package.push_relationship(crate::Relationship::new(&instance_id, "variant-of", family));
+        assert_eq!(package.external_refs(), vec!["1", identity, family]);
+
+        package
+            .observable_objects
+            .as_mut()
+            .unwrap()
+            .insert("1".to_string(), serde_json::json!({"type": "directory"}));
+        package.common.created_by_ref = None;
+        package.relationships.clear();
+        assert!(package.is_self_contained());
+    }
+
+    #[test]
+    fn test_validate_relationship_semantics() {
+        let family = crate::MalwareFamily::new("WannaCry");
+        let instance = crate::MalwareInstance::new(vec!["0".to_string()]);
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let (f, i, b) = (
+            family.common.id.clone(),
+            instance.common.id.clone(),
+            behavior.common.id.clone(),
+        );
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family))
+            .with_object(MaecObjectType::MalwareInstance(instance))
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_relationship(crate::Relationship::new(&i, "variant-of", &f))
+            .with_relationship(crate::Relationship::new(&b, "dropped-by", &i))
+            .with_relationship(crate::Relationship::new(&i, "exhibits", &b))
+            .with_relationship(crate::Relationship::new(&f, "related-to", &b))
+            .with_relationship(crate::Relationship::new(
+                &i,
+                "variant-of",
+                "malware-instance--550e8400-e29b-41d4-a716-446655440000",
+            ));
+
+        let errors = package.validate_relationship_semantics();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("dropped-by"));
+
+        let counts = package.relationship_type_counts();
+        assert_eq!(counts.get("variant-of"), Some(&2));
+        assert_eq!(counts.len(), 4);
+    }
+
+    #[test]
+    fn test_object_from_value() {
+        let family = crate::MalwareFamily::new("WannaCry");
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family))
+            .with_object(MaecObjectType::Behavior(behavior.clone()));
+        let mut value = serde_json::to_value(&package).unwrap();
+
+        let found = object_from_value(&value, &behavior.common.id).unwrap();
+        assert_eq!(found, Some(MaecObjectType::Behavior(behavior)));
+        assert_eq!(
+            object_from_value(&value, "behavior--missing").unwrap(),
+            None
+        );
+
+        value["maec_objects"][0]["name"] = serde_json::json!(42);
+        let family_id = package.maec_objects[0].id();
+        assert!(object_from_value(&value, family_id).is_err());
+
+        value["maec_objects"] = serde_json::json!({});
+        assert!(matches!(
+            object_from_value(&value, family_id),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_observable_refs() {
+        let family_id = "malware-family--550e8400-e29b-41d4-a716-446655440000";
+        let action = crate::MalwareAction::builder()
+            .name(crate::MalwareActionVocab::CreateFile)
+            .add_input_object_ref("0")
+            .add_output_object_ref("1")
+            .add_output_object_ref(family_id)
+            .build()
+            .unwrap();
+        let mut package = Package::new().with_object(MaecObjectType::MalwareAction(action));
+        package.observable_objects = Some(BTreeMap::from([
+            ("0".to_string(), serde_json::json!({"type": "directory"})),
+            ("1".to_string(), serde_json::json!({"type": "file"})),
+        ]));
+
+        match package.validate_observable_refs() {
+            Err(MaecError::ValidationError(msg)) => {
+                assert!(msg.contains(family_id), "{}", msg);
+                assert!(msg.contains("looks like a MAEC object id"), "{}", msg);
+                assert!(!msg.contains("'1'"), "{}", msg);
+            }
+            other => panic!("expected unresolved ref error, got {:?}", other),
+        }
+
+        if let MaecObjectType::MalwareAction(action) = &mut package.maec_objects[0] {
+            action.output_object_refs.pop();
+        }
+        assert!(package.validate_observable_refs().is_ok());
+        assert!(package.validate_deep().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_require_non_empty() {
+        assert!(Package::builder().build().is_ok());
+        match Package::builder().require_non_empty(true).build() {
+            Err(MaecError::ValidationError(msg)) => assert_eq!(msg, "package contains no objects"),
+            other => panic!("expected empty package error, got {:?}", other),
+        }
+        assert!(Package::builder()
+            .require_non_empty(true)
+            .add_malware_family(crate::MalwareFamily::new("WannaCry"))
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_fluent_mutation() {
+        let family = crate::MalwareFamily::new("WannaCry");
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let relationship =
+            crate::Relationship::new(&family.common.id, "related-to", &behavior.common.id);
+
+        let mut package = Package::new().with_object(MaecObjectType::MalwareFamily(family));
+        package
+            .push_object(MaecObjectType::Behavior(behavior))
+            .push_relationship(relationship.clone());
+        let package = package.with_relationship(relationship);
+
+        assert_eq!(package.maec_objects.len(), 2);
+        assert_eq!(package.relationships.len(), 2);
+        assert_eq!(package.behaviors().len(), 1);
+        assert!(package.validate().is_ok());
+    }
+
+    #[test]
+    fn test_object_ordering() {
+        let family = crate::MalwareFamily::builder()
+            .id("malware-family--00000000-0000-4000-8000-000000000002")
+            .name(crate::Name::new("WannaCry"))
+            .build()
+            .unwrap();
+        let mut renamed = family.clone();
+        renamed.name = crate::Name::new("Wcry");
+        let behavior = crate::Behavior::builder()
+            .id("behavior--00000000-0000-4000-8000-000000000009")
+            .name(crate::BehaviorVocab::EncryptFiles)
+            .build()
+            .unwrap();
+
+        let mut objects = vec![
+            MaecObjectType::MalwareFamily(renamed.clone()),
+            MaecObjectType::MalwareFamily(family.clone()),
+            MaecObjectType::Behavior(behavior.clone()),
+        ];
+        objects.sort();
+        assert_eq!(
+            objects,
+            vec![
+                MaecObjectType::Behavior(behavior),
+                MaecObjectType::MalwareFamily(family),
+                MaecObjectType::MalwareFamily(renamed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project() {
+        let family = crate::MalwareFamily::new("WannaCry");
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::EncryptFiles);
+        let other = crate::MalwareFamily::new("NotPetya");
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family.clone()))
+            .with_object(MaecObjectType::Behavior(behavior.clone()))
+            .with_object(MaecObjectType::MalwareFamily(other.clone()))
+            .with_relationship(crate::Relationship::new(
+                &family.common.id,
+                "related-to",
+                &behavior.common.id,
+            ))
+            .with_relationship(crate::Relationship::new(
+                &family.common.id,
+                "related-to",
+                &other.common.id,
+            ));
+
+        let projected = package.project(&[ObjectKind::MalwareFamily, ObjectKind::MalwareInstance]);
+        assert_eq!(projected.common.id, package.common.id);
+        assert_eq!(projected.malware_families().len(), 2);
+        assert!(projected.behaviors().is_empty());
+        assert_eq!(projected.relationships.len(), 1);
+        assert_eq!(projected.relationships[0].target_ref, other.common.id);
+        assert_eq!(package.maec_objects.len(), 3);
+    }
+
+    #[test]
+    fn test_cluster_instances() {
+        let instance = |id: &str, capabilities: &[&str]| {
+            let mut builder = crate::MalwareInstance::builder()
+                .id(format!("malware-instance--{}", id))
+                .add_instance_object_ref("0");
+            for capability in capabilities {
+                builder = builder.add_capability(crate::Capability::new(*capability));
+            }
+            MaecObjectType::MalwareInstance(builder.build().unwrap())
+        };
+        let package = Package::new()
+            .with_object(instance(
+                "00000000-0000-4000-8000-000000000001",
+                &["persistence", "command-and-control"],
+            ))
+            .with_object(instance(
+                "00000000-0000-4000-8000-000000000002",
+                &["persistence"],
+            ))
+            .with_object(instance(
+                "00000000-0000-4000-8000-000000000003",
+                &["anti-detection"],
+            ))
+            .with_object(instance(
+                "00000000-0000-4000-8000-000000000004",
+                &["command-and-control"],
+            ));
+
+        let suffixes = |threshold: f64| -> Vec<Vec<String>> {
+            package
+                .cluster_instances(threshold)
+                .iter()
+                .map(|cluster| {
+                    cluster
+                        .iter()
+                        .map(|id| id[id.len() - 1..].to_string())
+                        .collect()
+                })
+                .collect()
+        };
+        assert_eq!(suffixes(0.4), vec![vec!["1", "2", "4"], vec!["3"]]);
+
+        // Similarities equal to the threshold do not link instances
+        assert_eq!(suffixes(0.5).len(), 4);
+        assert_eq!(suffixes(0.0), vec![vec!["1", "2", "4"], vec!["3"]]);
+
+        assert_eq!(package.cluster_instances(0.9).len(), 4);
+        assert!(Package::new().cluster_instances(0.5).is_empty());
+    }
+
+    #[test]
+    fn test_package_merge_records_provenance() {
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_label("ransomware")
+            .build()
+            .unwrap();
+        let shared = crate::MalwareFamily::new("WannaCry");
+
+        let mut ours = Package::builder()
+            .add_malware_family(shared.clone())
+            .build()
+            .unwrap();
+        let mut theirs = Package::builder()
+            .add_malware_family(shared)
+            .add_malware_instance(instance)
+            .build()
+            .unwrap();
+        theirs.common.created_by_ref =
+            Some("identity--b0a7c4f2-3f1e-4d8b-9a9e-1f2d3c4b5a69".to_string());
+        ours.merge(theirs);
+
+        assert_eq!(ours.maec_objects.len(), 2);
+        let merged = ours.malware_instances()[0];
+        assert_eq!(
+            merged.field_source("labels"),
+            Some("identity--b0a7c4f2-3f1e-4d8b-9a9e-1f2d3c4b5a69")
+        );
+        assert_eq!(merged.field_source("description"), None);
+    }
+
+    #[test]
+    fn test_validate_deep_observable_namespaces() {
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .build()
+            .unwrap();
+        let instance_id = instance.common.id.clone();
+
+        let mut package = Package::builder()
+            .add_malware_instance(instance)
+            .build()
+            .unwrap();
+        package.observable_objects = Some(BTreeMap::from([
+            (
+                "0".to_string(),
+                serde_json::json!({"type": "file", "parent_directory_ref": "1"}),
+            ),
+            ("1".to_string(), serde_json::json!({"type": "directory"})),
+            ("2".to_string(), serde_json::json!({"type": "mutex"})),
+        ]));
+
+        let warnings = package.validate_deep().unwrap();
+        assert_eq!(
+            warnings,
+            vec!["observable '2' is not referenced by any object"]
+        );
+
+        package
+            .observable_objects
+            .as_mut()
+            .unwrap()
+            .insert(instance_id, serde_json::json!({"type": "file"}));
+        assert!(matches!(
+            package.validate_deep(),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_package_builder_dedup_report() {
+        let family = crate::MalwareFamily::new("Emotet");
+        let (package, report) = Package::builder()
+            .dedup_on_build(true)
+            .add_malware_family(family.clone())
+            .add_malware_family(family.clone())
+            .add_malware_family(crate::MalwareFamily::new("Geodo"))
+            .build_with_report()
+            .unwrap();
+
+        assert_eq!(report.duplicates_removed, 1);
+        assert_eq!(package.maec_objects.len(), 2);
+
+        let package = Package::builder()
+            .add_malware_family(family.clone())
+            .add_malware_family(family)
+            .build()
+            .unwrap();
+        assert_eq!(package.maec_objects.len(), 2);
+    }
+
+    #[test]
+    fn test_package_timeline() {
+        let start = Utc::now();
+        let later = start + chrono::Duration::seconds(5);
+
+        let behavior = crate::Behavior::builder()
+            .name(crate::vocab_large::Behavior::SendBeacon)
+            .timestamp(later)
+            .build()
+            .unwrap();
+        let action = crate::MalwareAction::builder()
+            .name(crate::vocab_large::MalwareAction::CreateMutex)
+            .timestamp(start)
+            .build()
+            .unwrap();
+        let untimed = crate::MalwareAction::new(crate::vocab_large::MalwareAction::CreateFile);
+
+        let package = Package::builder()
+            .add_behavior(behavior)
+            .add_malware_action(action)
+            .add_malware_action(untimed)
+            .build()
+            .unwrap();
+
+        let timeline = package.timeline();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].kind, TimelineEventKind::Action);
+        assert_eq!(timeline[0].label, "create-mutex");
+        assert_eq!(timeline[1].kind, TimelineEventKind::Behavior);
+        assert_eq!(timeline[1].label, "send-beacon");
+    }
+
+    #[test]
+    fn test_package_json_convenience() {
+        let package = Package::builder()
+            .add_malware_family(crate::MalwareFamily::new("Emotet"))
+            .build()
+            .unwrap();
+
+        let json = package.to_json().unwrap();
+        assert_eq!(Package::from_json(&json).unwrap(), package);
+        assert!(package.to_json_pretty().unwrap().contains('\n'));
+
+        let invalid = json.replace("\"5.0\"", "\"4.1\"");
+        assert!(matches!(
+            Package::from_json(&invalid),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_json_with_strips_custom_prefixes() {
+        let mut family = crate::MalwareFamily::new("Emotet");
+        family.common.custom_properties.insert(
+            "x_internal_note".to_string(),
+            serde_json::json!("do not share"),
+        );
+        family
+            .common
+            .custom_properties
+            .insert("x_vendor_score".to_string(), serde_json::json!(7));
+
+        let mut package = Package::builder()
+            .add_malware_family(family)
+            .build()
+            .unwrap();
+        package
+            .common
+            .custom_properties
+            .insert("x_internal_ticket".to_string(), serde_json::json!("IR-1"));
+
+        let options = crate::SerializeOptions::strip_custom_prefixes(["x_internal_"]);
+        let json = package.to_json_with(&options).unwrap();
+        assert!(!json.contains("x_internal_"));
+        assert!(json.contains("x_vendor_score"));
+        assert_eq!(package.common.custom_properties.len(), 1);
+
+        // Custom properties need not follow the `x_` convention
+        package
+            .common
+            .custom_properties
+            .insert("internal_case".to_string(), serde_json::json!("IR-2"));
+        let options = crate::SerializeOptions::strip_custom_prefixes(["internal_"]);
+        let json = package.to_json_with(&options).unwrap();
+        assert!(!json.contains("internal_case"));
+        assert!(json.contains("x_internal_ticket"));
+    }
+
+    #[test]
+    fn test_to_json_with_keeps_standard_fields_and_observables() {
+        let mut family = crate::MalwareFamily::new("Emotet");
+        family.description = Some("banking trojan".to_string());
+        family
+            .common
+            .custom_properties
+            .insert("x_vendor_score".to_string(), serde_json::json!(7));
+
+        let observable = serde_json::json!({
+            "type": "file",
+            "id": "file--550e8400-e29b-41d4-a716-446655440000",
+            "name": "payload.exe",
+            "x_vendor_score": 3,
+        });
+        let mut package = Package::builder()
+            .add_malware_family(family)
+            .build()
+            .unwrap();
+        package.observable_objects = Some(
+            [("0".to_string(), observable.clone())]
+                .into_iter()
+                .collect(),
+        );
+
+        // Prefixes that match standard fields must not strip them
+        let options =
+            crate::SerializeOptions::strip_custom_prefixes(["x_vendor_", "desc", "name", "type"]);
+        let json: serde_json::Value =
+            serde_json::from_str(&package.to_json_with(&options).unwrap()).unwrap();
+        assert_eq!(json["observable_objects"]["0"], observable);
+        let family = &json["maec_objects"][0];
+        assert_eq!(family["description"], "banking trojan");
+        assert_eq!(family["type"], "malware-family");
+        assert!(family.get("x_vendor_score").is_none());
+    }
+
+    #[test]
+    fn test_relationship_path_and_reachability() {
+        let family = "malware-family--00000000-0000-4000-8000-000000000001";
+        let variant = "malware-family--00000000-0000-4000-8000-000000000002";
+        let instance = "malware-instance--00000000-0000-4000-8000-000000000003";
+        let unrelated = "malware-instance--00000000-0000-4000-8000-000000000004";
+
+        let mut package = Package::new();
+        package.relationships = vec![
+            crate::Relationship::new(variant, "variant-of", family),
+            crate::Relationship::new(family, "related-to", instance),
+            crate::Relationship::new(variant, "dropped", instance),
+        ];
+
+        assert_eq!(
+            package.path(variant, instance, None),
+            Some(vec![variant.to_string(), instance.to_string()])
+        );
+        assert_eq!(
+            package.path(variant, instance, Some(&["variant-of", "related-to"])),
+            Some(vec![
+                variant.to_string(),
+                family.to_string(),
+                instance.to_string()
+            ])
+        );
+        assert_eq!(package.path(instance, variant, None), None);
+        assert_eq!(package.path(variant, unrelated, None), None);
+
+        let reachable = package.reachable_from(variant);
+        assert_eq!(reachable.len(), 2);
+        assert!(reachable.contains(family) && reachable.contains(instance));
+        assert!(package.reachable_from(instance).is_empty());
+    }
+
+    #[test]
+    fn test_validate_observables() {
+        let mut package = Package::new();
+        assert!(package.validate_observables().is_ok());
+
+        package.observable_objects = Some(BTreeMap::from([
+            (
+                "0".to_string(),
+                serde_json::json!({"type": "file", "name": "a.exe", "parent_directory_ref": "1"}),
+            ),
+            (
+                "1".to_string(),
+                serde_json::json!({"type": "directory", "path": "C:\\Temp"}),
+            ),
+        ]));
+        assert!(package.validate_observables().is_ok());
+
+        let observables = package.observable_objects.as_mut().unwrap();
+        observables.insert(
+            "2".to_string(),
+            serde_json::json!({"type": "process", "child_refs": ["0", "9"]}),
+        );
+        observables.insert("3".to_string(), serde_json::json!({"type": "not-a-coo"}));
+        observables.insert("4".to_string(), serde_json::json!("file"));
+
+        match package.validate_observables() {
+            Err(MaecError::ValidationError(msg)) => {
+                assert!(
+                    msg.contains("'2' references unknown observable '9'"),
+                    "{}",
+                    msg
+                );
+                assert!(
+                    msg.contains("'3' has unknown STIX observable type"),
+                    "{}",
+                    msg
+                );
+                assert!(msg.contains("'4' is not a JSON object"), "{}", msg);
+                assert!(!msg.contains("'0'"), "{}", msg);
+            }
+            other => panic!("expected observable errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_package_title_and_description() {
+        let package = Package::builder()
+            .title("Emotet loader campaign")
+            .description("Samples collected in March")
+            .build()
+            .unwrap();
+
+        assert_eq!(package.title(), Some("Emotet loader campaign"));
+        assert_eq!(package.description(), Some("Samples collected in March"));
+
+        let json = package.to_json().unwrap();
+        assert!(json.contains("\"x_maec_title\":\"Emotet loader campaign\""));
+        assert_eq!(Package::from_json(&json).unwrap().title(), package.title());
+        assert_eq!(Package::new().title(), None);
+    }
+
+    #[test]
+    fn test_maec_object_type_dispatches_on_type() {
+        // A bare malware action also matches Collection's shape structurally
+        let action = crate::MalwareAction::new(crate::MalwareActionVocab::CreateFile);
+        let json = serde_json::to_string(&MaecObjectType::MalwareAction(action.clone())).unwrap();
+        let parsed: MaecObjectType = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, MaecObjectType::MalwareAction(action));
+
+        let family = crate::MalwareFamily::new("Emotet");
+        let mut value = serde_json::to_value(&family).unwrap();
+        value["type"] = serde_json::json!("malware-instance");
+        assert!(serde_json::from_value::<MaecObjectType>(value.clone()).is_err());
+
+        value["type"] = serde_json::json!("indicator");
+        let err = serde_json::from_value::<MaecObjectType>(value.clone()).unwrap_err();
+        assert!(err.to_string().contains("unknown MAEC object type"));
+
+        value.as_object_mut().unwrap().remove("type");
+        let err = serde_json::from_value::<MaecObjectType>(value).unwrap_err();
+        assert!(err.to_string().contains("missing its 'type'"));
+    }
+
+    #[test]
+    fn test_non_spec_behaviors() {
+        let custom = crate::Behavior::new(crate::OpenVocab::Custom("mine-crypto".to_string()));
+        let package = Package::builder()
+            .add_behavior(crate::Behavior::new(crate::BehaviorVocab::SendBeacon))
+            .add_behavior(custom.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(package.non_spec_behaviors(), vec![&custom]);
+    }
+
+    #[test]
+    fn test_infer_relationships() {
+        let beacon = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let mut capability = crate::Capability::new("command-and-control");
+        capability.behavior_refs.push(beacon.common.id.clone());
+
+        let instance = crate::MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .name(crate::Name::new("emotet"))
+            .add_capability(capability)
+            .build()
+            .unwrap();
+        let named = crate::MalwareFamily::new("Emotet");
+        let mut sharing = crate::MalwareFamily::new("Heodo");
+        sharing.common_behavior_refs.push(beacon.common.id.clone());
+        let unrelated = crate::MalwareFamily::new("WannaCry");
+
+        let builder = Package::builder()
+            .add_behavior(beacon.clone())
+            .add_malware_instance(instance.clone())
+            .add_malware_family(named.clone())
+            .add_malware_family(sharing.clone())
+            .add_malware_family(unrelated)
+            .add_relationship(crate::Relationship::new(
+                &instance.common.id,
+                "exhibits",
+                &beacon.common.id,
+            ))
+            .infer_relationships(true);
+
+        let (package, report) = builder.build_with_report().unwrap();
+        assert_eq!(report.relationships_inferred, 2);
+        let triples: Vec<(&str, &str, &str)> = package
+            .relationships
+            .iter()
+            .map(|r| {
+                (
+                    r.source_ref.as_str(),
+                    r.relationship_type.as_str(),
+                    r.target_ref.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            triples,
+            vec![
+                (
+                    instance.common.id.as_str(),
+                    "exhibits",
+                    beacon.common.id.as_str()
+                ),
+                (
+                    instance.common.id.as_str(),
+                    "variant-of",
+                    named.common.id.as_str()
+                ),
+                (
+                    instance.common.id.as_str(),
+                    "related-to",
+                    sharing.common.id.as_str()
+                ),
+            ]
+        );
+
+        // Only the instance-to-behavior edge uses a custom type
+        let custom: Vec<&str> = package
+            .relationships
+            .iter()
+            .filter(|r| r.known_type().is_none())
+            .map(|r| r.relationship_type.as_str())
+            .collect();
+        assert_eq!(custom, vec!["exhibits"]);
+        assert!(package.validate_relationship_semantics().is_empty());
+
+        // Inferring again over the result adds nothing
+        let mut again = Package::builder().infer_relationships(true);
+        for object in package.maec_objects.clone() {
+            again = again.add_object(object);
+        }
+        for relationship in package.relationships.clone() {
+            again = again.add_relationship(relationship);
+        }
+        let (again, report) = again.build_with_report().unwrap();
+        assert_eq!(report.relationships_inferred, 0);
+        assert_eq!(again.maec_objects, package.maec_objects);
+        assert_eq!(again.relationships, package.relationships);
+
+        let rebuilt = Package::builder()
+            .add_malware_instance(instance)
+            .add_malware_family(named)
+            .build()
+            .unwrap();
+        assert!(rebuilt.relationships.is_empty());
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml_roundtrip_minimal_package() {
+        let package = Package::builder()
+            .title("Minimal")
+            .schema_version("5.0")
+            .build()
+            .unwrap();
+
+        let xml = package.to_xml().unwrap();
+        assert!(xml.starts_with("<package>"));
+        assert_eq!(Package::from_xml(&xml).unwrap(), package);
+    }
+
+    #[cfg(not(feature = "xml"))]
+    #[test]
+    fn test_xml_unsupported_without_feature() {
+        assert!(matches!(
+            Package::new().to_xml(),
+            Err(MaecError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            Package::from_xml("<package/>"),
+            Err(MaecError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_downgrade_to() {
+        let mut instance = crate::MalwareInstance::new(vec!["file--1".to_string()]);
+        instance.common.schema_version = Some("5.1".to_string());
+        let package = Package::new().with_object(MaecObjectType::MalwareInstance(instance));
+
+        let (downgraded, warnings) = package.downgrade_to("5.0").unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(downgraded.common.schema_version.as_deref(), Some("5.0"));
+        assert_eq!(
+            downgraded.maec_objects[0]
+                .common()
+                .schema_version
+                .as_deref(),
+            Some("5.0")
+        );
+
+        assert!(matches!(
+            package.downgrade_to("4.1"),
+            Err(MaecError::ValidationError(_))
+        ));
+        assert_eq!(
+            crate::SchemaVersion::from_name("5.0").unwrap().as_ref(),
+            "5.0"
+        );
+    }
+
+    #[test]
+    fn test_find_relationship_cycles() {
+        let id = |n: u8| format!("behavior--550e8400-e29b-41d4-a716-44665544000{}", n);
+        let package = Package::new()
+            .with_relationship(crate::Relationship::new(id(2), "derived-from", id(1)))
+            .with_relationship(crate::Relationship::new(id(1), "derived-from", id(2)))
+            .with_relationship(crate::Relationship::new(id(2), "related-to", id(3)))
+            .with_relationship(crate::Relationship::new(id(3), "related-to", id(1)))
+            .with_relationship(crate::Relationship::new(id(4), "related-to", id(4)))
+            .with_relationship(crate::Relationship::new(id(5), "related-to", id(1)));
+
+        assert_eq!(
+            package.find_relationship_cycles(),
+            vec![vec![id(1), id(2)], vec![id(1), id(2), id(3)], vec![id(4)],]
+        );
+        assert!(Package::new().find_relationship_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_find_relationship_cycles_complete_graph() {
+        let id = |n: usize| format!("behavior--{}", n);
+        let mut package = Package::new();
+        for source in 0..4 {
+            for target in (0..4).filter(|&target| target != source) {
+                package.push_relationship(crate::Relationship::new(
+                    id(source),
+                    "related-to",
+                    id(target),
+                ));
+            }
+        }
+        // 6 two-cycles, 4 triangles in 2 directions, 6 orderings of all four
+        let cycles = package.find_relationship_cycles();
+        assert_eq!(cycles.len(), 20);
+        assert!(cycles
+            .iter()
+            .all(|cycle| cycle.iter().min() == cycle.first()));
+    }
+
+    #[test]
+    fn test_find_relationship_cycles_adversarial_shapes() {
+        // A long chain closed into a single cycle must not overflow the stack
+        const LENGTH: usize = 100_000;
+        let id = |n: usize| format!("behavior--{:06}", n);
+        let mut chain = Package::new();
+        for n in 0..LENGTH {
+            chain.push_relationship(crate::Relationship::new(
+                id(n),
+                "related-to",
+                id((n + 1) % LENGTH),
+            ));
+        }
+        let cycles = chain.find_relationship_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), LENGTH);
+        assert_eq!(cycles[0][0], id(0));
+
+        // A wide layered DAG has 8^30 paths but no cycles
+        let (layers, width) = (30, 8);
+        let mut dag = Package::new();
+        for layer in 0..layers - 1 {
+            for source in 0..width {
+                for target in 0..width {
+                    dag.push_relationship(crate::Relationship::new(
+                        id(layer * width + source),
+                        "related-to",
+                        id((layer + 1) * width + target),
+                    ));
+                }
+            }
+        }
+        assert!(dag.find_relationship_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_objects_json_roundtrip() {
+        let family = crate::MalwareFamily::new("Emotet");
+        let behavior = crate::Behavior::new(crate::BehaviorVocab::SendBeacon);
+        let relationship = crate::Relationship::new(
+            behavior.common.id.clone(),
+            "related-to",
+            family.common.id.clone(),
+        );
+        let package = Package::new()
+            .with_object(MaecObjectType::MalwareFamily(family))
+            .with_object(MaecObjectType::Behavior(behavior))
+            .with_relationship(relationship);
+
+        let json = package.objects_to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 3);
+        assert_eq!(value[2]["type"], "relationship");
+
+        let parsed = Package::from_objects_json(&json).unwrap();
+        assert_ne!(parsed.common.id, package.common.id);
+        assert_eq!(parsed.maec_objects, package.maec_objects);
+        assert_eq!(parsed.relationships, package.relationships);
+
+        assert!(Package::from_objects_json(r#"{"type": "package"}"#).is_err());
+        assert!(Package::from_objects_json(r#"[{"type": "behavior", "id": "bad"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_source_document() {
+        let mut package = Package::new();
+        assert!(package.source_document().is_none());
+
+        let original = b"{\"report\": \"\xff raw\"}".to_vec();
+        package.set_source_document(&original, "application/json");
+        let parsed = Package::from_json(&package.to_json().unwrap()).unwrap();
+        assert_eq!(
+            parsed.source_document(),
+            Some((original, "application/json".to_string()))
+        );
+
+        package.common.custom_properties.insert(
+            SOURCE_DOCUMENT_PROPERTY.to_string(),
+            serde_json::json!({"media_type": "text/plain", "data": "not base64!"}),
+        );
+        assert!(package.source_document().is_none());
     }
 }
@@ -1,126 +1,462 @@
-//! MAEC Relationship object
-
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-
-use crate::common::MaecObject;
-use crate::error::{MaecError, Result};
-
-/// MAEC Relationship
-///
-/// Connects two MAEC objects, expressing how they are related.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
-pub struct Relationship {
-    /// Common MAEC properties
-    #[serde(flatten)]
-    pub common: crate::common::CommonProperties,
-
-    /// ID of the source object
-    pub source_ref: String,
-
-    /// ID of the target object
-    pub target_ref: String,
-
-    /// Type of relationship (e.g., "derived-from", "variant-of")
-    pub relationship_type: String,
-
-    /// Textual description
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-}
-
-impl Relationship {
-    pub fn builder() -> RelationshipBuilder {
-        RelationshipBuilder::default()
-    }
-
-    pub fn new(
-        source_ref: impl Into<String>,
-        relationship_type: impl Into<String>,
-        target_ref: impl Into<String>,
-    ) -> Self {
-        Self {
-            common: crate::common::CommonProperties::new("relationship", None),
-            source_ref: source_ref.into(),
-            target_ref: target_ref.into(),
-            relationship_type: relationship_type.into(),
-            description: None,
-        }
-    }
-}
-
-impl MaecObject for Relationship {
-    fn id(&self) -> &str {
-        &self.common.id
-    }
-
-    fn type_(&self) -> &str {
-        &self.common.r#type
-    }
-
-    fn created(&self) -> DateTime<Utc> {
-        self.common.created
-    }
-}
-
-#[derive(Debug, Default)]
-pub struct RelationshipBuilder {
-    id: Option<String>,
-    source_ref: Option<String>,
-    target_ref: Option<String>,
-    relationship_type: Option<String>,
-    description: Option<String>,
-}
-
-impl RelationshipBuilder {
-    pub fn id(mut self, id: impl Into<String>) -> Self {
-        self.id = Some(id.into());
-        self
-    }
-
-    pub fn source_ref(mut self, ref_id: impl Into<String>) -> Self {
-        self.source_ref = Some(ref_id.into());
-        self
-    }
-
-    pub fn target_ref(mut self, ref_id: impl Into<String>) -> Self {
-        self.target_ref = Some(ref_id.into());
-        self
-    }
-
-    pub fn relationship_type(mut self, rel_type: impl Into<String>) -> Self {
-        self.relationship_type = Some(rel_type.into());
-        self
-    }
-
-    pub fn description(mut self, desc: impl Into<String>) -> Self {
-        self.description = Some(desc.into());
-        self
-    }
-
-    pub fn build(self) -> Result<Relationship> {
-        let source_ref = self
-            .source_ref
-            .ok_or(MaecError::MissingField("source_ref"))?;
-        let target_ref = self
-            .target_ref
-            .ok_or(MaecError::MissingField("target_ref"))?;
-        let relationship_type = self
-            .relationship_type
-            .ok_or(MaecError::MissingField("relationship_type"))?;
-
-        let mut common = crate::common::CommonProperties::new("relationship", None);
-        if let Some(id) = self.id {
-            common.id = id;
-        }
-
-        Ok(Relationship {
-            common,
-            source_ref,
-            target_ref,
-            relationship_type,
-            description: self.description,
-        })
-    }
-}
+//! MAEC Relationship object
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::common::MaecObject;
+use crate::error::{MaecError, Result};
+
+/// Custom property holding the number of observations of a relationship
+const COUNT_PROPERTY: &str = "x_maec_count";
+
+/// MAEC Relationship
+///
+/// Connects two MAEC objects, expressing how they are related.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct Relationship {
+    /// Common MAEC properties
+    #[serde(flatten)]
+    pub common: crate::common::CommonProperties,
+
+    /// ID of the source object
+    #[serde(alias = "sourceRef")]
+    pub source_ref: String,
+
+    /// ID of the target object
+    #[serde(alias = "targetRef")]
+    pub target_ref: String,
+
+    /// Type of relationship (e.g., "derived-from", "variant-of")
+    ///
+    /// When parsed, the type is lowercased and underscores become hyphens, so
+    /// `Derived_From` reads as `derived-from`.
+    #[serde(
+        deserialize_with = "deserialize_relationship_type",
+        alias = "relationshipType"
+    )]
+    pub relationship_type: String,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Normalizes the casing and separators of a relationship type
+///
+/// # Examples
+///
+/// ```
+/// use maec::objects::relationship::normalize_relationship_type;
+///
+/// assert_eq!(normalize_relationship_type("Derived_From"), "derived-from");
+/// assert_eq!(normalize_relationship_type("x-Acme_Loads"), "x-acme-loads");
+/// ```
+pub fn normalize_relationship_type(rel_type: &str) -> String {
+    rel_type.trim().to_lowercase().replace('_', "-")
+}
+
+fn deserialize_relationship_type<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|rel_type| normalize_relationship_type(&rel_type))
+}
+
+impl Relationship {
+    pub fn builder() -> RelationshipBuilder {
+        RelationshipBuilder::default()
+    }
+
+    pub fn new(
+        source_ref: impl Into<String>,
+        relationship_type: impl Into<String>,
+        target_ref: impl Into<String>,
+    ) -> Self {
+        Self {
+            common: crate::common::CommonProperties::new("relationship", None),
+            source_ref: source_ref.into(),
+            target_ref: target_ref.into(),
+            relationship_type: relationship_type.into(),
+            description: None,
+        }
+    }
+
+    /// Returns how often the relationship was observed, if recorded
+    ///
+    /// The count is stored under the `x_maec_count` custom property.
+    pub fn count(&self) -> Option<u64> {
+        self.common
+            .custom_properties
+            .get(COUNT_PROPERTY)
+            .and_then(|value| value.as_u64())
+    }
+
+    /// Records how often the relationship was observed
+    pub fn set_count(&mut self, count: u64) {
+        self.common
+            .custom_properties
+            .insert(COUNT_PROPERTY.to_string(), count.into());
+    }
+
+    /// Records one more observation of the relationship
+    ///
+    /// A relationship without a count stands for a single observation, so its
+    /// count becomes 2.
+    pub fn increment_count(&mut self) {
+        self.set_count(self.count().unwrap_or(1).saturating_add(1));
+    }
+
+    /// Returns the relationship type if it is defined by MAEC 5.0
+    ///
+    /// The type is [normalized](normalize_relationship_type) first, so
+    /// `"Variant_Of"` is recognized as `variant-of`.
+    pub fn known_type(&self) -> Option<crate::RelationshipType> {
+        crate::RelationshipType::from_name(&normalize_relationship_type(&self.relationship_type))
+    }
+
+    /// Validates the Relationship structure
+    ///
+    /// Self-referential relationships (`source_ref == target_ref`) are rejected;
+    /// use [`Relationship::validate_allowing_self_loops`] to permit them for
+    /// specific relationship types.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_allowing_self_loops(&[])
+    }
+
+    /// Validates the Relationship, permitting self-references for the given types
+    pub fn validate_allowing_self_loops(&self, allowed_types: &[&str]) -> Result<()> {
+        if self.common.r#type != "relationship" {
+            return Err(MaecError::ValidationError(format!(
+                "type must be 'relationship', got '{}'",
+                self.common.r#type
+            )));
+        }
+
+        if !crate::common::is_valid_maec_id(&self.common.id) {
+            return Err(MaecError::InvalidId(self.common.id.clone()));
+        }
+        self.common.validate_timestamps()?;
+
+        if self.source_ref == self.target_ref
+            && !allowed_types.contains(&self.relationship_type.as_str())
+        {
+            return Err(MaecError::ValidationError(format!(
+                "relationship {} is a self-loop on '{}' ('{}' does not allow self-reference)",
+                self.common.id, self.source_ref, self.relationship_type
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Applies a mutation to the relationship and records it as a new version
+    ///
+    /// The closure receives mutable access to the relationship; once it returns,
+    /// the `modified` timestamp is bumped so that edits are always versioned.
+    pub fn update(&mut self, f: impl FnOnce(&mut Self)) {
+        f(self);
+        self.common.new_version();
+    }
+}
+
+crate::common::impl_json_methods!(Relationship);
+
+/// Orders relationships by source, type, target and ID
+///
+/// This order exists for canonicalization (stable, sortable output) and
+/// carries no semantic meaning. Relationships agreeing on all four keys are
+/// ordered by their canonical JSON encoding.
+impl Ord for Relationship {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (
+            &self.source_ref,
+            &self.relationship_type,
+            &self.target_ref,
+            &self.common.id,
+        )
+            .cmp(&(
+                &other.source_ref,
+                &other.relationship_type,
+                &other.target_ref,
+                &other.common.id,
+            ))
+            .then_with(|| crate::common::canonical_cmp(self, other))
+    }
+}
+
+impl PartialOrd for Relationship {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl MaecObject for Relationship {
+    fn id(&self) -> &str {
+        &self.common.id
+    }
+
+    fn type_(&self) -> &str {
+        &self.common.r#type
+    }
+
+    fn created(&self) -> DateTime<Utc> {
+        self.common.created
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RelationshipBuilder {
+    id: Option<String>,
+    source_ref: Option<String>,
+    target_ref: Option<String>,
+    relationship_type: Option<String>,
+    description: Option<String>,
+    count: Option<u64>,
+    validate_known_type: bool,
+    allowed_self_loops: Vec<String>,
+}
+
+impl RelationshipBuilder {
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets a deterministic UUIDv5 ID derived from `name` in `namespace`
+    ///
+    /// See [`generate_maec_id_v5`](crate::generate_maec_id_v5).
+    pub fn deterministic_id(self, namespace: &uuid::Uuid, name: &str) -> Self {
+        self.id(crate::generate_maec_id_v5("relationship", namespace, name))
+    }
+
+    pub fn source_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.source_ref = Some(ref_id.into());
+        self
+    }
+
+    pub fn target_ref(mut self, ref_id: impl Into<String>) -> Self {
+        self.target_ref = Some(ref_id.into());
+        self
+    }
+
+    /// Sets the relationship type, stored in its
+    /// [normalized](normalize_relationship_type) form by `build`
+    pub fn relationship_type(mut self, rel_type: impl Into<String>) -> Self {
+        self.relationship_type = Some(rel_type.into());
+        self
+    }
+
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
+    /// Sets the number of observations, stored under `x_maec_count`
+    pub fn count(mut self, count: u64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Rejects relationship types not defined by MAEC when building (default: off)
+    ///
+    /// With this on, `build` returns `MaecError::ValidationError` unless the
+    /// type is a [`RelationshipType`](crate::RelationshipType). Leave it off to
+    /// allow custom relationship types.
+    pub fn validate_known_type(mut self, validate: bool) -> Self {
+        self.validate_known_type = validate;
+        self
+    }
+
+    /// Permits self-referential relationships of the given types when building
+    ///
+    /// By default `build` rejects relationships whose `source_ref` equals
+    /// their `target_ref`; see [`Relationship::validate_allowing_self_loops`].
+    pub fn allow_self_loops(mut self, allowed_types: &[&str]) -> Self {
+        self.allowed_self_loops = allowed_types.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Builds a preview of the Relationship without consuming the builder
+    ///
+    /// Unless an ID was set, every call generates a fresh ID.
+    pub fn peek(&self) -> Result<Relationship> {
+        self.clone().build()
+    }
+
+    pub fn build(self) -> Result<Relationship> {
+        let source_ref = self
+            .source_ref
+            .ok_or(MaecError::MissingField("source_ref"))?;
+        let target_ref = self
+            .target_ref
+            .ok_or(MaecError::MissingField("target_ref"))?;
+        let relationship_type = self
+            .relationship_type
+            .map(|rel_type| normalize_relationship_type(&rel_type))
+            .ok_or(MaecError::MissingField("relationship_type"))?;
+
+        let mut common = crate::common::CommonProperties::new("relationship", None);
+        if let Some(id) = self.id {
+            common.id = id;
+        }
+
+        let mut relationship = Relationship {
+            common,
+            source_ref,
+            target_ref,
+            relationship_type,
+            description: self.description,
+        };
+        if let Some(count) = self.count {
+            relationship.set_count(count);
+        }
+
+        let allowed_types: Vec<&str> = self.allowed_self_loops.iter().map(String::as_str).collect();
+        relationship.validate_allowing_self_loops(&allowed_types)?;
+        if self.validate_known_type && relationship.known_type().is_none() {
+            return Err(MaecError::ValidationError(format!(
+                "unknown relationship type '{}'",
+                relationship.relationship_type
+            )));
+        }
+        Ok(relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAMILY: &str = "malware-family--550e8400-e29b-41d4-a716-446655440000";
+
+    #[test]
+    fn test_typed_relationship_type() {
+        let relationship = Relationship::builder()
+            .source_ref("malware-instance--550e8400-e29b-41d4-a716-446655440001")
+            .target_ref(FAMILY)
+            .relationship_type(crate::RelationshipType::VariantOf)
+            .build()
+            .unwrap();
+        assert_eq!(relationship.relationship_type, "variant-of");
+    }
+
+    #[test]
+    fn test_relationship_rejects_self_loop() {
+        let result = Relationship::builder()
+            .source_ref(FAMILY)
+            .target_ref(FAMILY)
+            .relationship_type("derived-from")
+            .build();
+        match result {
+            Err(MaecError::ValidationError(msg)) => assert!(msg.contains(FAMILY)),
+            other => panic!("expected self-loop error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_relationship_self_loop_allowlist() {
+        let relationship = Relationship::new(FAMILY, "related-to", FAMILY);
+        assert!(relationship.validate().is_err());
+        assert!(relationship
+            .validate_allowing_self_loops(&["related-to"])
+            .is_ok());
+
+        let builder = Relationship::builder()
+            .source_ref(FAMILY)
+            .target_ref(FAMILY)
+            .relationship_type("related-to");
+        assert!(builder.clone().build().is_err());
+        assert!(builder
+            .clone()
+            .allow_self_loops(&["variant-of"])
+            .build()
+            .is_err());
+        assert!(builder.allow_self_loops(&["related-to"]).build().is_ok());
+    }
+
+    #[test]
+    fn test_relationship_ordering() {
+        let mut relationships = [
+            Relationship::new(FAMILY, "variant-of", FAMILY),
+            Relationship::new(FAMILY, "related-to", FAMILY),
+        ];
+        relationships.sort();
+        assert_eq!(relationships[0].relationship_type, "related-to");
+        assert!(relationships[0] < relationships[1]);
+    }
+
+    #[test]
+    fn test_relationship_type_normalized_on_parse() {
+        for rel_type in ["Derived-From", "derived_from", "DERIVED_FROM"] {
+            let json = serde_json::json!({
+                "type": "relationship",
+                "id": "relationship--550e8400-e29b-41d4-a716-446655440001",
+                "created": "2024-01-01T00:00:00Z",
+                "modified": "2024-01-01T00:00:00Z",
+                "source_ref": FAMILY,
+                "target_ref": "malware-family--550e8400-e29b-41d4-a716-446655440002",
+                "relationship_type": rel_type,
+            });
+            let relationship: Relationship = serde_json::from_value(json).unwrap();
+            assert_eq!(relationship.relationship_type, "derived-from");
+            assert_eq!(
+                relationship.known_type(),
+                Some(crate::RelationshipType::DerivedFrom)
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_known_type() {
+        let builder = |rel_type: &str| {
+            Relationship::builder()
+                .source_ref("malware-instance--550e8400-e29b-41d4-a716-446655440001")
+                .target_ref(FAMILY)
+                .relationship_type(rel_type)
+        };
+
+        assert!(builder("variant-of")
+            .validate_known_type(true)
+            .build()
+            .is_ok());
+        assert!(builder("inspired-by").build().is_ok());
+        for rel_type in ["Variant-Of", "variant_of", " VARIANT_of"] {
+            let relationship = builder(rel_type).validate_known_type(true).build().unwrap();
+            assert_eq!(relationship.relationship_type, "variant-of");
+            assert_eq!(
+                relationship.known_type(),
+                Some(crate::RelationshipType::VariantOf)
+            );
+        }
+        assert!(matches!(
+            builder("inspired-by").validate_known_type(true).build(),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_observation_count() {
+        let mut relationship = Relationship::builder()
+            .source_ref("malware-instance--550e8400-e29b-41d4-a716-446655440001")
+            .target_ref(FAMILY)
+            .relationship_type("variant-of")
+            .build()
+            .unwrap();
+        assert_eq!(relationship.count(), None);
+        relationship.increment_count();
+        assert_eq!(relationship.count(), Some(2));
+
+        let counted = Relationship::builder()
+            .source_ref("malware-instance--550e8400-e29b-41d4-a716-446655440001")
+            .target_ref(FAMILY)
+            .relationship_type("variant-of")
+            .count(7)
+            .build()
+            .unwrap();
+        assert_eq!(counted.count(), Some(7));
+        assert!(counted.to_json().unwrap().contains("\"x_maec_count\":7"));
+    }
+}
@@ -1,17 +1,19 @@
 //! Supporting types for MAEC objects
 //!
 //! This module contains common supporting types used across multiple MAEC objects,
-//! such as Name and FieldData.
+//! such as Name, FieldData and StaticFeatures.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::common::ExternalReference;
+use crate::vocab::{AnalysisConclusionType, AnalysisType};
 
 /// Captures the name of a malware instance, family, or alias
 ///
 /// Includes the actual name value along with optional source and confidence information.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub struct Name {
     /// The actual name value
@@ -36,6 +38,64 @@ impl Name {
         }
     }
 
+    /// Creates a Name, rejecting empty or whitespace-only values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Name;
+    ///
+    /// assert!(Name::try_new("WannaCry").is_ok());
+    /// assert!(Name::try_new("   ").is_err());
+    /// ```
+    pub fn try_new(value: impl Into<String>) -> crate::error::Result<Self> {
+        let name = Self::new(value);
+        name.validate()?;
+        Ok(name)
+    }
+
+    /// Validates that the name value is not empty or whitespace-only
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if self.value.trim().is_empty() {
+            return Err(crate::error::MaecError::ValidationError(
+                "name value must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the name value in normalized form, for comparing names
+    ///
+    /// Leading and trailing whitespace is removed, inner runs of whitespace
+    /// are collapsed into a single space and the value is lowercased.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::Name;
+    ///
+    /// assert_eq!(Name::new("  Wanna   Cry ").normalized(), "wanna cry");
+    /// ```
+    pub fn normalized(&self) -> String {
+        self.value
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Returns whether the name value equals `value`
+    ///
+    /// With `normalized`, both sides are compared in their
+    /// [normalized](Self::normalized) form; otherwise the comparison is exact.
+    pub fn matches(&self, value: &str, normalized: bool) -> bool {
+        if normalized {
+            self.normalized() == Name::new(value).normalized()
+        } else {
+            self.value == value
+        }
+    }
+
     /// Creates a Name with a source
     pub fn with_source(value: impl Into<String>, source: ExternalReference) -> Self {
         Self {
@@ -75,19 +135,19 @@ impl From<&str> for Name {
 ///
 /// Captures temporal information and delivery vectors.
 /// At least one field must be present.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub struct FieldData {
     /// Vectors used to distribute/deploy the malware
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "deliveryVectors")]
     pub delivery_vectors: Option<Vec<String>>,
 
     /// When the malware was first observed (ISO 8601 format)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "firstSeen")]
     pub first_seen: Option<DateTime<Utc>>,
 
     /// When the malware was last observed (ISO 8601 format)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "lastSeen")]
     pub last_seen: Option<DateTime<Utc>>,
 }
 
@@ -114,10 +174,33 @@ impl FieldData {
             last_seen,
         }
     }
+
+    /// Combines the observations of `other` into this field data
+    ///
+    /// Keeps the earlier `first_seen` and the later `last_seen`, and appends
+    /// the delivery vectors of `other` not already present.
+    pub fn merge(&mut self, other: &FieldData) {
+        self.first_seen = match (self.first_seen, other.first_seen) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_seen = match (self.last_seen, other.last_seen) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(vectors) = &other.delivery_vectors {
+            let merged = self.delivery_vectors.get_or_insert_with(Vec::new);
+            for vector in vectors {
+                if !merged.contains(vector) {
+                    merged.push(vector.clone());
+                }
+            }
+        }
+    }
 }
 
 /// Builder for FieldData
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct FieldDataBuilder {
     delivery_vectors: Option<Vec<String>>,
     first_seen: Option<DateTime<Utc>>,
@@ -137,16 +220,21 @@ impl FieldDataBuilder {
         self
     }
 
-    pub fn first_seen(mut self, timestamp: DateTime<Utc>) -> Self {
-        self.first_seen = Some(timestamp);
+    pub fn first_seen(mut self, timestamp: impl Into<DateTime<Utc>>) -> Self {
+        self.first_seen = Some(timestamp.into());
         self
     }
 
-    pub fn last_seen(mut self, timestamp: DateTime<Utc>) -> Self {
-        self.last_seen = Some(timestamp);
+    pub fn last_seen(mut self, timestamp: impl Into<DateTime<Utc>>) -> Self {
+        self.last_seen = Some(timestamp.into());
         self
     }
 
+    /// Builds a preview of the FieldData without consuming the builder
+    pub fn peek(&self) -> crate::error::Result<FieldData> {
+        self.clone().build()
+    }
+
     pub fn build(self) -> crate::error::Result<FieldData> {
         // Validate that at least one field is present
         if self.delivery_vectors.is_none() && self.first_seen.is_none() && self.last_seen.is_none()
@@ -165,6 +253,169 @@ impl FieldDataBuilder {
     }
 }
 
+/// Metadata describing an analysis performed on a malware instance
+///
+/// Captures who or what performed the analysis, when it ran, and its conclusion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct AnalysisMetadata {
+    /// Whether the analysis was automated (e.g., a sandbox run)
+    #[serde(alias = "isAutomated")]
+    pub is_automated: bool,
+
+    /// Type of analysis performed
+    #[serde(alias = "analysisType")]
+    pub analysis_type: AnalysisType,
+
+    /// When the analysis started
+    #[serde(skip_serializing_if = "Option::is_none", alias = "startTime")]
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// When the analysis ended
+    #[serde(skip_serializing_if = "Option::is_none", alias = "endTime")]
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// When the analysis was last updated
+    #[serde(skip_serializing_if = "Option::is_none", alias = "lastUpdateTime")]
+    pub last_update_time: Option<DateTime<Utc>>,
+
+    /// Confidence in the analysis results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<String>,
+
+    /// Names of the analysts who performed the analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub analysts: Vec<String>,
+
+    /// Free-form comments about the analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+
+    /// References to the tools used (STIX software observable IDs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "toolRefs")]
+    pub tool_refs: Vec<String>,
+
+    /// Properties of the analysis environment
+    #[serde(skip_serializing_if = "Option::is_none", alias = "analysisEnvironment")]
+    pub analysis_environment: Option<BTreeMap<String, serde_json::Value>>,
+
+    /// Textual description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Conclusion reached by the analysis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conclusion: Option<AnalysisConclusionType>,
+
+    /// External references related to the analysis
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ExternalReference>,
+}
+
+impl AnalysisMetadata {
+    /// Creates AnalysisMetadata with just the required fields
+    pub fn new(is_automated: bool, analysis_type: AnalysisType) -> Self {
+        Self {
+            is_automated,
+            analysis_type,
+            start_time: None,
+            end_time: None,
+            last_update_time: None,
+            confidence: None,
+            analysts: vec![],
+            comments: vec![],
+            tool_refs: vec![],
+            analysis_environment: None,
+            description: None,
+            conclusion: None,
+            references: vec![],
+        }
+    }
+}
+
+/// Static features of a malware instance
+///
+/// Captures properties obtained without executing the instance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct StaticFeatures {
+    /// Strings extracted from the instance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strings: Vec<StringFeature>,
+}
+
+impl StaticFeatures {
+    /// Returns whether no static features are recorded
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// A string extracted from a malware instance
+///
+/// The value may have been decoded; `encoding` records the encoding it was
+/// found in (e.g., "utf-16le" or "base64").
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct StringFeature {
+    /// The extracted string
+    pub value: String,
+
+    /// Encoding the string was found in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+
+    /// English translation of the string
+    #[serde(skip_serializing_if = "Option::is_none", alias = "englishTranslation")]
+    pub english_translation: Option<String>,
+
+    /// Languages the string is written in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+}
+
+impl StringFeature {
+    /// Creates a StringFeature with just a value
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            encoding: None,
+            english_translation: None,
+            languages: vec![],
+        }
+    }
+
+    /// Sets the encoding the string was found in
+    pub fn with_encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    /// Sets the English translation
+    pub fn with_translation(mut self, translation: impl Into<String>) -> Self {
+        self.english_translation = Some(translation.into());
+        self
+    }
+
+    /// Adds a language the string is written in
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+}
+
+impl From<String> for StringFeature {
+    fn from(value: String) -> Self {
+        StringFeature::new(value)
+    }
+}
+
+impl From<&str> for StringFeature {
+    fn from(value: &str) -> Self {
+        StringFeature::new(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,6 +434,49 @@ mod tests {
         assert_eq!(name.value, "Emotet");
     }
 
+    #[test]
+    fn test_name_rejects_blank_values() {
+        assert!(Name::try_new("").is_err());
+        assert!(Name::try_new(" \t").is_err());
+        assert!(Name::new("").validate().is_err());
+        assert_eq!(Name::try_new("Emotet").unwrap().value, "Emotet");
+    }
+
+    #[test]
+    fn test_field_data_merge() {
+        let day = |d: u32| {
+            DateTime::parse_from_rfc3339(&format!("2017-05-{:02}T00:00:00Z", d))
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let mut field_data = FieldData::builder()
+            .first_seen(day(12))
+            .last_seen(day(14))
+            .add_delivery_vector("email-attachment")
+            .build()
+            .unwrap();
+        let other = FieldData::builder()
+            .first_seen(day(10))
+            .last_seen(day(13))
+            .add_delivery_vector("phishing")
+            .add_delivery_vector("email-attachment")
+            .build()
+            .unwrap();
+
+        field_data.merge(&other);
+        assert_eq!(field_data.first_seen, Some(day(10)));
+        assert_eq!(field_data.last_seen, Some(day(14)));
+        assert_eq!(
+            field_data.delivery_vectors,
+            Some(vec!["email-attachment".to_string(), "phishing".to_string()])
+        );
+
+        let mut sparse = FieldData::with_delivery_vectors(vec![]);
+        sparse.merge(&FieldData::with_timestamps(day(1), None));
+        assert_eq!(sparse.first_seen, Some(day(1)));
+        assert_eq!(sparse.last_seen, None);
+    }
+
     #[test]
     fn test_field_data_builder() {
         let field_data = FieldData::builder()
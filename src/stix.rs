@@ -0,0 +1,715 @@
+//! STIX 2.1 interoperability helpers
+//!
+//! This module converts MAEC data into native STIX 2.1 objects (and back) so that
+//! MAEC analysis results can be exchanged with STIX-only tooling. It is enabled by
+//! the `stix-integration` feature.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+
+use crate::common::{generate_maec_id, is_valid_maec_id};
+use crate::error::{MaecError, Result};
+use crate::vocab::AnalysisConclusionType;
+use crate::{Collection, FieldData, MalwareInstance, Name, Package};
+
+/// STIX specification version emitted by this module
+pub const STIX_SPEC_VERSION: &str = "2.1";
+
+/// Object types under which a MAEC package may be embedded in a STIX bundle
+const EMBEDDED_PACKAGE_TYPES: &[&str] = &["x-maec-package", "package"];
+
+/// MAEC object types that have no STIX counterpart emitted by this module
+const MAEC_ONLY_TYPES: &[&str] = &[
+    "behavior",
+    "malware-action",
+    "malware-family",
+    "malware-instance",
+    "package",
+    "relationship",
+];
+
+/// Formats a timestamp using the STIX millisecond-precision representation
+fn stix_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Maps a MAEC analysis conclusion onto the STIX `malware-result-ov` vocabulary
+fn stix_result(conclusion: AnalysisConclusionType) -> &'static str {
+    match conclusion {
+        AnalysisConclusionType::Benign => "benign",
+        AnalysisConclusionType::Malicious => "malicious",
+        AnalysisConclusionType::Suspicious => "suspicious",
+        AnalysisConclusionType::Indeterminate => "unknown",
+    }
+}
+
+/// Looks up an observable by its key in `observable_objects` or by its `id`
+fn find_observable<'a>(
+    observables: Option<&'a BTreeMap<String, Value>>,
+    reference: &str,
+) -> Option<&'a Value> {
+    let observables = observables?;
+    observables.get(reference).or_else(|| {
+        observables
+            .values()
+            .find(|observable| observable.get("id").and_then(Value::as_str) == Some(reference))
+    })
+}
+
+/// Returns the STIX identifier a MAEC observable reference stands for
+///
+/// References that already are STIX identifiers are returned as they are;
+/// observable keys resolve to the `id` of the referenced observable.
+fn stix_ref<'a>(
+    observables: Option<&'a BTreeMap<String, Value>>,
+    reference: &'a str,
+) -> Option<&'a str> {
+    if is_valid_maec_id(reference) {
+        return Some(reference);
+    }
+    find_observable(observables, reference)?
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|id| is_valid_maec_id(id))
+}
+
+/// Exports the analysis metadata of a MalwareInstance as STIX `malware-analysis` SDOs
+///
+/// One `malware-analysis` object is emitted per entry in `analysis_metadata`.
+/// `sample_ref` is the first of the instance's `instance_object_refs` that is
+/// a STIX identifier; observable keys such as `"0"` cannot be resolved
+/// without the package, so `sample_ref` is omitted for them. Likewise the
+/// names of the analysis tools are unknown, so `product` is `"unknown"`. Use
+/// [`package_analysis_to_stix`] to resolve both against the package's
+/// observables.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{AnalysisConclusionType, AnalysisMetadata, AnalysisType, MalwareInstance};
+///
+/// let mut metadata = AnalysisMetadata::new(true, AnalysisType::Dynamic);
+/// metadata.conclusion = Some(AnalysisConclusionType::Malicious);
+///
+/// let instance = MalwareInstance::builder()
+///     .add_instance_object_ref("file--12345678-1234-1234-1234-123456789abc")
+///     .add_analysis_metadata(metadata)
+///     .build()
+///     .unwrap();
+///
+/// let analyses = maec::stix::analysis_to_stix(&instance);
+/// assert_eq!(analyses.len(), 1);
+/// assert_eq!(analyses[0]["type"], "malware-analysis");
+/// assert_eq!(analyses[0]["result"], "malicious");
+/// ```
+pub fn analysis_to_stix(instance: &MalwareInstance) -> Vec<Value> {
+    analyses_to_stix(instance, None)
+}
+
+/// Exports the analyses of a MalwareInstance, resolving references in `package`
+///
+/// Works like [`analysis_to_stix`], except that observable keys are resolved
+/// against the package's `observable_objects`: `sample_ref` becomes the STIX
+/// `id` of the first instance object that has one, and `product` is the
+/// `name` of the first software observable among the analysis `tool_refs`.
+/// `product` falls back to `"unknown"` only when no tool name is found.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{AnalysisMetadata, AnalysisType, MalwareInstance, Package};
+///
+/// let mut metadata = AnalysisMetadata::new(true, AnalysisType::Dynamic);
+/// metadata.tool_refs.push("1".to_string());
+/// let instance = MalwareInstance::builder()
+///     .add_instance_object_ref("0")
+///     .add_analysis_metadata(metadata)
+///     .build()
+///     .unwrap();
+///
+/// let mut package = Package::new();
+/// let observables = package.observable_objects.get_or_insert_with(Default::default);
+/// observables.insert(
+///     "0".to_string(),
+///     serde_json::json!({"type": "file", "id": "file--12345678-1234-1234-1234-123456789abc"}),
+/// );
+/// observables.insert(
+///     "1".to_string(),
+///     serde_json::json!({"type": "software", "name": "cuckoo"}),
+/// );
+///
+/// let analyses = maec::stix::package_analysis_to_stix(&package, &instance);
+/// assert_eq!(analyses[0]["sample_ref"], "file--12345678-1234-1234-1234-123456789abc");
+/// assert_eq!(analyses[0]["product"], "cuckoo");
+/// ```
+pub fn package_analysis_to_stix(package: &Package, instance: &MalwareInstance) -> Vec<Value> {
+    analyses_to_stix(instance, package.observable_objects.as_ref())
+}
+
+fn analyses_to_stix(
+    instance: &MalwareInstance,
+    observables: Option<&BTreeMap<String, Value>>,
+) -> Vec<Value> {
+    let sample_ref = instance
+        .instance_object_refs
+        .iter()
+        .find_map(|reference| stix_ref(observables, reference));
+
+    instance
+        .analysis_metadata
+        .iter()
+        .map(|metadata| {
+            let mut sdo = Map::new();
+            sdo.insert("type".to_string(), json!("malware-analysis"));
+            sdo.insert("spec_version".to_string(), json!(STIX_SPEC_VERSION));
+            sdo.insert(
+                "id".to_string(),
+                json!(generate_maec_id("malware-analysis")),
+            );
+            sdo.insert(
+                "created".to_string(),
+                json!(stix_timestamp(instance.common.created)),
+            );
+            sdo.insert(
+                "modified".to_string(),
+                json!(stix_timestamp(instance.common.modified)),
+            );
+            let product = metadata
+                .tool_refs
+                .iter()
+                .filter_map(|reference| find_observable(observables, reference))
+                .filter(|tool| tool.get("type").and_then(Value::as_str) == Some("software"))
+                .find_map(|tool| tool.get("name").and_then(Value::as_str))
+                .unwrap_or("unknown");
+            sdo.insert("product".to_string(), json!(product));
+
+            if let Some(start) = metadata.start_time {
+                sdo.insert("analysis_started".to_string(), json!(stix_timestamp(start)));
+            }
+            if let Some(end) = metadata.end_time {
+                sdo.insert("analysis_ended".to_string(), json!(stix_timestamp(end)));
+            }
+            if let Some(conclusion) = metadata.conclusion {
+                sdo.insert("result".to_string(), json!(stix_result(conclusion)));
+            }
+            if let Some(sample_ref) = sample_ref {
+                sdo.insert("sample_ref".to_string(), json!(sample_ref));
+            }
+
+            Value::Object(sdo)
+        })
+        .collect()
+}
+
+/// Exports a Collection as a STIX `grouping` SDO
+///
+/// The grouping reuses the UUID of the collection, so exporting the same
+/// collection twice yields the same `id`. Its `object_refs` are the
+/// collection's `entity_refs` in order, mapped to the STIX identifiers this
+/// module emits: references to collections become references to their
+/// groupings, STIX identifiers such as observables are kept, and references
+/// to MAEC-only objects and observable keys are dropped. `context` is
+/// `"unspecified"` since MAEC records no grouping context. `name` and
+/// `description` are copied when present.
+///
+/// STIX requires a grouping to reference at least one object, so `None` is
+/// returned when no entity reference maps to a STIX identifier, e.g. when the
+/// collection only holds MAEC-only objects.
+///
+/// # Examples
+///
+/// ```
+/// use maec::Collection;
+///
+/// let mut collection = Collection::new();
+/// collection.name = Some("Kill chain".to_string());
+/// collection.entity_refs = vec![
+///     "behavior--550e8400-e29b-41d4-a716-446655440000".to_string(),
+///     "collection--550e8400-e29b-41d4-a716-446655440001".to_string(),
+/// ];
+///
+/// let grouping = maec::stix::collection_to_grouping(&collection).unwrap();
+/// assert_eq!(grouping["type"], "grouping");
+/// assert_eq!(grouping["context"], "unspecified");
+/// assert_eq!(
+///     grouping["object_refs"],
+///     serde_json::json!(["grouping--550e8400-e29b-41d4-a716-446655440001"])
+/// );
+///
+/// collection.entity_refs.pop();
+/// assert!(maec::stix::collection_to_grouping(&collection).is_none());
+/// ```
+pub fn collection_to_grouping(collection: &Collection) -> Option<Value> {
+    let object_refs: Vec<String> = collection
+        .entity_refs
+        .iter()
+        .filter_map(|r| grouping_ref(r))
+        .collect();
+    if object_refs.is_empty() {
+        return None;
+    }
+
+    let id = match collection.common.id.split_once("--") {
+        Some((_, uuid)) => format!("grouping--{}", uuid),
+        None => generate_maec_id("grouping"),
+    };
+
+    let mut sdo = Map::new();
+    sdo.insert("type".to_string(), json!("grouping"));
+    sdo.insert("spec_version".to_string(), json!(STIX_SPEC_VERSION));
+    sdo.insert("id".to_string(), json!(id));
+    sdo.insert(
+        "created".to_string(),
+        json!(stix_timestamp(collection.common.created)),
+    );
+    sdo.insert(
+        "modified".to_string(),
+        json!(stix_timestamp(collection.common.modified)),
+    );
+    if let Some(name) = &collection.name {
+        sdo.insert("name".to_string(), json!(name));
+    }
+    if let Some(description) = &collection.description {
+        sdo.insert("description".to_string(), json!(description));
+    }
+    sdo.insert("context".to_string(), json!("unspecified"));
+    sdo.insert("object_refs".to_string(), json!(object_refs));
+
+    Some(Value::Object(sdo))
+}
+
+/// Maps a collection entity reference onto the STIX identifier it exports as
+fn grouping_ref(reference: &str) -> Option<String> {
+    if !is_valid_maec_id(reference) {
+        return None;
+    }
+    let (r#type, uuid) = reference.split_once("--")?;
+    match r#type {
+        "collection" => Some(format!("grouping--{}", uuid)),
+        _ if MAEC_ONLY_TYPES.contains(&r#type) => None,
+        _ => Some(reference.to_string()),
+    }
+}
+
+/// Extracts the MAEC package embedded in a STIX bundle
+///
+/// The package is the single object of the bundle's `objects` array whose
+/// `type` is `x-maec-package` (a STIX custom object) or `package` (a MAEC
+/// package dropped in verbatim). Its `type` is read as `package`, an
+/// `x-maec-package--` prefix of its `id` is rewritten to `package--`, and the
+/// result is validated.
+///
+/// Returns `MaecError::ValidationError` if the input is not a bundle or if it
+/// embeds no package or more than one.
+///
+/// # Examples
+///
+/// ```
+/// use maec::Package;
+/// use serde_json::json;
+///
+/// let mut embedded = serde_json::to_value(Package::new()).unwrap();
+/// embedded["type"] = json!("x-maec-package");
+/// embedded["id"] = json!("x-maec-package--2f8e8d3b-0f0e-4bb5-8a3f-1b2c3d4e5f60");
+/// let bundle = json!({
+///     "type": "bundle",
+///     "id": "bundle--5d0092c5-5f74-4287-9642-33f4c354e56d",
+///     "objects": [embedded]
+/// });
+///
+/// let package = maec::stix::extract_package_from_bundle(&bundle).unwrap();
+/// assert_eq!(package.common.r#type, "package");
+/// assert_eq!(package.common.id, "package--2f8e8d3b-0f0e-4bb5-8a3f-1b2c3d4e5f60");
+/// ```
+pub fn extract_package_from_bundle(bundle: &Value) -> Result<Package> {
+    if bundle.get("type").and_then(Value::as_str) != Some("bundle") {
+        return Err(MaecError::ValidationError(
+            "expected a STIX bundle".to_string(),
+        ));
+    }
+
+    let objects = bundle
+        .get("objects")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut embedded = objects.iter().filter(|object| {
+        object
+            .get("type")
+            .and_then(Value::as_str)
+            .is_some_and(|t| EMBEDDED_PACKAGE_TYPES.contains(&t))
+    });
+    let object = match (embedded.next(), embedded.next()) {
+        (Some(object), None) => object,
+        (None, _) => {
+            return Err(MaecError::ValidationError(
+                "STIX bundle contains no MAEC package".to_string(),
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err(MaecError::ValidationError(
+                "STIX bundle contains more than one MAEC package".to_string(),
+            ))
+        }
+    };
+
+    let mut object = object.clone();
+    object["type"] = json!("package");
+    if let Some(uuid) = object
+        .get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| id.strip_prefix("x-maec-package--"))
+    {
+        object["id"] = json!(format!("package--{}", uuid));
+    }
+    let package: Package = serde_json::from_value(object)?;
+    package.validate()?;
+    Ok(package)
+}
+
+/// Reads an optional string property from a STIX object
+fn string_property(sdo: &Value, key: &str) -> Result<Option<String>> {
+    match sdo.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(other) => Err(MaecError::ValidationError(format!(
+            "STIX property '{}' must be a string, got {}",
+            key, other
+        ))),
+    }
+}
+
+/// Reads an optional list-of-strings property from a STIX object
+fn string_list_property(sdo: &Value, key: &str) -> Result<Vec<String>> {
+    match sdo.get(key) {
+        None | Some(Value::Null) => Ok(vec![]),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str().map(str::to_string).ok_or_else(|| {
+                    MaecError::ValidationError(format!(
+                        "STIX property '{}' must contain only strings",
+                        key
+                    ))
+                })
+            })
+            .collect(),
+        Some(other) => Err(MaecError::ValidationError(format!(
+            "STIX property '{}' must be a list, got {}",
+            key, other
+        ))),
+    }
+}
+
+/// Reads an optional timestamp property from a STIX object
+fn timestamp_property(sdo: &Value, key: &str) -> Result<Option<DateTime<Utc>>> {
+    string_property(sdo, key)?
+        .map(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|e| {
+                    MaecError::ValidationError(format!(
+                        "STIX property '{}' is not a valid timestamp: {}",
+                        key, e
+                    ))
+                })
+        })
+        .transpose()
+}
+
+/// Imports a STIX 2.1 `malware` SDO as a MalwareInstance
+///
+/// The following properties are mapped:
+/// - `name` and `aliases` become the instance's [`Name`]s
+/// - `malware_types` become `labels`
+/// - `first_seen`/`last_seen` become [`FieldData`]
+/// - `sample_refs` become `instance_object_refs` (falling back to the SDO's own
+///   `id` when no samples are referenced)
+/// - `is_family` and any `x_maec_*` extension properties are kept in
+///   `custom_properties` (`is_family` under `x_maec_is_family`)
+///
+/// Returns `MaecError::ValidationError` if the input is not a `malware` SDO.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let sdo = json!({
+///     "type": "malware",
+///     "id": "malware--31b940d4-6f7f-459a-80ea-9c1f17b5891b",
+///     "name": "Cryptolocker",
+///     "malware_types": ["ransomware"],
+///     "is_family": false
+/// });
+///
+/// let instance = maec::stix::malware_sdo_to_instance(&sdo).unwrap();
+/// assert_eq!(instance.name.unwrap().value, "Cryptolocker");
+/// assert_eq!(instance.labels, vec!["ransomware".to_string()]);
+/// ```
+pub fn malware_sdo_to_instance(sdo: &Value) -> Result<MalwareInstance> {
+    let sdo_type = sdo.get("type").and_then(Value::as_str);
+    if sdo_type != Some("malware") {
+        return Err(MaecError::ValidationError(format!(
+            "expected a STIX 'malware' SDO, got type {:?}",
+            sdo_type
+        )));
+    }
+
+    let mut instance_object_refs = string_list_property(sdo, "sample_refs")?;
+    if instance_object_refs.is_empty() {
+        let id = string_property(sdo, "id")?.ok_or(MaecError::MissingField("id"))?;
+        instance_object_refs.push(id);
+    }
+
+    let mut instance = MalwareInstance::new(instance_object_refs);
+    instance.name = string_property(sdo, "name")?.map(Name::new);
+    instance.aliases = string_list_property(sdo, "aliases")?
+        .into_iter()
+        .map(Name::new)
+        .collect();
+    instance.labels = string_list_property(sdo, "malware_types")?;
+    instance.description = string_property(sdo, "description")?;
+    instance.architecture_execution_envs =
+        string_list_property(sdo, "architecture_execution_envs")?;
+
+    let first_seen = timestamp_property(sdo, "first_seen")?;
+    let last_seen = timestamp_property(sdo, "last_seen")?;
+    if let Some(first_seen) = first_seen {
+        instance.field_data = Some(FieldData::with_timestamps(first_seen, last_seen));
+    } else if let Some(last_seen) = last_seen {
+        instance.field_data = Some(FieldData::builder().last_seen(last_seen).build()?);
+    }
+
+    if let Some(created) = timestamp_property(sdo, "created")? {
+        instance.common.created = created;
+    }
+    if let Some(modified) = timestamp_property(sdo, "modified")? {
+        instance.common.modified = modified;
+    }
+    instance.common.created_by_ref = string_property(sdo, "created_by_ref")?;
+
+    if let Some(is_family) = sdo.get("is_family") {
+        instance
+            .common
+            .custom_properties
+            .insert("x_maec_is_family".to_string(), is_family.clone());
+    }
+    if let Some(properties) = sdo.as_object() {
+        for (key, value) in properties {
+            if key.starts_with("x_maec_") {
+                instance
+                    .common
+                    .custom_properties
+                    .insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    instance.validate()?;
+    Ok(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnalysisMetadata, AnalysisType};
+
+    #[test]
+    fn test_analysis_to_stix() {
+        let started = Utc::now();
+        let mut metadata = AnalysisMetadata::new(true, AnalysisType::Dynamic);
+        metadata.start_time = Some(started);
+        metadata.conclusion = Some(AnalysisConclusionType::Indeterminate);
+
+        let instance = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_analysis_metadata(metadata)
+            .add_analysis_metadata(AnalysisMetadata::new(false, AnalysisType::Static))
+            .build()
+            .unwrap();
+
+        let analyses = analysis_to_stix(&instance);
+        assert_eq!(analyses.len(), 2);
+
+        let first = &analyses[0];
+        assert_eq!(first["spec_version"], "2.1");
+        assert!(first["id"]
+            .as_str()
+            .unwrap()
+            .starts_with("malware-analysis--"));
+        assert_eq!(first["analysis_started"], stix_timestamp(started));
+        assert_eq!(first["result"], "unknown");
+        assert_eq!(first["product"], "unknown");
+        assert!(first.get("sample_ref").is_none());
+
+        assert!(analyses[1].get("result").is_none());
+    }
+
+    #[test]
+    fn test_package_analysis_to_stix() {
+        let mut metadata = AnalysisMetadata::new(true, AnalysisType::Dynamic);
+        metadata.tool_refs = vec!["missing".to_string(), "2".to_string(), "3".to_string()];
+        let instance = MalwareInstance::builder()
+            .add_instance_object_ref("0")
+            .add_instance_object_ref("1")
+            .add_analysis_metadata(metadata)
+            .add_analysis_metadata(AnalysisMetadata::new(false, AnalysisType::Static))
+            .build()
+            .unwrap();
+
+        let mut package = Package::new();
+        let sample = "file--550e8400-e29b-41d4-a716-446655440000";
+        for (key, observable) in [
+            ("0", json!({"type": "file", "name": "no-id.exe"})),
+            ("1", json!({"type": "file", "id": sample})),
+            ("2", json!({"type": "process", "name": "not a tool"})),
+            (
+                "3",
+                json!({"type": "software", "name": "cuckoo", "vendor": "cuckoo"}),
+            ),
+        ] {
+            package
+                .observable_objects
+                .get_or_insert_with(BTreeMap::new)
+                .insert(key.to_string(), observable);
+        }
+
+        let analyses = package_analysis_to_stix(&package, &instance);
+        assert_eq!(analyses[0]["sample_ref"], sample);
+        assert_eq!(analyses[0]["product"], "cuckoo");
+        assert_eq!(analyses[1]["product"], "unknown");
+        assert_eq!(analyses[1]["sample_ref"], sample);
+
+        // References that already are STIX identifiers need no package
+        let direct = MalwareInstance::builder()
+            .add_instance_object_ref(sample)
+            .add_analysis_metadata(AnalysisMetadata::new(false, AnalysisType::Static))
+            .build()
+            .unwrap();
+        assert_eq!(analysis_to_stix(&direct)[0]["sample_ref"], sample);
+    }
+
+    #[test]
+    fn test_collection_to_grouping() {
+        let mut collection = Collection::new();
+        collection.common.id = "collection--550e8400-e29b-41d4-a716-446655440000".to_string();
+        collection.description = Some("Initial access steps".to_string());
+        collection.entity_refs = vec![
+            "file--550e8400-e29b-41d4-a716-446655440003".to_string(),
+            "behavior--550e8400-e29b-41d4-a716-446655440002".to_string(),
+            "collection--550e8400-e29b-41d4-a716-446655440001".to_string(),
+            "malware-instance--550e8400-e29b-41d4-a716-446655440004".to_string(),
+            "0".to_string(),
+        ];
+
+        let grouping = collection_to_grouping(&collection).unwrap();
+        assert_eq!(
+            grouping["id"],
+            "grouping--550e8400-e29b-41d4-a716-446655440000"
+        );
+        assert_eq!(grouping["spec_version"], "2.1");
+        assert_eq!(grouping["description"], "Initial access steps");
+        assert!(grouping.get("name").is_none());
+        assert_eq!(
+            grouping["object_refs"],
+            json!([
+                "file--550e8400-e29b-41d4-a716-446655440003",
+                "grouping--550e8400-e29b-41d4-a716-446655440001",
+            ])
+        );
+
+        collection.entity_refs = vec![
+            "behavior--550e8400-e29b-41d4-a716-446655440002".to_string(),
+            "malware-instance--550e8400-e29b-41d4-a716-446655440004".to_string(),
+        ];
+        assert!(collection_to_grouping(&collection).is_none());
+        collection.entity_refs.clear();
+        assert!(collection_to_grouping(&collection).is_none());
+    }
+
+    #[test]
+    fn test_extract_package_from_bundle() {
+        let package = Package::builder()
+            .add_malware_family(crate::MalwareFamily::new("WannaCry"))
+            .build()
+            .unwrap();
+        let mut embedded = serde_json::to_value(&package).unwrap();
+        embedded["type"] = json!("x-maec-package");
+        let indicator = json!({"type": "indicator", "id": "indicator--1"});
+        let bundle = |objects: Vec<&Value>| {
+            json!({
+                "type": "bundle",
+                "id": "bundle--5d0092c5-5f74-4287-9642-33f4c354e56d",
+                "objects": objects
+            })
+        };
+
+        let extracted = extract_package_from_bundle(&bundle(vec![&indicator, &embedded])).unwrap();
+        assert_eq!(extracted, package);
+
+        // A conformant custom object carries the custom type in its ID as well
+        let mut custom = embedded.clone();
+        custom["id"] = json!(package
+            .common
+            .id
+            .replacen("package--", "x-maec-package--", 1));
+        let extracted = extract_package_from_bundle(&bundle(vec![&custom])).unwrap();
+        assert_eq!(extracted, package);
+
+        for objects in [vec![&indicator], vec![&embedded, &embedded]] {
+            assert!(matches!(
+                extract_package_from_bundle(&bundle(objects)),
+                Err(MaecError::ValidationError(_))
+            ));
+        }
+        assert!(extract_package_from_bundle(&embedded).is_err());
+    }
+
+    #[test]
+    fn test_malware_sdo_to_instance() {
+        let sdo = json!({
+            "type": "malware",
+            "spec_version": "2.1",
+            "id": "malware--0c7b5b88-8ff7-4a4d-aa9d-feb398cd0061",
+            "created": "2016-05-12T08:17:27.000Z",
+            "modified": "2016-05-12T08:17:27.000Z",
+            "name": "Cryptolocker",
+            "aliases": ["Crypto"],
+            "malware_types": ["ransomware", "dropper"],
+            "is_family": false,
+            "first_seen": "2016-05-01T00:00:00Z",
+            "sample_refs": ["file--6ce09d9c-0ad3-5ebf-900c-e3cb288955b5"],
+            "x_maec_source": "sandbox-7"
+        });
+
+        let instance = malware_sdo_to_instance(&sdo).unwrap();
+        assert_eq!(instance.name.as_ref().unwrap().value, "Cryptolocker");
+        assert_eq!(instance.aliases, vec![Name::new("Crypto")]);
+        assert_eq!(instance.labels.len(), 2);
+        assert_eq!(
+            instance.instance_object_refs,
+            vec!["file--6ce09d9c-0ad3-5ebf-900c-e3cb288955b5".to_string()]
+        );
+        assert!(instance.field_data.unwrap().first_seen.is_some());
+        assert_eq!(
+            instance.common.custom_properties.get("x_maec_source"),
+            Some(&json!("sandbox-7"))
+        );
+        assert_eq!(
+            instance.common.custom_properties.get("x_maec_is_family"),
+            Some(&json!(false))
+        );
+    }
+
+    #[test]
+    fn test_malware_sdo_to_instance_rejects_other_types() {
+        let sdo = json!({"type": "indicator", "id": "indicator--1"});
+        assert!(matches!(
+            malware_sdo_to_instance(&sdo),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,382 @@
+//! Upgrading legacy MAEC content to MAEC 5.0
+//!
+//! MAEC 4.x organised analysis results around malware subjects, each carrying
+//! the analysed object and one or more findings bundles. This module maps that
+//! structure onto the 5.0 object model on a best-effort basis and reports
+//! everything it could not carry over.
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{MaecError, Result};
+use crate::objects::MaecObjectType;
+use crate::{
+    Behavior, BehaviorVocab, Capability, MalwareAction, MalwareActionVocab, MalwareInstance,
+    Package,
+};
+
+/// Package properties handled by the upgrade
+const PACKAGE_KEYS: &[&str] = &["id", "timestamp", "schema_version", "malware_subjects"];
+
+/// Malware subject properties handled by the upgrade
+const SUBJECT_KEYS: &[&str] = &[
+    "id",
+    "label",
+    "malware_instance_object_attributes",
+    "findings_bundles",
+];
+
+/// Bundle properties handled by the upgrade
+const BUNDLE_KEYS: &[&str] = &["id", "actions", "behaviors", "capabilities"];
+
+/// Upgrades a MAEC 4.x package, in its JSON rendering, to a MAEC 5.0 Package
+///
+/// The following parts of the legacy structure are mapped:
+/// - each malware subject becomes a [`MalwareInstance`], with its
+///   `malware_instance_object_attributes` turned into a STIX `file` observable
+/// - actions and behaviors in the subject's findings bundles become
+///   [`MalwareAction`]s and [`Behavior`]s when their names exist in the 5.0
+///   vocabularies
+/// - bundle capabilities become capabilities of the instance
+///
+/// Legacy IDs are replaced by MAEC 5.0 IDs. Everything that is dropped or
+/// approximated is described in the returned list of warnings.
+///
+/// # Examples
+///
+/// ```
+/// let legacy = r#"{
+///     "id": "example:package-1",
+///     "schema_version": "4.1",
+///     "malware_subjects": [{
+///         "id": "example:subject-1",
+///         "malware_instance_object_attributes": {
+///             "properties": {"file_name": "dropper.exe"}
+///         },
+///         "findings_bundles": {"bundle": [{
+///             "actions": [{"id": "example:action-1", "name": "create file"}]
+///         }]}
+///     }]
+/// }"#;
+///
+/// let (package, warnings) = maec::upgrade::from_maec_4x(legacy).unwrap();
+/// assert_eq!(package.malware_instances().len(), 1);
+/// assert_eq!(package.malware_actions().len(), 1);
+/// assert!(warnings.is_empty());
+/// ```
+pub fn from_maec_4x(json: &str) -> Result<(Package, Vec<String>)> {
+    let root: Value = serde_json::from_str(json)?;
+    let root = root.as_object().ok_or_else(|| {
+        MaecError::ValidationError("MAEC 4.x package must be a JSON object".to_string())
+    })?;
+
+    let mut warnings = vec![];
+    let mut package = Package::new();
+    if let Some(created) = timestamp(root.get("timestamp")) {
+        package.common.created = created;
+        package.common.modified = created;
+    }
+
+    let mut observables = BTreeMap::new();
+    for subject in items(root.get("malware_subjects"), "malware_subject") {
+        upgrade_subject(subject, &mut package, &mut observables, &mut warnings);
+    }
+    if !observables.is_empty() {
+        package.observable_objects = Some(observables);
+    }
+
+    warn_unmapped(root, PACKAGE_KEYS, "package", &mut warnings);
+
+    package.validate()?;
+    Ok((package, warnings))
+}
+
+/// Converts one malware subject and its findings bundles
+fn upgrade_subject(
+    subject: &Value,
+    package: &mut Package,
+    observables: &mut BTreeMap<String, Value>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(subject) = subject.as_object() else {
+        warnings.push("skipped a malware subject that is not a JSON object".to_string());
+        return;
+    };
+    let subject_id = subject
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or("<unnamed>");
+
+    let key = observables.len().to_string();
+    let observable = match subject.get("malware_instance_object_attributes") {
+        Some(attributes) => file_observable(attributes),
+        None => {
+            warnings.push(format!(
+                "malware subject '{}' has no malware_instance_object_attributes; \
+                 an empty file observable was created",
+                subject_id
+            ));
+            json!({"type": "file"})
+        }
+    };
+    observables.insert(key.clone(), observable);
+
+    let mut instance = MalwareInstance::new(vec![key]);
+    instance.labels = strings(subject.get("label"));
+
+    for bundle in items(subject.get("findings_bundles"), "bundle") {
+        let Some(bundle) = bundle.as_object() else {
+            continue;
+        };
+
+        let mut action_ids = HashMap::new();
+        for action in items(bundle.get("actions"), "action") {
+            let name = action.get("name").and_then(Value::as_str).unwrap_or("");
+            match vocab::<MalwareActionVocab>(name) {
+                Some(vocab) => {
+                    let mut upgraded = MalwareAction::new(vocab);
+                    upgraded.description = string(action.get("description"));
+                    upgraded.timestamp = timestamp(action.get("timestamp"));
+                    if let Some(legacy_id) = action.get("id").and_then(Value::as_str) {
+                        action_ids.insert(legacy_id.to_string(), upgraded.common.id.clone());
+                    }
+                    package
+                        .maec_objects
+                        .push(MaecObjectType::MalwareAction(upgraded));
+                }
+                None => warnings.push(format!(
+                    "action '{}' in malware subject '{}' has no MAEC 5.0 equivalent",
+                    name, subject_id
+                )),
+            }
+        }
+
+        for behavior in items(bundle.get("behaviors"), "behavior") {
+            let name = behavior.get("name").and_then(Value::as_str).unwrap_or("");
+            let Some(vocab) = vocab::<BehaviorVocab>(name) else {
+                warnings.push(format!(
+                    "behavior '{}' in malware subject '{}' has no MAEC 5.0 equivalent",
+                    name, subject_id
+                ));
+                continue;
+            };
+
+            let mut upgraded = Behavior::new(vocab);
+            upgraded.description = string(behavior.get("description"));
+            for reference in items(behavior.get("action_composition"), "action_reference") {
+                let legacy_ref = reference
+                    .get("action_id")
+                    .or_else(|| reference.get("idref"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                match action_ids.get(legacy_ref) {
+                    Some(id) => upgraded.action_refs.push(id.clone()),
+                    None => warnings.push(format!(
+                        "behavior '{}' references action '{}', which was not upgraded",
+                        name, legacy_ref
+                    )),
+                }
+            }
+            package
+                .maec_objects
+                .push(MaecObjectType::Behavior(upgraded));
+        }
+
+        for capability in items(bundle.get("capabilities"), "capability") {
+            match capability.get("name").and_then(Value::as_str) {
+                Some(name) => {
+                    let mut upgraded = Capability::new(name.replace([' ', '_'], "-"));
+                    upgraded.description = string(capability.get("description"));
+                    instance.capabilities.push(upgraded);
+                }
+                None => warnings.push(format!(
+                    "skipped an unnamed capability in malware subject '{}'",
+                    subject_id
+                )),
+            }
+        }
+
+        warn_unmapped(
+            bundle,
+            BUNDLE_KEYS,
+            &format!("findings bundle of malware subject '{}'", subject_id),
+            warnings,
+        );
+    }
+
+    warn_unmapped(
+        subject,
+        SUBJECT_KEYS,
+        &format!("malware subject '{}'", subject_id),
+        warnings,
+    );
+    package
+        .maec_objects
+        .push(MaecObjectType::MalwareInstance(instance));
+}
+
+/// Builds a STIX `file` observable from legacy object attributes
+fn file_observable(attributes: &Value) -> Value {
+    let properties = attributes.get("properties").unwrap_or(attributes);
+
+    let mut file = Map::new();
+    file.insert("type".to_string(), json!("file"));
+    if let Some(name) = properties.get("file_name") {
+        file.insert("name".to_string(), name.clone());
+    }
+    if let Some(size) = properties.get("size_in_bytes") {
+        file.insert("size".to_string(), size.clone());
+    }
+
+    let hashes: Map<String, Value> = items(properties.get("hashes"), "hash")
+        .into_iter()
+        .filter_map(|hash| {
+            let algorithm = hash.get("type").and_then(Value::as_str)?;
+            let value = hash.get("simple_hash_value")?;
+            Some((algorithm.to_uppercase(), value.clone()))
+        })
+        .collect();
+    if !hashes.is_empty() {
+        file.insert("hashes".to_string(), Value::Object(hashes));
+    }
+
+    Value::Object(file)
+}
+
+/// Returns the entries of a legacy list
+///
+/// MAEC 4.x lists appear either as plain arrays or wrapped in an object under
+/// their singular element name (e.g. `{"bundle": [...]}`); a single element
+/// may also appear without an array.
+fn items<'a>(value: Option<&'a Value>, element: &str) -> Vec<&'a Value> {
+    match value {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(wrapper @ Value::Object(map)) => match map.get(element) {
+            Some(Value::Array(items)) => items.iter().collect(),
+            Some(item) => vec![item],
+            None => vec![wrapper],
+        },
+        _ => vec![],
+    }
+}
+
+/// Parses a legacy vocabulary name (e.g. `"create file"`) into a 5.0 vocabulary
+fn vocab<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let normalized = name.trim().to_lowercase().replace([' ', '_'], "-");
+    serde_json::from_value(Value::String(normalized)).ok()
+}
+
+fn string(value: Option<&Value>) -> Option<String> {
+    value.and_then(Value::as_str).map(str::to_string)
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn timestamp(value: Option<&Value>) -> Option<DateTime<Utc>> {
+    let value = value?.as_str()?;
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Records a warning for every property of `object` the upgrade ignores
+fn warn_unmapped(
+    object: &Map<String, Value>,
+    handled: &[&str],
+    context: &str,
+    warnings: &mut Vec<String>,
+) {
+    for key in object.keys() {
+        if !handled.contains(&key.as_str()) {
+            warnings.push(format!("dropped property '{}' of {}", key, context));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_maec_4x() {
+        let legacy = json!({
+            "id": "example:package-1",
+            "schema_version": "4.1",
+            "timestamp": "2014-02-20T09:00:00Z",
+            "malware_subjects": {"malware_subject": [{
+                "id": "example:subject-1",
+                "label": ["trojan"],
+                "malware_instance_object_attributes": {
+                    "properties": {
+                        "file_name": "sample.exe",
+                        "size_in_bytes": 4096,
+                        "hashes": [{"type": "md5", "simple_hash_value": "0123456789abcdef"}]
+                    }
+                },
+                "findings_bundles": {"bundle": [{
+                    "id": "example:bundle-1",
+                    "actions": {"action": [
+                        {"id": "example:action-1", "name": "create_file"},
+                        {"id": "example:action-2", "name": "frobnicate"}
+                    ]},
+                    "behaviors": [{
+                        "name": "persist after system reboot",
+                        "action_composition": {"action_reference": [
+                            {"action_id": "example:action-1"},
+                            {"action_id": "example:action-2"}
+                        ]}
+                    }],
+                    "capabilities": [{"name": "persistence"}],
+                    "process_tree": {}
+                }]},
+                "minor_variants": []
+            }]}
+        });
+
+        let (package, warnings) = from_maec_4x(&legacy.to_string()).unwrap();
+
+        let instances = package.malware_instances();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].labels, vec!["trojan".to_string()]);
+        assert_eq!(instances[0].capabilities[0].name, "persistence");
+
+        let observables = package.observable_objects.as_ref().unwrap();
+        assert_eq!(
+            observables["0"],
+            json!({"type": "file", "name": "sample.exe", "size": 4096,
+                   "hashes": {"MD5": "0123456789abcdef"}})
+        );
+
+        let actions = package.malware_actions();
+        assert_eq!(actions.len(), 1);
+        let behaviors = package.behaviors();
+        assert_eq!(behaviors.len(), 1);
+        assert_eq!(behaviors[0].action_refs, vec![actions[0].common.id.clone()]);
+
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.contains("'frobnicate'")));
+        assert!(warnings.iter().any(|w| w.contains("'example:action-2'")));
+        assert!(warnings.iter().any(|w| w.contains("'process_tree'")));
+        assert!(warnings.iter().any(|w| w.contains("'minor_variants'")));
+    }
+
+    #[test]
+    fn test_from_maec_4x_rejects_non_objects() {
+        assert!(matches!(
+            from_maec_4x("[]"),
+            Err(MaecError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! Validation on deserialization
+//!
+//! [`Valid`] wraps a MAEC type so that deserializing it also validates it,
+//! which lets serde-based frameworks hand out only valid objects.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Result;
+
+/// MAEC types with a structural `validate` check
+///
+/// Implemented for [`Package`](crate::Package), [`MaecObjectType`](crate::MaecObjectType)
+/// and every MAEC object type by delegating to their inherent `validate`.
+pub trait Validate {
+    /// Checks the structure of the value
+    fn validate(&self) -> Result<()>;
+}
+
+/// A value that passed [`Validate::validate`]
+///
+/// Deserializing a `Valid<T>` deserializes `T` and then validates it; a
+/// validation failure is reported as a deserialization error. Serialization
+/// is transparent.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{Package, Valid};
+///
+/// let json = Package::new().to_json().unwrap();
+/// let package: Valid<Package> = serde_json::from_str(&json).unwrap();
+/// assert!(package.maec_objects.is_empty());
+///
+/// let invalid = json.replace("\"package--", "\"package-");
+/// assert!(serde_json::from_str::<Valid<Package>>(&invalid).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Valid<T>(T);
+
+impl<T: Validate> Valid<T> {
+    /// Validates `value`, wrapping it on success
+    pub fn new(value: T) -> Result<Self> {
+        value.validate()?;
+        Ok(Self(value))
+    }
+}
+
+impl<T> Valid<T> {
+    /// Returns the validated value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Valid<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Serialize> Serialize for Valid<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Validate> Deserialize<'de> for Valid<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = T::deserialize(deserializer)?;
+        Valid::new(value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Validate for crate::MaecObjectType {
+    fn validate(&self) -> Result<()> {
+        crate::MaecObjectType::validate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Behavior, BehaviorVocab, MaecObject, MaecObjectType};
+
+    #[test]
+    fn test_valid_behavior() {
+        let behavior = Behavior::new(BehaviorVocab::SendBeacon);
+        let json = behavior.to_json().unwrap();
+
+        let valid: Valid<Behavior> = serde_json::from_str(&json).unwrap();
+        assert_eq!(valid.name, BehaviorVocab::SendBeacon);
+        assert_eq!(serde_json::to_string(&valid).unwrap(), json);
+        assert_eq!(valid.into_inner(), behavior);
+
+        let wrong_id = json.replace("\"behavior--", "\"behavior-");
+        let error = serde_json::from_str::<Valid<Behavior>>(&wrong_id).unwrap_err();
+        assert!(error.to_string().contains("invalid MAEC ID"));
+
+        let object: Valid<MaecObjectType> = serde_json::from_str(&json).unwrap();
+        assert_eq!(object.id(), behavior.common.id);
+    }
+}
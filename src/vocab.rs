@@ -1,352 +1,744 @@
-//! MAEC 5.0 Open Vocabularies
-//!
-//! This module provides type-safe enumerations for all MAEC 5.0 open vocabularies,
-//! ensuring 100% compliance with the MAEC specification.
-
-use serde::{Deserialize, Serialize};
-
-/// Analysis conclusion types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AnalysisConclusionType {
-    /// The analyzed entity is benign
-    Benign,
-    /// The analyzed entity is malicious
-    Malicious,
-    /// The analyzed entity is suspicious
-    Suspicious,
-    /// The conclusion is indeterminate
-    Indeterminate,
-}
-
-/// Analysis environment properties
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AnalysisEnvironment {
-    /// Operating system property
-    OperatingSystem,
-    /// Host VM property
-    HostVm,
-    /// Installed software property
-    InstalledSoftware,
-}
-
-/// Malware analysis types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum AnalysisType {
-    /// Static analysis
-    Static,
-    /// Dynamic analysis
-    Dynamic,
-    /// Combination of static and dynamic
-    Combination,
-}
-
-/// Confidence measure levels (aligned with STIX HighMediumLow vocabulary)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum ConfidenceMeasure {
-    /// Low confidence
-    Low,
-    /// Medium confidence
-    Medium,
-    /// High confidence
-    High,
-    /// No confidence
-    None,
-    /// Unknown confidence
-    Unknown,
-}
-
-/// Processor architectures
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum ProcessorArchitecture {
-    /// x86 32-bit architecture
-    X86,
-    /// x86-64 (AMD64) architecture
-    #[serde(rename = "x86-64")]
-    X8664,
-    /// Intel IA-64 architecture
-    #[serde(rename = "ia-64")]
-    Ia64,
-    /// PowerPC architecture
-    PowerPc,
-    /// ARM architecture
-    Arm,
-    /// Alpha architecture
-    Alpha,
-    /// SPARC architecture
-    Sparc,
-    /// MIPS architecture
-    Mips,
-}
-
-/// Binary obfuscation methods
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum ObfuscationMethod {
-    /// Packing/compression
-    Packing,
-    /// Code encryption
-    CodeEncryption,
-    /// Dead code insertion
-    DeadCodeInsertion,
-    /// Entry point obfuscation
-    EntryPointObfuscation,
-    /// Import address table obfuscation
-    ImportAddressTableObfuscation,
-    /// Interleaving code
-    InterleavingCode,
-    /// Symbolic obfuscation
-    SymbolicObfuscation,
-    /// String obfuscation
-    StringObfuscation,
-    /// Subroutine reordering
-    SubroutineReordering,
-    /// Code transposition
-    CodeTransposition,
-    /// Instruction substitution
-    InstructionSubstitution,
-    /// Register reassignment
-    RegisterReassignment,
-}
-
-// Helper macro for creating large string-based enums
-macro_rules! string_enum {
-    (
-        $(#[$meta:meta])*
-        $vis:vis enum $name:ident {
-            $(
-                $(#[$variant_meta:meta])*
-                $variant:ident => $value:expr
-            ),* $(,)?
-        }
-    ) => {
-        $(#[$meta])*
-        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        $vis enum $name {
-            $(
-                $(#[$variant_meta])*
-                #[serde(rename = $value)]
-                $variant,
-            )*
-        }
-    };
-}
-
-string_enum! {
-    /// Delivery/infection vectors
-    pub enum DeliveryVector {
-        ActiveAttacker => "active-attacker",
-        AutoExecutingMedia => "auto-executing-media",
-        Downloader => "downloader",
-        Dropper => "dropper",
-        EmailAttachment => "email-attachment",
-        ExploitKitLandingPage => "exploit-kit-landing-page",
-        FakeWebsite => "fake-website",
-        JanitorAttack => "janitor-attack",
-        MaliciousIframes => "malicious-iframes",
-        Malvertising => "malvertising",
-        MediaBaiting => "media-baiting",
-        Pharming => "pharming",
-        Phishing => "phishing",
-        TrojanizedLink => "trojanized-link",
-        TrojanizedSoftware => "trojanized-software",
-        UsbCableSyncing => "usb-cable-syncing",
-        WateringHole => "watering-hole",
-    }
-}
-
-string_enum! {
-    /// Common malware labels
-    pub enum MalwareLabel {
-        Adware => "adware",
-        Appender => "appender",
-        Backdoor => "backdoor",
-        BootSectorVirus => "boot-sector-virus",
-        Bot => "bot",
-        CavityFiller => "cavity-filler",
-        Clicker => "clicker",
-        CompanionVirus => "companion-virus",
-        DataDiddler => "data-diddler",
-        Downloader => "downloader",
-        DropperFile => "dropper-file",
-        FileInfectorVirus => "file-infector-virus",
-        ForkBomb => "fork-bomb",
-        Greyware => "greyware",
-        Implant => "implant",
-        Infector => "infector",
-        JokeProgram => "joke-program",
-        Keylogger => "keylogger",
-        KleptographicWorm => "kleptographic-worm",
-        MacroVirus => "macro-virus",
-        MassMailer => "mass-mailer",
-        MetamorphicVirus => "metamorphic-virus",
-        MidInfector => "mid-infector",
-        MobileCode => "mobile-code",
-        MultipartiteVirus => "multipartite-virus",
-        ParentalControl => "parental-control",
-        PasswordStealer => "password-stealer",
-        PolymorphicVirus => "polymorphic-virus",
-        PremiumDialerOrSmser => "premium-dialer-or-smser",
-        Prepender => "prepender",
-        Ransomware => "ransomware",
-        RogueAntiMalware => "rogue-anti-malware",
-        Rootkit => "rootkit",
-        Scareware => "scareware",
-        SecurityAssessmentTool => "security-assessment-tool",
-        Shellcode => "shellcode",
-        SpaghettiPacker => "spaghetti-packer",
-        Spyware => "spyware",
-        Trackware => "trackware",
-        TrojanHorse => "trojan-horse",
-        Virus => "virus",
-        WebBug => "web-bug",
-        Wiper => "wiper",
-        Worm => "worm",
-    }
-}
-
-string_enum! {
-    /// MAEC entity association types
-    pub enum EntityAssociation {
-        FileSystemEntities => "file-system-entities",
-        NetworkEntities => "network-entities",
-        ProcessEntities => "process-entities",
-        MemoryEntities => "memory-entities",
-        IpcEntities => "ipc-entities",
-        DeviceEntities => "device-entities",
-        RegistryEntities => "registry-entities",
-        ServiceEntities => "service-entities",
-        PotentialIndicators => "potential-indicators",
-        SameMalwareFamily => "same-malware-family",
-        ClusteredTogether => "clustered-together",
-        ObservedTogether => "observed-together",
-        PartOfIntrusionSet => "part-of-intrusion-set",
-        SameMalwareToolkit => "same-malware-toolkit",
-    }
-}
-
-/// Allow using string slices directly for vocabularies
-impl AsRef<str> for DeliveryVector {
-    fn as_ref(&self) -> &str {
-        match self {
-            DeliveryVector::ActiveAttacker => "active-attacker",
-            DeliveryVector::AutoExecutingMedia => "auto-executing-media",
-            DeliveryVector::Downloader => "downloader",
-            DeliveryVector::Dropper => "dropper",
-            DeliveryVector::EmailAttachment => "email-attachment",
-            DeliveryVector::ExploitKitLandingPage => "exploit-kit-landing-page",
-            DeliveryVector::FakeWebsite => "fake-website",
-            DeliveryVector::JanitorAttack => "janitor-attack",
-            DeliveryVector::MaliciousIframes => "malicious-iframes",
-            DeliveryVector::Malvertising => "malvertising",
-            DeliveryVector::MediaBaiting => "media-baiting",
-            DeliveryVector::Pharming => "pharming",
-            DeliveryVector::Phishing => "phishing",
-            DeliveryVector::TrojanizedLink => "trojanized-link",
-            DeliveryVector::TrojanizedSoftware => "trojanized-software",
-            DeliveryVector::UsbCableSyncing => "usb-cable-syncing",
-            DeliveryVector::WateringHole => "watering-hole",
-        }
-    }
-}
-
-impl AsRef<str> for MalwareLabel {
-    fn as_ref(&self) -> &str {
-        match self {
-            MalwareLabel::Adware => "adware",
-            MalwareLabel::Appender => "appender",
-            MalwareLabel::Backdoor => "backdoor",
-            MalwareLabel::BootSectorVirus => "boot-sector-virus",
-            MalwareLabel::Bot => "bot",
-            MalwareLabel::CavityFiller => "cavity-filler",
-            MalwareLabel::Clicker => "clicker",
-            MalwareLabel::CompanionVirus => "companion-virus",
-            MalwareLabel::DataDiddler => "data-diddler",
-            MalwareLabel::Downloader => "downloader",
-            MalwareLabel::DropperFile => "dropper-file",
-            MalwareLabel::FileInfectorVirus => "file-infector-virus",
-            MalwareLabel::ForkBomb => "fork-bomb",
-            MalwareLabel::Greyware => "greyware",
-            MalwareLabel::Implant => "implant",
-            MalwareLabel::Infector => "infector",
-            MalwareLabel::JokeProgram => "joke-program",
-            MalwareLabel::Keylogger => "keylogger",
-            MalwareLabel::KleptographicWorm => "kleptographic-worm",
-            MalwareLabel::MacroVirus => "macro-virus",
-            MalwareLabel::MassMailer => "mass-mailer",
-            MalwareLabel::MetamorphicVirus => "metamorphic-virus",
-            MalwareLabel::MidInfector => "mid-infector",
-            MalwareLabel::MobileCode => "mobile-code",
-            MalwareLabel::MultipartiteVirus => "multipartite-virus",
-            MalwareLabel::ParentalControl => "parental-control",
-            MalwareLabel::PasswordStealer => "password-stealer",
-            MalwareLabel::PolymorphicVirus => "polymorphic-virus",
-            MalwareLabel::PremiumDialerOrSmser => "premium-dialer-or-smser",
-            MalwareLabel::Prepender => "prepender",
-            MalwareLabel::Ransomware => "ransomware",
-            MalwareLabel::RogueAntiMalware => "rogue-anti-malware",
-            MalwareLabel::Rootkit => "rootkit",
-            MalwareLabel::Scareware => "scareware",
-            MalwareLabel::SecurityAssessmentTool => "security-assessment-tool",
-            MalwareLabel::Shellcode => "shellcode",
-            MalwareLabel::SpaghettiPacker => "spaghetti-packer",
-            MalwareLabel::Spyware => "spyware",
-            MalwareLabel::Trackware => "trackware",
-            MalwareLabel::TrojanHorse => "trojan-horse",
-            MalwareLabel::Virus => "virus",
-            MalwareLabel::WebBug => "web-bug",
-            MalwareLabel::Wiper => "wiper",
-            MalwareLabel::Worm => "worm",
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_analysis_conclusion_serde() {
-        let conclusion = AnalysisConclusionType::Malicious;
-        let json = serde_json::to_string(&conclusion).unwrap();
-        assert_eq!(json, "\"malicious\"");
-
-        let deserialized: AnalysisConclusionType = serde_json::from_str(&json).unwrap();
-        assert_eq!(conclusion, deserialized);
-    }
-
-    #[test]
-    fn test_delivery_vector_serde() {
-        let vector = DeliveryVector::EmailAttachment;
-        let json = serde_json::to_string(&vector).unwrap();
-        assert_eq!(json, "\"email-attachment\"");
-
-        let deserialized: DeliveryVector = serde_json::from_str(&json).unwrap();
-        assert_eq!(vector, deserialized);
-    }
-
-    #[test]
-    fn test_malware_label_serde() {
-        let label = MalwareLabel::Ransomware;
-        let json = serde_json::to_string(&label).unwrap();
-        assert_eq!(json, "\"ransomware\"");
-
-        let deserialized: MalwareLabel = serde_json::from_str(&json).unwrap();
-        assert_eq!(label, deserialized);
-    }
-
-    #[test]
-    fn test_processor_arch_serde() {
-        let arch = ProcessorArchitecture::X8664;
-        let json = serde_json::to_string(&arch).unwrap();
-        assert_eq!(json, "\"x86-64\"");
-
-        let deserialized: ProcessorArchitecture = serde_json::from_str(&json).unwrap();
-        assert_eq!(arch, deserialized);
-    }
-}
+//! MAEC 5.0 Open Vocabularies
+//!
+//! This module provides type-safe enumerations for all MAEC 5.0 open vocabularies,
+//! ensuring 100% compliance with the MAEC specification.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ObjectKind;
+
+// Implements `FromStr` for a vocabulary enum, accepting exactly the
+// serialized kebab-case value of each variant
+macro_rules! impl_from_str {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl std::str::FromStr for $name {
+                type Err = crate::MaecError;
+
+                fn from_str(s: &str) -> crate::Result<Self> {
+                    serde_json::from_value(serde_json::Value::String(s.to_string())).map_err(|_| {
+                        crate::MaecError::ValidationError(format!(
+                            "unknown {} '{}'",
+                            stringify!($name),
+                            s
+                        ))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use impl_from_str;
+
+// Declares a vocabulary enum as written and generates its `all()` list, so
+// the list cannot drift from the variants
+macro_rules! vocab_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// Returns every variant in declaration order
+            pub fn all() -> &'static [Self] {
+                &[$($name::$variant,)*]
+            }
+        }
+    };
+}
+
+vocab_enum! {
+    /// Analysis conclusion types
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum AnalysisConclusionType {
+        /// The analyzed entity is benign
+        Benign,
+        /// The analyzed entity is malicious
+        Malicious,
+        /// The analyzed entity is suspicious
+        Suspicious,
+        /// The conclusion is indeterminate
+        Indeterminate,
+    }
+}
+
+vocab_enum! {
+    /// Analysis environment properties
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum AnalysisEnvironment {
+        /// Operating system property
+        OperatingSystem,
+        /// Host VM property
+        HostVm,
+        /// Installed software property
+        InstalledSoftware,
+    }
+}
+
+vocab_enum! {
+    /// Malware analysis types
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum AnalysisType {
+        /// Static analysis
+        Static,
+        /// Dynamic analysis
+        Dynamic,
+        /// Combination of static and dynamic
+        Combination,
+    }
+}
+
+vocab_enum! {
+    /// Confidence measure levels (aligned with STIX HighMediumLow vocabulary)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum ConfidenceMeasure {
+        /// Low confidence
+        Low,
+        /// Medium confidence
+        Medium,
+        /// High confidence
+        High,
+        /// No confidence
+        None,
+        /// Unknown confidence
+        Unknown,
+    }
+}
+
+vocab_enum! {
+    /// Processor architectures
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum ProcessorArchitecture {
+        /// x86 32-bit architecture
+        X86,
+        /// x86-64 (AMD64) architecture
+        #[serde(rename = "x86-64")]
+        X8664,
+        /// Intel IA-64 architecture
+        #[serde(rename = "ia-64")]
+        Ia64,
+        /// PowerPC architecture
+        PowerPc,
+        /// ARM architecture
+        Arm,
+        /// Alpha architecture
+        Alpha,
+        /// SPARC architecture
+        Sparc,
+        /// MIPS architecture
+        Mips,
+    }
+}
+
+vocab_enum! {
+    /// Binary obfuscation methods
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum ObfuscationMethod {
+        /// Packing/compression
+        Packing,
+        /// Code encryption
+        CodeEncryption,
+        /// Dead code insertion
+        DeadCodeInsertion,
+        /// Entry point obfuscation
+        EntryPointObfuscation,
+        /// Import address table obfuscation
+        ImportAddressTableObfuscation,
+        /// Interleaving code
+        InterleavingCode,
+        /// Symbolic obfuscation
+        SymbolicObfuscation,
+        /// String obfuscation
+        StringObfuscation,
+        /// Subroutine reordering
+        SubroutineReordering,
+        /// Code transposition
+        CodeTransposition,
+        /// Instruction substitution
+        InstructionSubstitution,
+        /// Register reassignment
+        RegisterReassignment,
+    }
+}
+
+// Helper macro for creating large string-based enums
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => $value:expr
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                #[serde(rename = $value)]
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// Returns every variant in declaration order
+            pub fn all() -> &'static [Self] {
+                &[$($name::$variant,)*]
+            }
+        }
+
+        impl_from_str!($name);
+    };
+}
+
+string_enum! {
+    /// Delivery/infection vectors
+    pub enum DeliveryVector {
+        ActiveAttacker => "active-attacker",
+        AutoExecutingMedia => "auto-executing-media",
+        Downloader => "downloader",
+        Dropper => "dropper",
+        EmailAttachment => "email-attachment",
+        ExploitKitLandingPage => "exploit-kit-landing-page",
+        FakeWebsite => "fake-website",
+        JanitorAttack => "janitor-attack",
+        MaliciousIframes => "malicious-iframes",
+        Malvertising => "malvertising",
+        MediaBaiting => "media-baiting",
+        Pharming => "pharming",
+        Phishing => "phishing",
+        TrojanizedLink => "trojanized-link",
+        TrojanizedSoftware => "trojanized-software",
+        UsbCableSyncing => "usb-cable-syncing",
+        WateringHole => "watering-hole",
+    }
+}
+
+string_enum! {
+    /// Common malware labels
+    pub enum MalwareLabel {
+        Adware => "adware",
+        Appender => "appender",
+        Backdoor => "backdoor",
+        BootSectorVirus => "boot-sector-virus",
+        Bot => "bot",
+        CavityFiller => "cavity-filler",
+        Clicker => "clicker",
+        CompanionVirus => "companion-virus",
+        DataDiddler => "data-diddler",
+        Downloader => "downloader",
+        DropperFile => "dropper-file",
+        FileInfectorVirus => "file-infector-virus",
+        ForkBomb => "fork-bomb",
+        Greyware => "greyware",
+        Implant => "implant",
+        Infector => "infector",
+        JokeProgram => "joke-program",
+        Keylogger => "keylogger",
+        KleptographicWorm => "kleptographic-worm",
+        MacroVirus => "macro-virus",
+        MassMailer => "mass-mailer",
+        MetamorphicVirus => "metamorphic-virus",
+        MidInfector => "mid-infector",
+        MobileCode => "mobile-code",
+        MultipartiteVirus => "multipartite-virus",
+        ParentalControl => "parental-control",
+        PasswordStealer => "password-stealer",
+        PolymorphicVirus => "polymorphic-virus",
+        PremiumDialerOrSmser => "premium-dialer-or-smser",
+        Prepender => "prepender",
+        Ransomware => "ransomware",
+        RogueAntiMalware => "rogue-anti-malware",
+        Rootkit => "rootkit",
+        Scareware => "scareware",
+        SecurityAssessmentTool => "security-assessment-tool",
+        Shellcode => "shellcode",
+        SpaghettiPacker => "spaghetti-packer",
+        Spyware => "spyware",
+        Trackware => "trackware",
+        TrojanHorse => "trojan-horse",
+        Virus => "virus",
+        WebBug => "web-bug",
+        Wiper => "wiper",
+        Worm => "worm",
+    }
+}
+
+string_enum! {
+    /// MAEC entity association types
+    pub enum EntityAssociation {
+        FileSystemEntities => "file-system-entities",
+        NetworkEntities => "network-entities",
+        ProcessEntities => "process-entities",
+        MemoryEntities => "memory-entities",
+        IpcEntities => "ipc-entities",
+        DeviceEntities => "device-entities",
+        RegistryEntities => "registry-entities",
+        ServiceEntities => "service-entities",
+        PotentialIndicators => "potential-indicators",
+        SameMalwareFamily => "same-malware-family",
+        ClusteredTogether => "clustered-together",
+        ObservedTogether => "observed-together",
+        PartOfIntrusionSet => "part-of-intrusion-set",
+        SameMalwareToolkit => "same-malware-toolkit",
+    }
+}
+
+vocab_enum! {
+    /// Relationship types defined by MAEC 5.0
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum RelationshipType {
+        /// The source was derived from the target
+        DerivedFrom,
+        /// The source is a disk image of the target
+        DiskImageOf,
+        /// The source was downloaded by the target
+        DownloadedBy,
+        /// The source was dropped by the target
+        DroppedBy,
+        /// The source was extracted from the target
+        ExtractedFrom,
+        /// The source was installed by the target
+        InstalledBy,
+        /// The source is a memory image of the target
+        MemoryImageOf,
+        /// The source is related to the target
+        RelatedTo,
+        /// The source is a variant of the target
+        VariantOf,
+    }
+}
+
+impl_from_str!(
+    AnalysisConclusionType,
+    AnalysisEnvironment,
+    AnalysisType,
+    ConfidenceMeasure,
+    ProcessorArchitecture,
+    ObfuscationMethod,
+    RelationshipType,
+);
+
+impl RelationshipType {
+    /// Looks up a relationship type by its MAEC name (e.g. `"variant-of"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+
+    /// Returns the object kinds allowed as source and as target
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::{ObjectKind, RelationshipType};
+    ///
+    /// let (sources, targets) = RelationshipType::DroppedBy.valid_endpoints();
+    /// assert_eq!(sources, &[ObjectKind::MalwareInstance]);
+    /// assert_eq!(targets, &[ObjectKind::MalwareInstance]);
+    /// ```
+    pub fn valid_endpoints(&self) -> (&'static [ObjectKind], &'static [ObjectKind]) {
+        const INSTANCE: &[ObjectKind] = &[ObjectKind::MalwareInstance];
+        const MALWARE: &[ObjectKind] = &[ObjectKind::MalwareInstance, ObjectKind::MalwareFamily];
+        const ANY: &[ObjectKind] = &[
+            ObjectKind::Behavior,
+            ObjectKind::Collection,
+            ObjectKind::MalwareAction,
+            ObjectKind::MalwareFamily,
+            ObjectKind::MalwareInstance,
+        ];
+
+        match self {
+            RelationshipType::DerivedFrom => (MALWARE, MALWARE),
+            RelationshipType::DiskImageOf
+            | RelationshipType::DownloadedBy
+            | RelationshipType::DroppedBy
+            | RelationshipType::ExtractedFrom
+            | RelationshipType::InstalledBy
+            | RelationshipType::MemoryImageOf => (INSTANCE, INSTANCE),
+            RelationshipType::RelatedTo => (ANY, ANY),
+            RelationshipType::VariantOf => (INSTANCE, MALWARE),
+        }
+    }
+}
+
+impl AsRef<str> for RelationshipType {
+    fn as_ref(&self) -> &str {
+        match self {
+            RelationshipType::DerivedFrom => "derived-from",
+            RelationshipType::DiskImageOf => "disk-image-of",
+            RelationshipType::DownloadedBy => "downloaded-by",
+            RelationshipType::DroppedBy => "dropped-by",
+            RelationshipType::ExtractedFrom => "extracted-from",
+            RelationshipType::InstalledBy => "installed-by",
+            RelationshipType::MemoryImageOf => "memory-image-of",
+            RelationshipType::RelatedTo => "related-to",
+            RelationshipType::VariantOf => "variant-of",
+        }
+    }
+}
+
+impl From<RelationshipType> for String {
+    fn from(relationship_type: RelationshipType) -> Self {
+        relationship_type.as_ref().to_string()
+    }
+}
+
+/// Allow using string slices directly for vocabularies
+impl AsRef<str> for DeliveryVector {
+    fn as_ref(&self) -> &str {
+        match self {
+            DeliveryVector::ActiveAttacker => "active-attacker",
+            DeliveryVector::AutoExecutingMedia => "auto-executing-media",
+            DeliveryVector::Downloader => "downloader",
+            DeliveryVector::Dropper => "dropper",
+            DeliveryVector::EmailAttachment => "email-attachment",
+            DeliveryVector::ExploitKitLandingPage => "exploit-kit-landing-page",
+            DeliveryVector::FakeWebsite => "fake-website",
+            DeliveryVector::JanitorAttack => "janitor-attack",
+            DeliveryVector::MaliciousIframes => "malicious-iframes",
+            DeliveryVector::Malvertising => "malvertising",
+            DeliveryVector::MediaBaiting => "media-baiting",
+            DeliveryVector::Pharming => "pharming",
+            DeliveryVector::Phishing => "phishing",
+            DeliveryVector::TrojanizedLink => "trojanized-link",
+            DeliveryVector::TrojanizedSoftware => "trojanized-software",
+            DeliveryVector::UsbCableSyncing => "usb-cable-syncing",
+            DeliveryVector::WateringHole => "watering-hole",
+        }
+    }
+}
+
+impl AsRef<str> for MalwareLabel {
+    fn as_ref(&self) -> &str {
+        match self {
+            MalwareLabel::Adware => "adware",
+            MalwareLabel::Appender => "appender",
+            MalwareLabel::Backdoor => "backdoor",
+            MalwareLabel::BootSectorVirus => "boot-sector-virus",
+            MalwareLabel::Bot => "bot",
+            MalwareLabel::CavityFiller => "cavity-filler",
+            MalwareLabel::Clicker => "clicker",
+            MalwareLabel::CompanionVirus => "companion-virus",
+            MalwareLabel::DataDiddler => "data-diddler",
+            MalwareLabel::Downloader => "downloader",
+            MalwareLabel::DropperFile => "dropper-file",
+            MalwareLabel::FileInfectorVirus => "file-infector-virus",
+            MalwareLabel::ForkBomb => "fork-bomb",
+            MalwareLabel::Greyware => "greyware",
+            MalwareLabel::Implant => "implant",
+            MalwareLabel::Infector => "infector",
+            MalwareLabel::JokeProgram => "joke-program",
+            MalwareLabel::Keylogger => "keylogger",
+            MalwareLabel::KleptographicWorm => "kleptographic-worm",
+            MalwareLabel::MacroVirus => "macro-virus",
+            MalwareLabel::MassMailer => "mass-mailer",
+            MalwareLabel::MetamorphicVirus => "metamorphic-virus",
+            MalwareLabel::MidInfector => "mid-infector",
+            MalwareLabel::MobileCode => "mobile-code",
+            MalwareLabel::MultipartiteVirus => "multipartite-virus",
+            MalwareLabel::ParentalControl => "parental-control",
+            MalwareLabel::PasswordStealer => "password-stealer",
+            MalwareLabel::PolymorphicVirus => "polymorphic-virus",
+            MalwareLabel::PremiumDialerOrSmser => "premium-dialer-or-smser",
+            MalwareLabel::Prepender => "prepender",
+            MalwareLabel::Ransomware => "ransomware",
+            MalwareLabel::RogueAntiMalware => "rogue-anti-malware",
+            MalwareLabel::Rootkit => "rootkit",
+            MalwareLabel::Scareware => "scareware",
+            MalwareLabel::SecurityAssessmentTool => "security-assessment-tool",
+            MalwareLabel::Shellcode => "shellcode",
+            MalwareLabel::SpaghettiPacker => "spaghetti-packer",
+            MalwareLabel::Spyware => "spyware",
+            MalwareLabel::Trackware => "trackware",
+            MalwareLabel::TrojanHorse => "trojan-horse",
+            MalwareLabel::Virus => "virus",
+            MalwareLabel::WebBug => "web-bug",
+            MalwareLabel::Wiper => "wiper",
+            MalwareLabel::Worm => "worm",
+        }
+    }
+}
+
+/// Derives malware labels from an antivirus detection name
+///
+/// The name is split on non-alphanumeric characters (so `.`, `/`, `:`, `-`,
+/// `!` and friends all separate tokens) and each token is matched,
+/// case-insensitively, against common AV abbreviations and the MAEC label
+/// names. Unknown tokens such as platforms and family names are ignored. Each
+/// label appears once, in the order it was first found.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{labels_from_detection_name, MalwareLabel};
+///
+/// assert_eq!(
+///     labels_from_detection_name("Trojan.Ransom.WannaCry"),
+///     vec![MalwareLabel::TrojanHorse, MalwareLabel::Ransomware]
+/// );
+/// assert_eq!(
+///     labels_from_detection_name("Backdoor:Win32/Bladabindi!ml"),
+///     vec![MalwareLabel::Backdoor]
+/// );
+/// ```
+pub fn labels_from_detection_name(name: &str) -> Vec<MalwareLabel> {
+    let mut labels = vec![];
+    for token in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let token = token.to_ascii_lowercase();
+        let label = match token.as_str() {
+            "" => continue,
+            "trojan" | "troj" => MalwareLabel::TrojanHorse,
+            "ransom" | "ransomware" | "filecoder" | "cryptor" => MalwareLabel::Ransomware,
+            "bkdr" => MalwareLabel::Backdoor,
+            "dldr" | "trojandownloader" => MalwareLabel::Downloader,
+            "dropper" | "trojandropper" => MalwareLabel::DropperFile,
+            "keylog" => MalwareLabel::Keylogger,
+            "spy" | "trojanspy" => MalwareLabel::Spyware,
+            "pws" | "psw" | "stealer" | "passwordstealer" => MalwareLabel::PasswordStealer,
+            "fakeav" | "rogue" => MalwareLabel::RogueAntiMalware,
+            "joke" => MalwareLabel::JokeProgram,
+            "infector" => MalwareLabel::Infector,
+            other => match other.parse::<MalwareLabel>() {
+                Ok(label) => label,
+                Err(_) => continue,
+            },
+        };
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
+}
+
+/// A value from an open vocabulary
+///
+/// MAEC vocabularies are open: producers may use values outside the
+/// specification's list. Known values deserialize to `Known`; anything else is
+/// preserved verbatim as `Custom`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OpenVocab<T> {
+    /// A value defined by the MAEC specification
+    Known(T),
+    /// A value outside the specification's vocabulary
+    Custom(String),
+}
+
+impl<T> OpenVocab<T> {
+    /// Returns whether the value is defined by the MAEC specification
+    pub fn is_known(&self) -> bool {
+        matches!(self, OpenVocab::Known(_))
+    }
+
+    /// Returns the specification value, if this is one
+    pub fn known(&self) -> Option<&T> {
+        match self {
+            OpenVocab::Known(value) => Some(value),
+            OpenVocab::Custom(_) => None,
+        }
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> OpenVocab<T> {
+    /// Parses a vocabulary value, keeping unknown values as `Custom`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use maec::{BehaviorVocab, OpenVocab};
+    ///
+    /// assert_eq!(OpenVocab::parse("send-beacon"), BehaviorVocab::SendBeacon);
+    /// assert!(!OpenVocab::<BehaviorVocab>::parse("mine-crypto").is_known());
+    /// ```
+    pub fn parse(value: &str) -> Self {
+        match serde_json::from_value(serde_json::Value::String(value.to_string())) {
+            Ok(known) => OpenVocab::Known(known),
+            Err(_) => OpenVocab::Custom(value.to_string()),
+        }
+    }
+}
+
+impl<T> From<T> for OpenVocab<T> {
+    fn from(value: T) -> Self {
+        OpenVocab::Known(value)
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for OpenVocab<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.known() == Some(other)
+    }
+}
+
+impl<T: AsRef<str>> AsRef<str> for OpenVocab<T> {
+    fn as_ref(&self) -> &str {
+        match self {
+            OpenVocab::Known(value) => value.as_ref(),
+            OpenVocab::Custom(value) => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_variants() {
+        assert_eq!(MalwareLabel::all()[0], MalwareLabel::Adware);
+        for label in MalwareLabel::all() {
+            assert_eq!(&label.as_ref().parse::<MalwareLabel>().unwrap(), label);
+        }
+        for capability in crate::CapabilityVocab::all() {
+            assert_eq!(
+                &capability
+                    .as_ref()
+                    .parse::<crate::CapabilityVocab>()
+                    .unwrap(),
+                capability
+            );
+        }
+        assert_eq!(RelationshipType::all().len(), 9);
+        assert_eq!(
+            ProcessorArchitecture::all()[1],
+            ProcessorArchitecture::X8664
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "email-attachment".parse::<DeliveryVector>().unwrap(),
+            DeliveryVector::EmailAttachment
+        );
+        assert_eq!(
+            "code-encryption".parse::<ObfuscationMethod>().unwrap(),
+            ObfuscationMethod::CodeEncryption
+        );
+        assert_eq!(
+            "variant-of".parse::<RelationshipType>().unwrap(),
+            RelationshipType::VariantOf
+        );
+        assert_eq!(
+            "anti-behavioral-analysis"
+                .parse::<crate::CapabilityVocab>()
+                .unwrap(),
+            crate::CapabilityVocab::AntiBehavioralAnalysis
+        );
+
+        assert!(matches!(
+            "Email-Attachment".parse::<DeliveryVector>(),
+            Err(crate::MaecError::ValidationError(_))
+        ));
+        assert!("anti-behavioral-anlysis"
+            .parse::<crate::CapabilityVocab>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_open_vocab_serde() {
+        use crate::vocab_large::Behavior;
+
+        let known: OpenVocab<Behavior> = serde_json::from_str("\"send-beacon\"").unwrap();
+        assert_eq!(known, OpenVocab::Known(Behavior::SendBeacon));
+        assert_eq!(known, Behavior::SendBeacon);
+
+        let custom: OpenVocab<Behavior> = serde_json::from_str("\"mine-crypto\"").unwrap();
+        assert_eq!(custom, OpenVocab::Custom("mine-crypto".to_string()));
+        assert_eq!(custom.as_ref(), "mine-crypto");
+        assert_eq!(serde_json::to_string(&custom).unwrap(), "\"mine-crypto\"");
+    }
+
+    #[test]
+    fn test_analysis_conclusion_serde() {
+        let conclusion = AnalysisConclusionType::Malicious;
+        let json = serde_json::to_string(&conclusion).unwrap();
+        assert_eq!(json, "\"malicious\"");
+
+        let deserialized: AnalysisConclusionType = serde_json::from_str(&json).unwrap();
+        assert_eq!(conclusion, deserialized);
+    }
+
+    #[test]
+    fn test_relationship_type_names() {
+        let rel_type = RelationshipType::from_name("variant-of").unwrap();
+        assert_eq!(rel_type, RelationshipType::VariantOf);
+        assert_eq!(rel_type.as_ref(), "variant-of");
+        assert_eq!(RelationshipType::from_name("exhibits"), None);
+    }
+
+    #[test]
+    fn test_delivery_vector_serde() {
+        let vector = DeliveryVector::EmailAttachment;
+        let json = serde_json::to_string(&vector).unwrap();
+        assert_eq!(json, "\"email-attachment\"");
+
+        let deserialized: DeliveryVector = serde_json::from_str(&json).unwrap();
+        assert_eq!(vector, deserialized);
+    }
+
+    #[test]
+    fn test_malware_label_serde() {
+        let label = MalwareLabel::Ransomware;
+        let json = serde_json::to_string(&label).unwrap();
+        assert_eq!(json, "\"ransomware\"");
+
+        let deserialized: MalwareLabel = serde_json::from_str(&json).unwrap();
+        assert_eq!(label, deserialized);
+    }
+
+    #[test]
+    fn test_labels_from_detection_name() {
+        assert_eq!(
+            labels_from_detection_name("Trojan-Ransom.Win32.Wanna.m"),
+            vec![MalwareLabel::TrojanHorse, MalwareLabel::Ransomware]
+        );
+        assert_eq!(
+            labels_from_detection_name("W32/Worm.Downloader!tr"),
+            vec![MalwareLabel::Worm, MalwareLabel::Downloader]
+        );
+        assert_eq!(
+            labels_from_detection_name("PWS:Win32/Zbot.gen!Y"),
+            vec![MalwareLabel::PasswordStealer]
+        );
+        assert_eq!(
+            labels_from_detection_name("TROJ_SPY.TROJ.gen"),
+            vec![MalwareLabel::TrojanHorse, MalwareLabel::Spyware]
+        );
+        assert!(labels_from_detection_name("Win32.Generic.12345").is_empty());
+        assert!(labels_from_detection_name("").is_empty());
+    }
+
+    #[test]
+    fn test_processor_arch_serde() {
+        let arch = ProcessorArchitecture::X8664;
+        let json = serde_json::to_string(&arch).unwrap();
+        assert_eq!(json, "\"x86-64\"");
+
+        let deserialized: ProcessorArchitecture = serde_json::from_str(&json).unwrap();
+        assert_eq!(arch, deserialized);
+    }
+}
@@ -0,0 +1,150 @@
+//! Streaming output of MAEC packages
+//!
+//! [`PackageWriter`] writes a package to any [`Write`] sink one object at a
+//! time, so very large packages never have to be held in memory in full.
+
+use std::io::Write;
+
+use crate::error::Result;
+use crate::{MaecObjectType, Package};
+
+/// Writes a package as JSON, streaming objects as they are added
+///
+/// The package envelope (every property except `maec_objects`) is written
+/// when the writer is created, each [`add_object`](Self::add_object) call
+/// appends one element to the `maec_objects` array, and
+/// [`finish`](Self::finish) closes the document. The output is only valid
+/// JSON once `finish` has been called.
+///
+/// # Examples
+///
+/// ```
+/// use maec::{MaecObjectType, MalwareFamily, Package, PackageWriter};
+///
+/// let mut writer = PackageWriter::new(Vec::new()).unwrap();
+/// writer
+///     .add_object(&MaecObjectType::MalwareFamily(MalwareFamily::new("WannaCry")))
+///     .unwrap();
+/// let output = writer.finish().unwrap();
+///
+/// let package = Package::from_json(std::str::from_utf8(&output).unwrap()).unwrap();
+/// assert_eq!(package.maec_objects.len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct PackageWriter<W: Write> {
+    writer: W,
+    objects_written: usize,
+}
+
+impl<W: Write> PackageWriter<W> {
+    /// Starts writing a new, empty package
+    pub fn new(writer: W) -> Result<Self> {
+        Self::with_envelope(writer, &Package::new())
+    }
+
+    /// Starts writing a package with the properties of `envelope`
+    ///
+    /// The envelope is validated first. Its ID, timestamps, custom properties,
+    /// observable objects and relationships are written immediately; any
+    /// objects it already contains are written as the first elements of
+    /// `maec_objects`.
+    pub fn with_envelope(writer: W, envelope: &Package) -> Result<Self> {
+        envelope.validate()?;
+
+        let mut value = serde_json::to_value(envelope)?;
+        let properties = value
+            .as_object_mut()
+            .expect("a package serializes to a JSON object");
+        properties.remove("maec_objects");
+
+        let mut this = Self {
+            writer,
+            objects_written: 0,
+        };
+        this.writer.write_all(b"{")?;
+        for (key, value) in properties.iter() {
+            serde_json::to_writer(&mut this.writer, key)?;
+            this.writer.write_all(b":")?;
+            serde_json::to_writer(&mut this.writer, value)?;
+            this.writer.write_all(b",")?;
+        }
+        this.writer.write_all(b"\"maec_objects\":[")?;
+
+        for object in &envelope.maec_objects {
+            this.add_object(object)?;
+        }
+        Ok(this)
+    }
+
+    /// Validates an object and appends it to `maec_objects`
+    pub fn add_object(&mut self, object: &MaecObjectType) -> Result<()> {
+        object.validate()?;
+        if self.objects_written > 0 {
+            self.writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut self.writer, object)?;
+        self.objects_written += 1;
+        Ok(())
+    }
+
+    /// Returns the number of objects written so far
+    pub fn objects_written(&self) -> usize {
+        self.objects_written
+    }
+
+    /// Closes the package document, flushes and returns the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.write_all(b"]}")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Behavior, BehaviorVocab, MalwareFamily, Relationship};
+
+    #[test]
+    fn test_streamed_package_matches_envelope() {
+        let family = MalwareFamily::new("WannaCry");
+        let behavior = Behavior::new(BehaviorVocab::EncryptFiles);
+        let envelope = Package::builder()
+            .title("Streamed")
+            .add_malware_family(family.clone())
+            .add_relationship(Relationship::new(
+                &family.common.id,
+                "related-to",
+                &behavior.common.id,
+            ))
+            .build()
+            .unwrap();
+
+        let mut writer = PackageWriter::with_envelope(Vec::new(), &envelope).unwrap();
+        writer
+            .add_object(&MaecObjectType::Behavior(behavior.clone()))
+            .unwrap();
+        assert_eq!(writer.objects_written(), 2);
+
+        let mut invalid = behavior;
+        invalid.common.id = "not-an-id".to_string();
+        assert!(writer
+            .add_object(&MaecObjectType::Behavior(invalid))
+            .is_err());
+
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let package = Package::from_json(&output).unwrap();
+        assert_eq!(package.common, envelope.common);
+        assert_eq!(package.title(), Some("Streamed"));
+        assert_eq!(package.relationships, envelope.relationships);
+        assert_eq!(package.maec_objects.len(), 2);
+        assert_eq!(package.behaviors().len(), 1);
+    }
+
+    #[test]
+    fn test_empty_package() {
+        let output = PackageWriter::new(Vec::new()).unwrap().finish().unwrap();
+        let package: Package = serde_json::from_slice(&output).unwrap();
+        assert!(package.maec_objects.is_empty());
+    }
+}
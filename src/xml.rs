@@ -0,0 +1,322 @@
+//! XML encoding of MAEC content
+//!
+//! MAEC 5.0 is defined in terms of JSON, and its flattened custom properties
+//! and untagged object unions do not map onto quick-xml's serde support. XML is
+//! therefore produced from, and parsed back into, the JSON data model:
+//!
+//! - a JSON object becomes an element with one child element per property
+//! - a string becomes an element holding the text
+//! - other values are marked with a `json` attribute: arrays
+//!   (`json="array"`, one `<item>` child per element), numbers
+//!   (`json="number"`), booleans (`json="boolean"`), `null` (`json="null"`)
+//!   and empty objects (`json="object"`)
+//! - properties whose names are not valid XML names (such as the numeric keys
+//!   of `observable_objects`) are written as `<entry key="...">`
+//!
+//! Documents using this representation round-trip losslessly. When reading,
+//! unmarked elements without children are strings and repeated unmarked
+//! child elements are collected into an array.
+//!
+//! This module is enabled by the `xml` feature.
+
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use serde_json::{Map, Number, Value};
+
+use crate::error::{MaecError, Result};
+
+/// Attribute marking the JSON kind of non-string, non-object values
+const KIND_ATTRIBUTE: &str = "json";
+
+/// Element name used for array items
+const ITEM_ELEMENT: &str = "item";
+
+/// Element name used for properties whose names are not valid XML names
+const ENTRY_ELEMENT: &str = "entry";
+
+/// Attribute holding the property name of an `entry` element
+const KEY_ATTRIBUTE: &str = "key";
+
+/// Writes a JSON value as an XML document with the given root element
+pub(crate) fn to_string(root: &str, value: &Value) -> Result<String> {
+    let mut writer = Writer::new(Vec::new());
+    write_element(&mut writer, root, value)?;
+    String::from_utf8(writer.into_inner())
+        .map_err(|e| MaecError::XmlSerializationError(e.to_string()))
+}
+
+fn write_element(writer: &mut Writer<Vec<u8>>, key: &str, value: &Value) -> Result<()> {
+    let mut start = if is_xml_name(key) {
+        BytesStart::new(key)
+    } else {
+        let mut start = BytesStart::new(ENTRY_ELEMENT);
+        start.push_attribute((KEY_ATTRIBUTE, key));
+        start
+    };
+    let end = BytesEnd::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+
+    match value {
+        Value::Null => {
+            start.push_attribute((KIND_ATTRIBUTE, "null"));
+            write_event(writer, Event::Empty(start))
+        }
+        Value::Bool(b) => {
+            start.push_attribute((KIND_ATTRIBUTE, "boolean"));
+            write_text_element(writer, start, end, &b.to_string())
+        }
+        Value::Number(n) => {
+            start.push_attribute((KIND_ATTRIBUTE, "number"));
+            write_text_element(writer, start, end, &n.to_string())
+        }
+        Value::String(s) => write_text_element(writer, start, end, s),
+        Value::Array(items) => {
+            start.push_attribute((KIND_ATTRIBUTE, "array"));
+            if items.is_empty() {
+                return write_event(writer, Event::Empty(start));
+            }
+            write_event(writer, Event::Start(start))?;
+            for item in items {
+                write_element(writer, ITEM_ELEMENT, item)?;
+            }
+            write_event(writer, Event::End(end))
+        }
+        Value::Object(map) => {
+            if map.is_empty() {
+                start.push_attribute((KIND_ATTRIBUTE, "object"));
+                return write_event(writer, Event::Empty(start));
+            }
+            write_event(writer, Event::Start(start))?;
+            for (key, item) in map {
+                write_element(writer, key, item)?;
+            }
+            write_event(writer, Event::End(end))
+        }
+    }
+}
+
+fn write_text_element(
+    writer: &mut Writer<Vec<u8>>,
+    start: BytesStart,
+    end: BytesEnd,
+    text: &str,
+) -> Result<()> {
+    write_event(writer, Event::Start(start))?;
+    write_event(writer, Event::Text(BytesText::new(text)))?;
+    write_event(writer, Event::End(end))
+}
+
+fn write_event(writer: &mut Writer<Vec<u8>>, event: Event) -> Result<()> {
+    writer
+        .write_event(event)
+        .map_err(|e| MaecError::XmlSerializationError(e.to_string()))
+}
+
+/// Returns whether a property name can be used directly as an element name
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    valid_start
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !name.to_ascii_lowercase().starts_with("xml")
+        && name != ENTRY_ELEMENT
+}
+
+/// An element whose children are still being read
+struct OpenElement {
+    key: String,
+    kind: Option<String>,
+    children: Vec<(String, Value)>,
+    text: String,
+}
+
+impl OpenElement {
+    fn from_start(start: &BytesStart) -> Result<Self> {
+        let name = String::from_utf8(start.name().as_ref().to_vec())
+            .map_err(|e| MaecError::XmlError(e.to_string()))?;
+
+        let mut key = None;
+        let mut kind = None;
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(|e| MaecError::XmlError(e.to_string()))?;
+            let value = attribute
+                .unescape_value()
+                .map_err(|e| MaecError::XmlError(e.to_string()))?
+                .into_owned();
+            match attribute.key.as_ref() {
+                k if k == KEY_ATTRIBUTE.as_bytes() && name == ENTRY_ELEMENT => key = Some(value),
+                k if k == KIND_ATTRIBUTE.as_bytes() => kind = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            key: key.unwrap_or(name),
+            kind,
+            children: vec![],
+            text: String::new(),
+        })
+    }
+
+    fn into_value(self) -> Result<Value> {
+        let invalid = |kind: &str, text: &str| {
+            MaecError::XmlError(format!(
+                "element '{}' holds an invalid {}: '{}'",
+                self.key, kind, text
+            ))
+        };
+
+        match self.kind.as_deref() {
+            Some("null") => Ok(Value::Null),
+            Some("boolean") => match self.text.trim() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                other => Err(invalid("boolean", other)),
+            },
+            Some("number") => serde_json::from_str::<Number>(self.text.trim())
+                .map(Value::Number)
+                .map_err(|_| invalid("number", &self.text)),
+            Some("array") => Ok(Value::Array(
+                self.children.into_iter().map(|(_, value)| value).collect(),
+            )),
+            Some("object") => Ok(Value::Object(collect_object(self.children))),
+            Some(other) => Err(MaecError::XmlError(format!(
+                "element '{}' has unknown json kind '{}'",
+                self.key, other
+            ))),
+            None if self.children.is_empty() => Ok(Value::String(self.text)),
+            None => Ok(Value::Object(collect_object(self.children))),
+        }
+    }
+}
+
+/// Builds an object from child elements, gathering repeated names into arrays
+fn collect_object(children: Vec<(String, Value)>) -> Map<String, Value> {
+    let mut map = Map::new();
+    for (key, value) in children {
+        match map.get_mut(&key) {
+            Some(Value::Array(items)) => items.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                map.insert(key, value);
+            }
+        }
+    }
+    map
+}
+
+/// Parses an XML document into a JSON value, returning the root element name too
+pub(crate) fn from_str(xml: &str) -> Result<(String, Value)> {
+    let mut reader = Reader::from_str(xml);
+
+    let mut stack: Vec<OpenElement> = vec![];
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| MaecError::XmlError(e.to_string()))?;
+        match event {
+            Event::Start(start) => stack.push(OpenElement::from_start(&start)?),
+            Event::Empty(start) => {
+                let element = OpenElement::from_start(&start)?;
+                if let Some(root) = close_element(&mut stack, element)? {
+                    return Ok(root);
+                }
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| MaecError::XmlError(e.to_string()))?;
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .ok_or_else(|| MaecError::XmlError("unexpected closing tag".to_string()))?;
+                if let Some(root) = close_element(&mut stack, element)? {
+                    return Ok(root);
+                }
+            }
+            Event::Eof => {
+                return Err(MaecError::XmlError(
+                    "document ended before the root element was closed".to_string(),
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Attaches a finished element to its parent, or returns it if it is the root
+fn close_element(
+    stack: &mut [OpenElement],
+    element: OpenElement,
+) -> Result<Option<(String, Value)>> {
+    let key = element.key.clone();
+    let value = element.into_value()?;
+    Ok(match stack.last_mut() {
+        Some(parent) => {
+            parent.children.push((key, value));
+            None
+        }
+        None => Some((key, value)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_mapping_roundtrip() {
+        let value = serde_json::json!({
+            "id": "package--1",
+            "note": " a < b & c ",
+            "empty": "",
+            "labels": ["worm"],
+            "none": [],
+            "size": 4096,
+            "ratio": 0.5,
+            "automated": true,
+            "missing": null,
+            "attributes": {},
+            "observables": {"0": {"type": "file"}, "entry": "x"},
+            "nested": [[1, 2], {"value": "x"}]
+        });
+
+        let xml = to_string("package", &value).unwrap();
+        assert!(xml.contains("<labels json=\"array\"><item>worm</item></labels>"));
+        assert!(xml.contains("<entry key=\"0\"><type>file</type></entry>"));
+        assert!(xml.contains(" a &lt; b &amp; c "));
+
+        let (root, parsed) = from_str(&xml).unwrap();
+        assert_eq!(root, "package");
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_untyped_repeated_elements() {
+        let xml = "<package>\n  <labels>worm</labels>\n  <labels>bot</labels>\n</package>";
+        let (_, parsed) = from_str(xml).unwrap();
+        assert_eq!(parsed, serde_json::json!({"labels": ["worm", "bot"]}));
+    }
+
+    #[test]
+    fn test_malformed_xml() {
+        assert!(matches!(
+            from_str("<package><id>1</id>"),
+            Err(MaecError::XmlError(_))
+        ));
+        assert!(matches!(
+            from_str("<package><size json=\"number\">big</size></package>"),
+            Err(MaecError::XmlError(_))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
 use maec::{Behavior, MalwareFamily, Name, Package};
+#[cfg(feature = "xml")]
+use std::collections::BTreeMap;
 
 #[test]
 fn json_roundtrip() {
@@ -29,15 +31,180 @@ fn json_roundtrip() {
     assert_eq!(pkg, from_json);
 }
 
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_roundtrip() {
+    let behavior = Behavior::builder()
+        .name(maec::vocab_large::Behavior::CheckForPayload)
+        .description("Test behavior")
+        .build()
+        .unwrap();
+
+    let family = MalwareFamily::builder()
+        .name(Name::new("TestMalware"))
+        .description("Test malware family")
+        .build()
+        .unwrap();
+
+    let pkg = Package::builder()
+        .add_malware_family(family)
+        .add_behavior(behavior)
+        .build()
+        .unwrap();
+
+    let yaml = pkg.to_yaml().unwrap();
+    let from_yaml = Package::from_yaml(&yaml).unwrap();
+    assert_eq!(pkg, from_yaml);
+    assert_eq!(
+        serde_json::to_value(&from_yaml).unwrap(),
+        serde_json::to_value(&pkg).unwrap()
+    );
+}
+
+#[cfg(feature = "xml")]
 #[test]
-#[ignore] // XML serialization has limitations with complex nested structures in quick-xml
 fn xml_roundtrip() {
-    // Note: MAEC 5.0 primarily uses JSON serialization.
-    // XML support is provided but has limitations with nested enums.
-    // For production use, JSON is the recommended format.
+    let behavior = Behavior::builder()
+        .name(maec::vocab_large::Behavior::CheckForPayload)
+        .description("Test behavior")
+        .build()
+        .unwrap();
+
+    let family = MalwareFamily::builder()
+        .name(Name::new("TestMalware"))
+        .add_label("ransomware")
+        .add_common_behavior_ref(behavior.common.id.clone())
+        .build()
+        .unwrap();
+
+    let relationship =
+        maec::Relationship::new(&family.common.id, "related-to", &behavior.common.id);
+
+    let mut observables = BTreeMap::new();
+    observables.insert(
+        "0".to_string(),
+        serde_json::json!({"type": "file", "name": "a.exe", "size": 4096}),
+    );
+
+    let mut pkg = Package::builder()
+        .add_malware_family(family)
+        .add_behavior(behavior)
+        .add_relationship(relationship)
+        .build()
+        .unwrap();
+    pkg.observable_objects = Some(observables);
+
+    let xml = pkg.to_xml().unwrap();
+    let from_xml = Package::from_xml(&xml).unwrap();
+    assert_eq!(pkg, from_xml);
+}
+
+#[test]
+fn map_keys_serialize_in_sorted_order() {
+    let keys = ["x_zulu", "x_alpha", "x_mike", "x_bravo", "x_yankee"];
+    let base = Behavior::builder()
+        .name(maec::vocab_large::Behavior::SendBeacon)
+        .build()
+        .unwrap();
+
+    let serialized: Vec<String> = (0..4)
+        .map(|round| {
+            let mut behavior = base.clone();
+            let mut ordered = keys.to_vec();
+            ordered.rotate_left(round);
+            for key in &ordered {
+                behavior
+                    .common
+                    .custom_properties
+                    .insert(key.to_string(), serde_json::json!(key));
+                behavior
+                    .attributes
+                    .get_or_insert_with(Default::default)
+                    .insert(key.to_string(), serde_json::json!(1));
+            }
+            serde_json::to_string(&behavior).unwrap()
+        })
+        .collect();
+
+    assert!(serialized.windows(2).all(|pair| pair[0] == pair[1]));
+    let json = &serialized[0];
+    let positions: Vec<usize> = ["\"x_alpha\"", "\"x_bravo\"", "\"x_mike\"", "\"x_yankee\""]
+        .iter()
+        .map(|key| json.find(key).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn camel_case_ingestion() {
+    let json = r#"{
+        "type": "package",
+        "id": "package--550e8400-e29b-41d4-a716-446655440000",
+        "schemaVersion": "5.0",
+        "createdByRef": "identity--550e8400-e29b-41d4-a716-446655440009",
+        "maecObjects": [
+            {
+                "type": "behavior",
+                "id": "behavior--550e8400-e29b-41d4-a716-446655440001",
+                "name": "send-beacon",
+                "actionRefs": ["malware-action--550e8400-e29b-41d4-a716-446655440002"],
+                "techniqueRefs": [{"sourceName": "mitre-attack", "externalId": "T1071"}]
+            },
+            {
+                "type": "malware-action",
+                "id": "malware-action--550e8400-e29b-41d4-a716-446655440002",
+                "name": "create-socket",
+                "outputObjectRefs": ["0"]
+            },
+            {
+                "type": "malware-instance",
+                "id": "malware-instance--550e8400-e29b-41d4-a716-446655440003",
+                "instanceObjectRefs": ["0"],
+                "fieldData": {"firstSeen": "2017-05-12T07:44:00Z"},
+                "analysisMetadata": [{"isAutomated": true, "analysisType": "dynamic"}]
+            }
+        ],
+        "observableObjects": {"0": {"type": "file", "name": "beacon.exe"}},
+        "relationships": [
+            {
+                "type": "relationship",
+                "id": "relationship--550e8400-e29b-41d4-a716-446655440004",
+                "sourceRef": "malware-instance--550e8400-e29b-41d4-a716-446655440003",
+                "targetRef": "behavior--550e8400-e29b-41d4-a716-446655440001",
+                "relationshipType": "related-to"
+            }
+        ]
+    }"#;
 
-    let pkg = Package::builder().build().unwrap();
+    let package = Package::from_json(json).unwrap();
+    assert!(package.common.custom_properties.is_empty());
+    assert_eq!(package.maec_objects.len(), 3);
+    assert_eq!(
+        package.behaviors()[0].technique_refs[0]
+            .external_id
+            .as_deref(),
+        Some("T1071")
+    );
+    assert_eq!(package.relationships[0].relationship_type, "related-to");
 
-    // This test is ignored due to quick-xml limitations
-    let _xml = quick_xml::se::to_string(&pkg);
+    let output = package.to_json().unwrap();
+    for key in [
+        "schema_version",
+        "created_by_ref",
+        "maec_objects",
+        "action_refs",
+        "source_name",
+        "external_id",
+        "output_object_refs",
+        "instance_object_refs",
+        "first_seen",
+        "is_automated",
+        "observable_objects",
+        "source_ref",
+        "relationship_type",
+    ] {
+        assert!(output.contains(&format!("\"{}\"", key)), "missing {}", key);
+    }
+    assert!(!output.contains("Ref"));
+    assert_eq!(Package::from_json(&output).unwrap(), package);
 }